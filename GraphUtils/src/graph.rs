@@ -1,4 +1,5 @@
-use matrix::Matrix;
+use graph_primitives::matrix::Matrix;
+use graph_primitives::graph::{Weight, WeightedGraph};
 use graphviz::GraphVizDiGraph;
 
 use regex::Regex;
@@ -47,6 +48,24 @@ impl<T> Graph<T>
 
 }
 
+// lets the shortest circuit and path searches run on these graphs, with an
+// edge wherever the weight isn't the default
+impl<T> WeightedGraph for Graph<T>
+    where T : Weight + Default {
+        type Weight = T;
+
+        fn nodes(&self) -> usize {
+            self.nodes
+        }
+
+        fn edge(&self, from : usize, to : usize) -> Option<T> {
+            match self.graph.get(from, to) {
+                Some(&weight) if from != to && weight != T::default() => Some(weight),
+                _ => None
+            }
+        }
+}
+
 
 impl<T> Graph<T>
     where T : Default + FromStr + PartialEq + PartialOrd + Clone {
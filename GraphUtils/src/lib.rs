@@ -1,7 +1,38 @@
+extern crate regex;
+extern crate graph_primitives;
+#[cfg(test)]
+extern crate hamiltonian_paths;
+
+pub mod graph;
+pub mod graphviz;
+
 #[cfg(test)]
 mod tests {
+    use graph::Graph;
+    use graph_primitives::graph::WeightedGraph;
+    use hamiltonian_paths::exact::{branch_and_bound, held_karp};
+    use hamiltonian_paths::heuristics::{nearest_neighbour, two_opt};
+    use hamiltonian_paths::paths::shortest_path;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn finds_shortest_circuits_and_paths() {
+        // a ring of cheap edges, with dearer ones across it
+        let mut graph : Graph<i32> = Graph::new(4);
+        for &(from, to, weight) in &[(0, 1, 1), (1, 2, 2), (2, 3, 1), (3, 0, 2), (0, 2, 5), (1, 3, 5)] {
+            graph.add_edge(from, to, weight);
+            graph.add_edge(to, from, weight);
+        }
+        assert!(graph.is_symmetric());
+        assert_eq!(held_karp(&graph).map(|(length, _)| length), Some(6));
+        assert_eq!(branch_and_bound(&graph).map(|(length, _)| length), Some(6));
+
+        let (_, circuit) = nearest_neighbour(&graph).unwrap();
+        assert_eq!(two_opt(&graph, &circuit).0, 6);
+        assert_eq!(shortest_path(&graph, Some(0), None), Some((4, vec![0, 1, 2, 3])));
+    }
 }
@@ -1,10 +1,8 @@
-extern crate regex;
-mod matrix;
-mod graph;
-mod graphviz;
-use matrix::Matrix;
-use graph::Graph;
-use graphviz::GraphVizDiGraph;
+extern crate graph_primitives;
+extern crate GraphUtils;
+use graph_primitives::matrix::Matrix;
+use GraphUtils::graph::Graph;
+use GraphUtils::graphviz::GraphVizDiGraph;
 use std::fs::File;
 use std::io::Write;
 
@@ -8,6 +8,9 @@
 //
 //
 // read a grid and determine whether it could possibly be valid
+//
+// Solved on top of the tic_tac_toe board in tic_tac_toe/src/validity.rs,
+// run it with `tic_tac_toe --uva < input`.
 
 // Define struct to contains object
 
@@ -0,0 +1,131 @@
+use ::std::fs::File;
+use ::std::io::{
+    Write,
+    Read,
+    Error,
+    ErrorKind
+};
+use ::std::path::Path;
+use ::std::time::Duration;
+use GA::interface::{
+    Genome,
+    SerialisableGenome
+};
+use GA::progress::TerminationState;
+
+/// Snapshot of a strategy's search state.
+///
+/// Stored on disk as plain text:
+///
+/// ```text
+/// generation <n>
+/// best <fitness> <genome>
+/// termination <elapsed nanoseconds> <best fitness seen> <stagnant generations>
+/// population <count>
+/// <genome>
+/// ...
+/// ```
+pub struct Checkpoint<T : Genome> {
+    pub generation : u32,
+    pub best : T,
+    pub best_fitness : f64,
+    pub termination : TerminationState,
+    pub population : Vec<T>
+}
+
+impl <T : Genome> Clone for Checkpoint<T> {
+    fn clone(&self) -> Checkpoint<T> {
+        Checkpoint {
+            generation : self.generation,
+            best : self.best.clone(),
+            best_fitness : self.best_fitness,
+            termination : self.termination,
+            population : self.population.iter().map(|genome| genome.clone()).collect()
+        }
+    }
+}
+
+impl <T : SerialisableGenome> Checkpoint<T> {
+    pub fn save<P : AsRef<Path>>(&self, path : P) -> Result<(), Error> {
+        let mut file = File::create(path)?;
+
+        writeln!(file, "generation {}", self.generation)?;
+        writeln!(file, "best {} {}", self.best_fitness, self.best.serialise())?;
+        writeln!(file, "termination {} {} {}",
+                 self.termination.elapsed.as_nanos(), self.termination.best_fitness, self.termination.stagnant_for)?;
+        writeln!(file, "population {}", self.population.len())?;
+        for genome in self.population.iter() {
+            writeln!(file, "{}", genome.serialise())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load<P : AsRef<Path>>(path : P) -> Result<Checkpoint<T>, Error> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+
+        let mut lines = text.lines();
+
+        let generation = field(lines.next(), "generation")?
+            .parse::<u32>()
+            .map_err(|_| invalid("bad generation count"))?;
+
+        let mut best_line = field(lines.next(), "best")?.splitn(2, ' ');
+        let best_fitness = best_line.next()
+            .and_then(|fitness| fitness.parse::<f64>().ok())
+            .ok_or_else(|| invalid("bad best fitness"))?;
+        let best = best_line.next()
+            .and_then(T::deserialise)
+            .ok_or_else(|| invalid("bad best genome"))?;
+
+        let mut termination_line = field(lines.next(), "termination")?.split(' ');
+        let elapsed = termination_line.next()
+            .and_then(|nanos| nanos.parse::<u64>().ok())
+            .ok_or_else(|| invalid("bad elapsed time"))?;
+        let seen_fitness = termination_line.next()
+            .and_then(|fitness| fitness.parse::<f64>().ok())
+            .ok_or_else(|| invalid("bad best fitness seen"))?;
+        let stagnant_for = termination_line.next()
+            .and_then(|generations| generations.parse::<u32>().ok())
+            .ok_or_else(|| invalid("bad stagnant generation count"))?;
+        let termination = TerminationState {
+            elapsed : Duration::from_nanos(elapsed),
+            best_fitness : seen_fitness,
+            stagnant_for
+        };
+
+        let count = field(lines.next(), "population")?
+            .parse::<usize>()
+            .map_err(|_| invalid("bad population size"))?;
+        let population = lines.take(count)
+            .map(|line| T::deserialise(line).ok_or_else(|| invalid("bad population genome")))
+            .collect::<Result<Vec<T>, Error>>()?;
+
+        if population.len() != count {
+            return Err(invalid("population truncated"));
+        }
+
+        Ok(Checkpoint {
+            generation,
+            best,
+            best_fitness,
+            termination,
+            population
+        })
+    }
+}
+
+fn field<'a>(line : Option<&'a str>, name : &str) -> Result<&'a str, Error> {
+    let line = line.ok_or_else(|| invalid("unexpected end of checkpoint"))?;
+
+    if line.starts_with(name) && line[name.len()..].starts_with(' ') {
+        Ok(&line[name.len() + 1..])
+    } else {
+        Err(invalid("unexpected line in checkpoint"))
+    }
+}
+
+fn invalid(message : &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -1,13 +1,24 @@
+extern crate rand;
+
+use self::rand::Rng;
+use GA::checkpoint::Checkpoint;
 
 pub trait Genome {
-    fn mutate(&mut self);
-    fn crossover(parent_a : &Self, parent_b : &Self) -> Self;
+    fn mutate<R : Rng>(&mut self, rng : &mut R);
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self;
     fn clone(&self) -> Self;
 }
 
+/// A genome that can be written to and read back from a single line of text,
+/// so runs can be checkpointed and resumed.
+pub trait SerialisableGenome : Genome + Sized {
+    fn serialise(&self) -> String;
+    fn deserialise(text : &str) -> Option<Self>;
+}
+
 pub trait Generator {
     type Genome : Genome;
-    fn create_new(&self) -> Self::Genome;
+    fn create_new<R : Rng>(&self, rng : &mut R) -> Self::Genome;
 }
 
 pub trait Evaluator {
@@ -20,4 +31,8 @@ pub trait EvolutionaryAlgorithmStrategy {
     type Generator : Generator<Genome=Self::Genome>;
     type Evaluator : Evaluator<Genome=Self::Genome>;
     fn execute(&mut self, generator : &mut Self::Generator, evaluator : &mut Self::Evaluator) -> Self::Genome;
+    /// State the strategy finished its last execution in, if it has run.
+    fn checkpoint(&self) -> Option<&Checkpoint<Self::Genome>>;
+    /// Makes the next execution continue from `checkpoint` instead of a fresh genome.
+    fn resume_from(&mut self, checkpoint : Checkpoint<Self::Genome>);
 }
@@ -1,12 +1,30 @@
+extern crate rand;
+
 pub mod interface;
 pub mod strategy;
+pub mod progress;
+pub mod checkpoint;
+
+use self::rand::{
+    SeedableRng,
+    StdRng
+};
 
 pub use self::interface::{
     Genome,
     Generator,
     Evaluator,
-    EvolutionaryAlgorithmStrategy
+    EvolutionaryAlgorithmStrategy,
+    SerialisableGenome
 };
+pub use self::progress::{
+    GenerationStats,
+    Observer,
+    Termination,
+    TerminationState,
+    Monitor
+};
+pub use self::checkpoint::Checkpoint;
 
 pub struct GeneticAlgorithm<T,G,E>
     where T : Genome,
@@ -34,5 +52,134 @@ impl <T,G,E> GeneticAlgorithm<T,G,E>
     pub fn execute_strategy(&mut self, strategy : &mut EvolutionaryAlgorithmStrategy<Genome=T, Generator=G, Evaluator=E>) -> T {
        strategy.execute(&mut self.generator, &mut self.evaluator)
     }
+
+    pub fn evaluate(&self, genome : &T) -> f64 {
+       self.evaluator.fitness(genome)
+    }
+
+    pub fn evaluator(&self) -> &E {
+       &self.evaluator
+    }
+}
+
+/// Builds the random number generator used by strategies and genome
+/// operators from a fixed seed, so that a run can be replayed exactly.
+pub fn seeded_rng(seed : usize) -> StdRng {
+    StdRng::from_seed(&[seed][..])
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::rand::Rng;
+    use ::std::sync::Arc;
+    use alphabet::{Alphabet, SubstitutionCipher};
+    use GA::strategy::SteepestAscentReplacementHillClimbing::SteepestAscentReplacementHillClimbingStrategy;
+
+    struct TestGenome(SubstitutionCipher);
+
+    impl Genome for TestGenome {
+        fn mutate<R : Rng>(&mut self, rng : &mut R) {
+            self.0.mutate(rng);
+        }
+        fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+            TestGenome(SubstitutionCipher::crossover(&parent_a.0, &parent_b.0, rng))
+        }
+        fn clone(&self) -> Self {
+            TestGenome(self.0.clone())
+        }
+    }
+
+    impl SerialisableGenome for TestGenome {
+        fn serialise(&self) -> String {
+            self.0.key()
+        }
+        fn deserialise(text : &str) -> Option<Self> {
+            SubstitutionCipher::from_key(Arc::new(Alphabet::english()), text).map(TestGenome)
+        }
+    }
+
+    struct TestGenerator;
+
+    impl Generator for TestGenerator {
+        type Genome = TestGenome;
+        fn create_new<R : Rng>(&self, rng : &mut R) -> TestGenome {
+            TestGenome(SubstitutionCipher::new(Arc::new(Alphabet::english()), rng))
+        }
+    }
+
+    // rewards ciphers that map "abc" close to "xyz"
+    struct TestEvaluator;
+
+    impl Evaluator for TestEvaluator {
+        type Genome = TestGenome;
+        fn fitness(&self, genome : &TestGenome) -> f64 {
+            genome.0.apply("abc").bytes().zip(b"xyz".iter())
+                .map(|(a, b)| -((a as f64) - (*b as f64)).abs())
+                .sum()
+        }
+    }
+
+    fn run(seed : usize) -> SubstitutionCipher {
+        let mut algorithm = GeneticAlgorithm::new(TestGenerator, TestEvaluator);
+        let mut strategy = SteepestAscentReplacementHillClimbingStrategy::new(50, 5, seeded_rng(seed));
+        algorithm.execute_strategy(&mut strategy).0
+    }
+
+    #[test]
+    fn same_seed_gives_same_best_genome() {
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn different_seeds_give_different_starting_ciphers() {
+        let alphabet = Arc::new(Alphabet::english());
+        assert_ne!(SubstitutionCipher::new(alphabet.clone(), &mut seeded_rng(1)), SubstitutionCipher::new(alphabet, &mut seeded_rng(2)));
+    }
+
+    #[test]
+    fn checkpoint_round_trips_through_disk() {
+        let mut algorithm = GeneticAlgorithm::new(TestGenerator, TestEvaluator);
+        let mut strategy = SteepestAscentReplacementHillClimbingStrategy::new(10, 5, seeded_rng(7));
+        algorithm.execute_strategy(&mut strategy);
+
+        let path = ::std::env::temp_dir().join("genetic_algorithm_cipher_checkpoint_test");
+        let checkpoint = strategy.checkpoint().unwrap();
+        checkpoint.save(&path).unwrap();
+        let loaded : Checkpoint<TestGenome> = Checkpoint::load(&path).unwrap();
+        let _ = ::std::fs::remove_file(&path);
+
+        assert_eq!(loaded.generation, 10);
+        assert_eq!(loaded.best.0, checkpoint.best.0);
+        assert_eq!(loaded.best_fitness, checkpoint.best_fitness);
+        assert_eq!(loaded.termination, checkpoint.termination);
+        assert_eq!(loaded.population.len(), checkpoint.population.len());
+    }
+
+    #[test]
+    fn resumed_run_continues_from_checkpoint() {
+        let mut algorithm = GeneticAlgorithm::new(TestGenerator, TestEvaluator);
+        let mut first = SteepestAscentReplacementHillClimbingStrategy::new(10, 5, seeded_rng(7));
+        algorithm.execute_strategy(&mut first);
+        let checkpoint = first.checkpoint().unwrap().clone();
+        let fitness = checkpoint.best_fitness;
+
+        let mut second = SteepestAscentReplacementHillClimbingStrategy::new(10, 5, seeded_rng(8));
+        second.resume_from(checkpoint);
+        let best = algorithm.execute_strategy(&mut second);
+
+        assert_eq!(second.checkpoint().unwrap().generation, 20);
+        assert!(TestEvaluator.fitness(&best) >= fitness);
+    }
+
+    #[test]
+    fn monitor_stops_strategy_early() {
+        let mut algorithm = GeneticAlgorithm::new(TestGenerator, TestEvaluator);
+        let mut strategy = SteepestAscentReplacementHillClimbingStrategy::new(1000, 5, seeded_rng(3))
+            .with_monitor(Monitor::new().termination(Termination::new().stagnation(0)));
+        algorithm.execute_strategy(&mut strategy);
+
+        assert_eq!(strategy.checkpoint().unwrap().generation, 1);
+    }
+}
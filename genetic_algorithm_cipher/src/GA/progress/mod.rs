@@ -0,0 +1,266 @@
+use ::std::sync::Arc;
+use ::std::sync::atomic::{
+    AtomicBool,
+    Ordering
+};
+use ::std::time::{
+    Duration,
+    Instant
+};
+
+/// Summary of the fitnesses evaluated during a single generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    pub generation : u32,
+    pub best : f64,
+    pub mean : f64,
+    pub worst : f64
+}
+
+impl GenerationStats {
+    pub fn from_fitnesses(generation : u32, fitnesses : &[f64]) -> GenerationStats {
+        let mut best = f64::NEG_INFINITY;
+        let mut worst = f64::INFINITY;
+        let mut total = 0.0;
+
+        for &fitness in fitnesses {
+            if fitness > best { best = fitness; }
+            if fitness < worst { worst = fitness; }
+            total += fitness;
+        }
+
+        GenerationStats {
+            generation,
+            best,
+            mean : total / fitnesses.len() as f64,
+            worst
+        }
+    }
+}
+
+pub trait Observer {
+    fn observe(&mut self, stats : &GenerationStats);
+}
+
+/// Prints one line per generation to stdout.
+pub struct PrintObserver;
+
+impl Observer for PrintObserver {
+    fn observe(&mut self, stats : &GenerationStats) {
+        println!("generation {}: best {:.4}, mean {:.4}, worst {:.4}",
+                 stats.generation, stats.best, stats.mean, stats.worst);
+    }
+}
+
+/// How far a run has got towards its termination criteria, kept in
+/// checkpoints so that a resumed run stops where the original would have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerminationState {
+    /// Time spent in generations so far.
+    pub elapsed : Duration,
+    pub best_fitness : f64,
+    pub stagnant_for : u32
+}
+
+impl Default for TerminationState {
+    fn default() -> TerminationState {
+        TerminationState {
+            elapsed : Duration::from_secs(0),
+            best_fitness : f64::NEG_INFINITY,
+            stagnant_for : 0
+        }
+    }
+}
+
+/// Conditions under which a strategy should stop before it has used up its iterations.
+pub struct Termination {
+    target_fitness : Option<f64>,
+    stagnation : Option<u32>,
+    time_budget : Option<Duration>,
+    interrupt : Option<Arc<AtomicBool>>,
+
+    started : Option<Instant>,
+    elapsed_before : Duration,
+    best_fitness : f64,
+    stagnant_for : u32
+}
+
+impl Termination {
+    pub fn new() -> Termination {
+        Termination {
+            target_fitness : None,
+            stagnation : None,
+            time_budget : None,
+            interrupt : None,
+            started : None,
+            elapsed_before : Duration::from_secs(0),
+            best_fitness : f64::NEG_INFINITY,
+            stagnant_for : 0
+        }
+    }
+
+    /// Stop as soon as a generation reaches `fitness`.
+    pub fn target_fitness(mut self, fitness : f64) -> Termination {
+        self.target_fitness = Some(fitness);
+        self
+    }
+
+    /// Stop once the best fitness has not improved for `generations` generations.
+    pub fn stagnation(mut self, generations : u32) -> Termination {
+        self.stagnation = Some(generations);
+        self
+    }
+
+    /// Stop once `budget` wall-clock time has passed since the first generation.
+    pub fn time_budget(mut self, budget : Duration) -> Termination {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Stop once `flag` is set, e.g. from a Ctrl-C handler.
+    pub fn interrupt(mut self, flag : Arc<AtomicBool>) -> Termination {
+        self.interrupt = Some(flag);
+        self
+    }
+
+    pub fn state(&self) -> TerminationState {
+        TerminationState {
+            elapsed : self.elapsed_before + self.started.map_or(Duration::from_secs(0), |started| started.elapsed()),
+            best_fitness : self.best_fitness,
+            stagnant_for : self.stagnant_for
+        }
+    }
+
+    /// Continues counting from `state`; `TerminationState::default()` starts afresh.
+    pub fn restore(&mut self, state : TerminationState) {
+        self.started = None;
+        self.elapsed_before = state.elapsed;
+        self.best_fitness = state.best_fitness;
+        self.stagnant_for = state.stagnant_for;
+    }
+
+    pub fn should_stop(&mut self, stats : &GenerationStats) -> bool {
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        if stats.best > self.best_fitness {
+            self.best_fitness = stats.best;
+            self.stagnant_for = 0;
+        } else {
+            self.stagnant_for += 1;
+        }
+
+        if let Some(target) = self.target_fitness {
+            if self.best_fitness >= target { return true; }
+        }
+        if let Some(generations) = self.stagnation {
+            if self.stagnant_for >= generations { return true; }
+        }
+        if let Some(budget) = self.time_budget {
+            if self.elapsed_before + started.elapsed() >= budget { return true; }
+        }
+        if let Some(ref flag) = self.interrupt {
+            if flag.load(Ordering::SeqCst) { return true; }
+        }
+
+        false
+    }
+}
+
+impl Default for Termination {
+    fn default() -> Termination {
+        Termination::new()
+    }
+}
+
+/// Bundles the observers and termination criteria a strategy reports to.
+#[derive(Default)]
+pub struct Monitor {
+    observers : Vec<Box<dyn Observer>>,
+    termination : Termination
+}
+
+impl Monitor {
+    pub fn new() -> Monitor {
+        Monitor::default()
+    }
+
+    pub fn observer(mut self, observer : Box<dyn Observer>) -> Monitor {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn termination(mut self, termination : Termination) -> Monitor {
+        self.termination = termination;
+        self
+    }
+
+    /// Reports a finished generation, returning true if the strategy should stop.
+    pub fn record(&mut self, generation : u32, fitnesses : &[f64]) -> bool {
+        let stats = GenerationStats::from_fitnesses(generation, fitnesses);
+
+        for observer in self.observers.iter_mut() {
+            observer.observe(&stats);
+        }
+
+        self.termination.should_stop(&stats)
+    }
+
+    pub fn termination_state(&self) -> TerminationState {
+        self.termination.state()
+    }
+
+    pub fn restore(&mut self, state : TerminationState) {
+        self.termination.restore(state);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_summarise_fitnesses() {
+        let stats = GenerationStats::from_fitnesses(3, &[1.0, 4.0, -2.0, 1.0]);
+        assert_eq!(stats, GenerationStats { generation : 3, best : 4.0, mean : 1.0, worst : -2.0 });
+    }
+
+    #[test]
+    fn stops_on_target_fitness() {
+        let mut monitor = Monitor::new().termination(Termination::new().target_fitness(10.0));
+        assert!(!monitor.record(0, &[5.0]));
+        assert!(monitor.record(1, &[10.0]));
+    }
+
+    #[test]
+    fn stops_on_stagnation() {
+        let mut monitor = Monitor::new().termination(Termination::new().stagnation(2));
+        assert!(!monitor.record(0, &[5.0]));
+        assert!(!monitor.record(1, &[4.0]));
+        assert!(monitor.record(2, &[5.0]));
+    }
+
+    #[test]
+    fn restored_termination_keeps_counting() {
+        let mut monitor = Monitor::new().termination(Termination::new().stagnation(2));
+        assert!(!monitor.record(0, &[5.0]));
+        assert!(!monitor.record(1, &[4.0]));
+        let state = monitor.termination_state();
+
+        let mut resumed = Monitor::new().termination(Termination::new().stagnation(2));
+        resumed.restore(state);
+        assert!(resumed.record(2, &[5.0]));
+
+        monitor.restore(TerminationState::default());
+        assert!(!monitor.record(2, &[5.0]));
+    }
+
+    #[test]
+    fn stops_on_interrupt() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut monitor = Monitor::new().termination(Termination::new().interrupt(flag.clone()));
+        assert!(!monitor.record(0, &[1.0]));
+        flag.store(true, Ordering::SeqCst);
+        assert!(monitor.record(1, &[2.0]));
+    }
+}
@@ -1,58 +1,115 @@
+extern crate rand;
+
 use ::std::marker::PhantomData;
+use self::rand::Rng;
 use GA::interface::{
     Genome,
     Generator,
     Evaluator,
     EvolutionaryAlgorithmStrategy
 };
+use GA::checkpoint::Checkpoint;
+use GA::progress::{
+    Monitor,
+    TerminationState
+};
 
 
-pub struct HillClimbingStrategy<T,G,E>
+pub struct HillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
    genome : PhantomData <T>,
    generator : PhantomData <G>,
    evaluator : PhantomData <E>,
+   rng : R,
+   monitor : Monitor,
+   state : Option<Checkpoint<T>>,
    iterations : u32
 }
 
-impl <T,G,E> HillClimbingStrategy<T,G,E> 
+impl <T,G,E,R> HillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
-    pub fn new(iterations : u32) -> HillClimbingStrategy<T,G,E> {
+    pub fn new(iterations : u32, rng : R) -> HillClimbingStrategy<T,G,E,R> {
         HillClimbingStrategy{
             genome : PhantomData,
             generator : PhantomData,
             evaluator : PhantomData,
+            rng,
+            monitor : Monitor::new(),
+            state : None,
             iterations
         }
     }
+
+    pub fn with_monitor(mut self, monitor : Monitor) -> HillClimbingStrategy<T,G,E,R> {
+        self.monitor = monitor;
+        self
+    }
 }
 
-impl <T,G,E> EvolutionaryAlgorithmStrategy for HillClimbingStrategy<T,G,E>
+impl <T,G,E,R> EvolutionaryAlgorithmStrategy for HillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T> {
+          E : Evaluator<Genome=T>,
+          R : Rng {
         type Genome = T;
         type Generator = G;
         type Evaluator = E;
 
         fn execute(&mut self, generator : &mut Self::Generator, evaluator : &mut Self::Evaluator) -> T {
-            let mut s = generator.create_new();
-            let mut best_fitness = evaluator.fitness(&s);
-            for i in (0..self.iterations) {
-                let r = generator.create_new();
+            // carry on counting towards termination from the checkpoint, if any
+            let termination = self.state.as_ref()
+                .map_or_else(TerminationState::default, |checkpoint| checkpoint.termination);
+            self.monitor.restore(termination);
+
+            let (mut s, mut best_fitness, mut generation) = match self.state.take() {
+                Some(checkpoint) => (checkpoint.best, checkpoint.best_fitness, checkpoint.generation),
+                None => {
+                    let s = generator.create_new(&mut self.rng);
+                    let fitness = evaluator.fitness(&s);
+                    (s, fitness, 0)
+                }
+            };
+
+            for _ in 0..self.iterations {
+                generation += 1;
+                let r = generator.create_new(&mut self.rng);
                 let fitness = evaluator.fitness(&r);
+                let stop = self.monitor.record(generation, &[best_fitness, fitness]);
+
                 if fitness > best_fitness {
                    s = r; 
                    best_fitness = fitness;
                 }
+
+                if stop {
+                    break;
+                }
             }
 
+            self.state = Some(Checkpoint {
+                generation,
+                best : s.clone(),
+                best_fitness,
+                termination : self.monitor.termination_state(),
+                population : vec![s.clone()]
+            });
+
             s
         }
+
+        fn checkpoint(&self) -> Option<&Checkpoint<T>> {
+            self.state.as_ref()
+        }
+
+        fn resume_from(&mut self, checkpoint : Checkpoint<T>) {
+            self.state = Some(checkpoint);
+        }
   }
@@ -1,72 +1,131 @@
+extern crate rand;
+
 use ::std::marker::PhantomData;
+use self::rand::Rng;
 use GA::interface::{
     Genome,
     Generator,
     Evaluator,
     EvolutionaryAlgorithmStrategy
 };
+use GA::checkpoint::Checkpoint;
+use GA::progress::{
+    Monitor,
+    TerminationState
+};
 
 
-pub struct SteepestAscentHillClimbingStrategy<T,G,E>
+pub struct SteepestAscentHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
    genome : PhantomData <T>,
    generator : PhantomData <G>,
    evaluator : PhantomData <E>,
+   rng : R,
+   monitor : Monitor,
+   state : Option<Checkpoint<T>>,
    iterations : u32,
    samples : u32
 }
 
-impl <T,G,E> SteepestAscentHillClimbingStrategy<T,G,E> 
+impl <T,G,E,R> SteepestAscentHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
-    pub fn new(iterations : u32, samples : u32) -> SteepestAscentHillClimbingStrategy<T,G,E> {
+    pub fn new(iterations : u32, samples : u32, rng : R) -> SteepestAscentHillClimbingStrategy<T,G,E,R> {
        SteepestAscentHillClimbingStrategy {
             genome : PhantomData,
             generator : PhantomData,
             evaluator : PhantomData,
+            rng,
+            monitor : Monitor::new(),
+            state : None,
             iterations,
             samples
         }
     }
+
+    pub fn with_monitor(mut self, monitor : Monitor) -> SteepestAscentHillClimbingStrategy<T,G,E,R> {
+        self.monitor = monitor;
+        self
+    }
 }
 
-impl <T,G,E> EvolutionaryAlgorithmStrategy for SteepestAscentHillClimbingStrategy<T,G,E>
+impl <T,G,E,R> EvolutionaryAlgorithmStrategy for SteepestAscentHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T> {
+          E : Evaluator<Genome=T>,
+          R : Rng {
         type Genome = T;
         type Generator = G;
         type Evaluator = E;
 
         fn execute(&mut self,  generator : &mut Self::Generator, evaluator : &mut Self::Evaluator) -> T {
-            let mut s = generator.create_new();
-            let mut best_fitness = evaluator.fitness(&s);
-            for i in (0..self.iterations) {
+            // carry on counting towards termination from the checkpoint, if any
+            let termination = self.state.as_ref()
+                .map_or_else(TerminationState::default, |checkpoint| checkpoint.termination);
+            self.monitor.restore(termination);
+
+            let (mut s, mut best_fitness, mut generation) = match self.state.take() {
+                Some(checkpoint) => (checkpoint.best, checkpoint.best_fitness, checkpoint.generation),
+                None => {
+                    let s = generator.create_new(&mut self.rng);
+                    let fitness = evaluator.fitness(&s);
+                    (s, fitness, 0)
+                }
+            };
+
+            for _ in 0..self.iterations {
+                generation += 1;
                 let mut r = s.clone();
-                r.mutate();
+                r.mutate(&mut self.rng);
                 let mut r_fitness = evaluator.fitness(&r);
+                let mut fitnesses = vec![r_fitness];
 
-                for j in (0..self.samples) {
+                for _ in 0..self.samples {
                     let mut w = s.clone();
-                    w.mutate();
+                    w.mutate(&mut self.rng);
                     let w_fitness = evaluator.fitness(&w);
+                    fitnesses.push(w_fitness);
                     if w_fitness > r_fitness {
                         r_fitness = w_fitness;
                         r = w;
                     }
                 }
 
+                let stop = self.monitor.record(generation, &fitnesses);
+
                 if r_fitness > best_fitness {
                    s = r; 
                    best_fitness = r_fitness;
                 }
+
+                if stop {
+                    break;
+                }
             }
 
+            self.state = Some(Checkpoint {
+                generation,
+                best : s.clone(),
+                best_fitness,
+                termination : self.monitor.termination_state(),
+                population : vec![s.clone()]
+            });
+
             s
         }
+
+        fn checkpoint(&self) -> Option<&Checkpoint<T>> {
+            self.state.as_ref()
+        }
+
+        fn resume_from(&mut self, checkpoint : Checkpoint<T>) {
+            self.state = Some(checkpoint);
+        }
   }
@@ -1,63 +1,105 @@
+extern crate rand;
+
 use ::std::marker::PhantomData;
+use self::rand::Rng;
 use GA::interface::{
     Genome,
     Generator,
     Evaluator,
     EvolutionaryAlgorithmStrategy
 };
+use GA::checkpoint::Checkpoint;
+use GA::progress::{
+    Monitor,
+    TerminationState
+};
 
 
-pub struct SteepestAscentReplacementHillClimbingStrategy<T,G,E>
+pub struct SteepestAscentReplacementHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
    genome : PhantomData <T>,
    generator : PhantomData <G>,
    evaluator : PhantomData <E>,
+   rng : R,
+   monitor : Monitor,
+   state : Option<Checkpoint<T>>,
    iterations : u32,
    samples : u32
 }
 
-impl <T,G,E> SteepestAscentReplacementHillClimbingStrategy<T,G,E> 
+impl <T,G,E,R> SteepestAscentReplacementHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T>
+          E : Evaluator<Genome=T>,
+          R : Rng
 {
-    pub fn new(iterations : u32, samples : u32) -> SteepestAscentReplacementHillClimbingStrategy<T,G,E> {
+    pub fn new(iterations : u32, samples : u32, rng : R) -> SteepestAscentReplacementHillClimbingStrategy<T,G,E,R> {
        SteepestAscentReplacementHillClimbingStrategy {
             genome : PhantomData,
             generator : PhantomData,
             evaluator : PhantomData,
+            rng,
+            monitor : Monitor::new(),
+            state : None,
             iterations,
             samples
         }
     }
+
+    pub fn with_monitor(mut self, monitor : Monitor) -> SteepestAscentReplacementHillClimbingStrategy<T,G,E,R> {
+        self.monitor = monitor;
+        self
+    }
 }
 
-impl <T,G,E> EvolutionaryAlgorithmStrategy for SteepestAscentReplacementHillClimbingStrategy<T,G,E>
+impl <T,G,E,R> EvolutionaryAlgorithmStrategy for SteepestAscentReplacementHillClimbingStrategy<T,G,E,R>
     where T : Genome,
           G : Generator<Genome=T>,
-          E : Evaluator<Genome=T> {
+          E : Evaluator<Genome=T>,
+          R : Rng {
         type Genome = T;
         type Generator = G;
         type Evaluator = E;
 
         fn execute(&mut self,  generator : &mut Self::Generator, evaluator : &mut Self::Evaluator) -> T {
-            let mut s = generator.create_new();
-            let mut best = s.clone();
+            // carry on counting towards termination from the checkpoint, if any
+            let termination = self.state.as_ref()
+                .map_or_else(TerminationState::default, |checkpoint| checkpoint.termination);
+            self.monitor.restore(termination);
 
-            let mut best_fitness = evaluator.fitness(&s);
+            let (mut s, mut best, mut best_fitness, mut generation) = match self.state.take() {
+                Some(mut checkpoint) => {
+                    let s = if checkpoint.population.is_empty() {
+                        checkpoint.best.clone()
+                    } else {
+                        checkpoint.population.swap_remove(0)
+                    };
+                    (s, checkpoint.best, checkpoint.best_fitness, checkpoint.generation)
+                },
+                None => {
+                    let s = generator.create_new(&mut self.rng);
+                    let best = s.clone();
+                    let fitness = evaluator.fitness(&s);
+                    (s, best, fitness, 0)
+                }
+            };
 
-            for i in (0..self.iterations) {
+            for _ in 0..self.iterations {
+                generation += 1;
                 let mut r = s.clone();
-                r.mutate();
+                r.mutate(&mut self.rng);
                 let mut r_fitness = evaluator.fitness(&r);
+                let mut fitnesses = vec![r_fitness];
 
-                for j in (0..self.samples) {
+                for _ in 0..self.samples {
                     let mut w = s.clone();
-                    w.mutate();
+                    w.mutate(&mut self.rng);
                     let w_fitness = evaluator.fitness(&w);
+                    fitnesses.push(w_fitness);
                     if w_fitness > r_fitness {
                         r_fitness = w_fitness;
                         r = w;
@@ -65,12 +107,34 @@ impl <T,G,E> EvolutionaryAlgorithmStrategy for SteepestAscentReplacementHillClim
                 }
                 s = r;
 
+                let stop = self.monitor.record(generation, &fitnesses);
+
                 if r_fitness > best_fitness {
                    best = s.clone(); 
                    best_fitness = r_fitness;
                 }
+
+                if stop {
+                    break;
+                }
             }
 
+            self.state = Some(Checkpoint {
+                generation,
+                best : best.clone(),
+                best_fitness,
+                termination : self.monitor.termination_state(),
+                population : vec![s]
+            });
+
             best 
         }
+
+        fn checkpoint(&self) -> Option<&Checkpoint<T>> {
+            self.state.as_ref()
+        }
+
+        fn resume_from(&mut self, checkpoint : Checkpoint<T>) {
+            self.state = Some(checkpoint);
+        }
   }
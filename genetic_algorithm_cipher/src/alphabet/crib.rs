@@ -0,0 +1,196 @@
+use ::std::fmt::{
+    Display,
+    Formatter,
+};
+use ::std::fmt;
+use ::std::sync::Arc;
+use super::Alphabet;
+
+/// Symbol mappings of a substitution key that are already known, e.g. from a
+/// fragment of known plaintext. `fixed[c]` is the index of the plaintext
+/// symbol that the ciphertext symbol at index `c` must decrypt to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crib {
+    alphabet : Arc<Alphabet>,
+    fixed : Vec<Option<usize>>
+}
+
+impl Crib {
+    pub fn new(alphabet : Arc<Alphabet>) -> Crib {
+        Crib {
+            fixed : vec!(None; alphabet.len()),
+            alphabet
+        }
+    }
+
+    /// Parses comma separated `cipher=plain` pairs, e.g. `q=e,x=t`.
+    pub fn parse(text : &str, alphabet : Arc<Alphabet>) -> Result<Crib, String> {
+        let mut crib = Crib::new(alphabet);
+
+        for pair in text.split(',').map(|pair| pair.trim()).filter(|pair| !pair.is_empty()) {
+            let characters : Vec<char> = pair.chars().collect();
+            if characters.len() != 3 || characters[1] != '=' {
+                return Err(format!("expected cipher=plain, got {}", pair));
+            }
+            crib.fix(characters[0], characters[2])?;
+        }
+
+        Ok(crib)
+    }
+
+    pub fn alphabet(&self) -> &Arc<Alphabet> {
+        &self.alphabet
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixed.iter().all(|mapping| mapping.is_none())
+    }
+
+    /// The plaintext symbol index `cipher` is fixed to, if any.
+    pub fn get(&self, cipher : usize) -> Option<usize> {
+        self.fixed[cipher]
+    }
+
+    /// Requires ciphertext symbol `cipher` to decrypt to `plain`, failing if
+    /// that contradicts a mapping already in the crib.
+    pub fn fix(&mut self, cipher : char, plain : char) -> Result<(), String> {
+        match (self.alphabet.index_of(cipher), self.alphabet.index_of(plain)) {
+            (Some(cipher), Some(plain)) => self.fix_index(cipher, plain),
+            _ => Err(format!("crib symbols must be in the {} alphabet, got {}={}", self.alphabet.spec(), cipher, plain))
+        }
+    }
+
+    fn fix_index(&mut self, cipher : usize, plain : usize) -> Result<(), String> {
+        let symbol = |index : usize| self.alphabet.symbol(index);
+
+        match self.fixed[cipher] {
+            Some(existing) if existing != plain => {
+                return Err(format!("{} is already fixed to {}, not {}", symbol(cipher), symbol(existing), symbol(plain)));
+            },
+            _ => {}
+        }
+        if let Some(other) = self.fixed.iter().position(|&mapping| mapping == Some(plain)) {
+            if other != cipher {
+                return Err(format!("{} is already the image of {}", symbol(plain), symbol(other)));
+            }
+        }
+
+        self.fixed[cipher] = Some(plain);
+        Ok(())
+    }
+
+    /// Fixes the mappings implied by `plaintext` lining up with the ciphertext
+    /// symbols starting at symbol `offset` (other characters are not counted).
+    pub fn add_known_plaintext(&mut self, ciphertext : &str, offset : usize, plaintext : &str) -> Result<(), String> {
+        let cipher_symbols = self.indices(ciphertext);
+        let plain_symbols = self.indices(plaintext);
+
+        if offset + plain_symbols.len() > cipher_symbols.len() {
+            return Err(format!("crib {} runs past the end of the ciphertext", plaintext));
+        }
+
+        let mut crib = self.clone();
+        for (&cipher, &plain) in cipher_symbols[offset..].iter().zip(plain_symbols.iter()) {
+            crib.fix_index(cipher, plain)?;
+        }
+
+        *self = crib;
+        Ok(())
+    }
+
+    /// Finds the first symbol offset at which `plaintext` could line up with
+    /// the ciphertext: repeated symbols must line up with repeated symbols and
+    /// the implied mappings must agree with the crib so far.
+    pub fn locate(&self, ciphertext : &str, plaintext : &str) -> Option<usize> {
+        let cipher_length = self.indices(ciphertext).len();
+        let length = self.indices(plaintext).len();
+
+        if length == 0 || length > cipher_length {
+            return None;
+        }
+
+        (0..cipher_length - length + 1).find(|&offset| {
+            self.clone().add_known_plaintext(ciphertext, offset, plaintext).is_ok()
+        })
+    }
+
+    /// Rearranges `mapping`, a permutation of the alphabet's indices, so that
+    /// every fixed symbol maps where the crib says while keeping it a permutation.
+    pub fn enforce(&self, mapping : &mut [usize]) {
+        for (cipher, fixed) in self.fixed.iter().enumerate() {
+            if let Some(plain) = *fixed {
+                let current = mapping.iter().position(|&symbol| symbol == plain).unwrap();
+                mapping.swap(cipher, current);
+            }
+        }
+    }
+
+    fn indices(&self, text : &str) -> Vec<usize> {
+        text.chars().filter_map(|character| self.alphabet.index_of(character)).collect()
+    }
+}
+
+/// Written as comma separated `cipher=plain` pairs, e.g. `q=e,x=t`.
+impl Display for Crib {
+    fn fmt(&self, f : &mut Formatter) -> fmt::Result {
+        let pairs = self.fixed.iter().enumerate()
+            .filter_map(|(cipher, fixed)| fixed.map(|plain| format!("{}={}", self.alphabet.symbol(cipher), self.alphabet.symbol(plain))))
+            .collect::<Vec<_>>();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Arc<Alphabet> {
+        Arc::new(Alphabet::english())
+    }
+
+    #[test]
+    fn known_plaintext_fixes_letters() {
+        let mut crib = Crib::new(english());
+        crib.add_known_plaintext("xy, zxw", 1, "hello").unwrap_err();
+        crib.add_known_plaintext("qw XZY", 2, "The").unwrap();
+
+        assert_eq!(crib.to_string(), "x=t,y=e,z=h");
+        assert!(crib.add_known_plaintext("qw xzy", 2, "a").is_err());
+    }
+
+    #[test]
+    fn locates_crib_by_letter_pattern() {
+        // "hello" has a double letter, so it can only sit on "abccd"
+        let ciphertext = "qrstu vabccd";
+        assert_eq!(Crib::new(english()).locate(ciphertext, "hello"), Some(6));
+        assert_eq!(Crib::new(english()).locate(ciphertext, "hellohello"), None);
+    }
+
+    #[test]
+    fn enforce_keeps_a_permutation() {
+        let crib = Crib::parse("a=z,b=a", english()).unwrap();
+        let mut mapping : Vec<usize> = (0..26).collect();
+        crib.enforce(&mut mapping);
+
+        assert_eq!(mapping[0], 25);
+        assert_eq!(mapping[1], 0);
+        let mut sorted = mapping.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..26).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn rejects_contradictions() {
+        assert!(Crib::parse("a=b,a=c", english()).is_err());
+        assert!(Crib::parse("a=b,c=b", english()).is_err());
+        assert!(Crib::parse("a=1", english()).is_err());
+        assert_eq!(Crib::parse("q=e, X=T", english()).unwrap().to_string(), "q=e,x=t");
+    }
+
+    #[test]
+    fn accepts_non_ascii_symbols() {
+        let crib = Crib::parse("é=e,Ç=c", Arc::new(Alphabet::extended_latin())).unwrap();
+        assert_eq!(crib.to_string(), "ç=c,é=e");
+    }
+}
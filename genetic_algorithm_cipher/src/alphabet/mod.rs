@@ -1,110 +1,294 @@
 extern crate rand;
 
+pub mod crib;
+mod symbols;
+
+pub use self::crib::Crib;
+pub use self::symbols::Alphabet;
+
+use ::std::sync::Arc;
 use self::rand::Rng;
+use GA::interface::{
+    Genome,
+    Generator,
+    SerialisableGenome
+};
+use ciphers::Cipher;
 use ::std::fmt::{
     Display,
     Formatter,
 };
 use ::std::fmt;
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct SubstitutionCipher {
-    mapping: [u8; 26]
+    alphabet: Arc<Alphabet>,
+    mapping: Vec<usize>,
+    crib: Option<Arc<Crib>>
 }
 
 impl SubstitutionCipher {
-    pub fn new() -> SubstitutionCipher {
-       let mut chars = [ 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, ];
+    pub fn new<R : Rng>(alphabet : Arc<Alphabet>, rng : &mut R) -> SubstitutionCipher {
+       let mut chars : Vec<usize> = (0..alphabet.len()).collect();
 
-       rand::thread_rng().shuffle(&mut chars);
+       rng.shuffle(&mut chars);
 
        SubstitutionCipher {
-        mapping: chars
+        alphabet,
+        mapping: chars,
+        crib: None
        }
     }
 
-    pub fn crossover(parent_a : &SubstitutionCipher, parent_b : &SubstitutionCipher) -> SubstitutionCipher {
-        let mut chars : [u8; 26] = [0; 26];
-        let mut copied : [bool; 26] = [false; 26];
+    /// Constrains this key, and every key mutated or bred from it, to the
+    /// mappings fixed by `crib`.
+    pub fn with_crib(mut self, crib : Arc<Crib>) -> SubstitutionCipher {
+        crib.enforce(&mut self.mapping);
+        self.crib = Some(crib);
+        self
+    }
+
+    pub fn crib(&self) -> Option<&Crib> {
+        self.crib.as_deref()
+    }
+
+    pub fn alphabet(&self) -> &Arc<Alphabet> {
+        &self.alphabet
+    }
+
+    pub fn crossover<R : Rng>(parent_a : &SubstitutionCipher, parent_b : &SubstitutionCipher, rng : &mut R) -> SubstitutionCipher {
+        let size = parent_a.mapping.len();
+        let mut chars : Vec<usize> = vec!(0; size);
+        let mut copied : Vec<bool> = vec!(false; size);
         let mut not_moved : Vec<usize> = vec!();
 
-        let mut rng = rand::thread_rng();
-        let crossover : usize = rng.gen::<usize>() % 26usize;
-        println!("Crossing over at {}", crossover);
+        let crossover : usize = rng.gen::<usize>() % size;
 
-        for i in 0..26 {
-            if i < crossover {
-                chars[i] = parent_a.mapping[i];
-                copied[(parent_a.mapping[i] - b'a') as usize] = true;
-            } 
+        for (i, &a_char) in parent_a.mapping.iter().enumerate().take(crossover) {
+            chars[i] = a_char;
+            copied[a_char] = true;
         }
 
-        for i in crossover..26 {
+        for i in crossover..size {
             let b_char = parent_b.mapping[i];
-            if !copied[(b_char - b'a') as usize] {
+            if !copied[b_char] {
                 chars[i] = b_char;
-                copied[(b_char - b'a') as usize]  = true;
+                copied[b_char]  = true;
             } else {
                 not_moved.push(i);
             }
         }
 
-        for i in 0..26 {
+        for i in 0..size {
             if !copied[i] {
                 let index = not_moved.pop().unwrap();
 
-                chars[index] = b'a' + i as u8;
+                chars[index] = i;
             }
         }
 
+        if let Some(ref crib) = parent_a.crib {
+            crib.enforce(&mut chars);
+        }
+
         SubstitutionCipher {
-            mapping: chars
+            alphabet: parent_a.alphabet.clone(),
+            mapping: chars,
+            crib: parent_a.crib.clone()
         }
     }
 
     pub fn clone(&self) -> Self {
-       let mut chars = [ 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0 ];
-
-       for i in 0..self.mapping.len() {
-            chars[i] = self.mapping[i];
-       }
-
        SubstitutionCipher {
-        mapping: chars
+        alphabet: self.alphabet.clone(),
+        mapping: self.mapping.clone(),
+        crib: self.crib.clone()
        }
- 
     }
 
-    pub fn mutate(&mut self) {
-        let positonA : usize = rand::thread_rng().gen::<usize>() % 26;
+    /// The cipher alphabet as a string, `key()[i]` being the image of the `i`th symbol.
+    pub fn key(&self) -> String {
+        self.mapping.iter().map(|&index| self.alphabet.symbol(index)).collect()
+    }
+
+    /// Parses a key produced by `key`, rejecting anything that is not a
+    /// permutation of the symbols of `alphabet`.
+    pub fn from_key(alphabet : Arc<Alphabet>, key : &str) -> Option<SubstitutionCipher> {
+        let mut chars : Vec<usize> = vec!();
+        let mut seen : Vec<bool> = vec!(false; alphabet.len());
+        for character in key.chars() {
+            let index = alphabet.index_of(character)?;
+            if seen[index] || alphabet.symbol(index) != character {
+                return None;
+            }
+            seen[index] = true;
+            chars.push(index);
+        }
+        if chars.len() != alphabet.len() {
+            return None;
+        }
+
+        Some(SubstitutionCipher {
+            alphabet,
+            mapping: chars,
+            crib: None
+        })
+    }
+
+    pub fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        if let Some(ref crib) = self.crib {
+            // swap two letters the crib leaves free, if there are two
+            let free : Vec<usize> = (0..self.mapping.len()).filter(|&i| crib.get(i).is_none()).collect();
+            if free.len() >= 2 {
+                let a = free[rng.gen_range(0, free.len())];
+                let b = free[rng.gen_range(0, free.len())];
+                self.mapping.swap(a, b);
+            }
+            return;
+        }
+
+        let positonA : usize = rng.gen::<usize>() % self.mapping.len();
         let characterA_old = self.mapping[positonA];
-        let characterA_new = self.mapping[(characterA_old - b'a') as usize];
+        let characterA_new = self.mapping[characterA_old];
        
         self.mapping[positonA] = characterA_new;
-        self.mapping[(characterA_old - b'a') as usize] = characterA_old;
+        self.mapping[characterA_old] = characterA_old;
     }
 
 
-    pub fn apply(&self, text : &String) -> String {
-        let mut result : String = String::new();
+    /// Replaces every symbol of `text` by its image, keeping its case and
+    /// leaving digits, punctuation and other characters as they are.
+    pub fn apply(&self, text : &str) -> String {
+        self.alphabet.map_symbols(text, |index| self.mapping[index])
+    }
+}
 
-        for character in text.chars() {
-            let char_value = character as u8;
-            if char_value  >=  b'a' && char_value - b'a'  < 26 {
-                result.push(self.mapping[(char_value - b'a') as usize] as char);
-            } else {
-                result.push(character);
-            }
+impl Genome for SubstitutionCipher {
+    fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        SubstitutionCipher::mutate(self, rng);
+    }
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+        SubstitutionCipher::crossover(parent_a, parent_b, rng)
+    }
+    fn clone(&self) -> Self {
+        SubstitutionCipher::clone(self)
+    }
+}
+
+/// Serialised as the key, followed by the crib if there is one.
+impl SerialisableGenome for SubstitutionCipher {
+    fn serialise(&self) -> String {
+        let body = match self.crib() {
+            Some(crib) if !crib.is_empty() => format!("{} {}", self.key(), crib),
+            _ => self.key()
+        };
+        self.alphabet.tag(&body)
+    }
+    fn deserialise(text : &str) -> Option<Self> {
+        let (alphabet, body) = Alphabet::untag(text)?;
+        let alphabet = Arc::new(alphabet);
+        // the key may itself contain spaces, so split it off by length
+        let split = body.char_indices().nth(alphabet.len()).map_or(body.len(), |(offset, _)| offset);
+        let cipher = SubstitutionCipher::from_key(alphabet.clone(), &body[..split])?;
+        match body[split..].strip_prefix(' ') {
+            Some(crib) => {
+                let crib = Crib::parse(crib, alphabet).ok()?;
+                let constrained = cipher.clone().with_crib(Arc::new(crib));
+                if constrained.mapping == cipher.mapping { Some(constrained) } else { None }
+            },
+            None if body.len() == split => Some(cipher),
+            None => None
+        }
+    }
+}
+
+/// The mapping takes ciphertext letters to plaintext letters, so deciphering
+/// applies it and enciphering applies its inverse.
+impl Cipher for SubstitutionCipher {
+    fn encipher(&self, text : &str) -> String {
+        let mut inverse : Vec<usize> = vec!(0; self.mapping.len());
+        for (index, &character) in self.mapping.iter().enumerate() {
+            inverse[character] = index;
         }
 
-        result
+        self.alphabet.map_symbols(text, |index| inverse[index])
+    }
+    fn decipher(&self, text : &str) -> String {
+        self.apply(text)
+    }
+}
+
+pub struct SubstitutionGenerator {
+    pub alphabet : Arc<Alphabet>,
+    pub crib : Option<Arc<Crib>>
+}
+
+impl Default for SubstitutionGenerator {
+    fn default() -> SubstitutionGenerator {
+        SubstitutionGenerator {
+            alphabet : Arc::new(Alphabet::english()),
+            crib : None
+        }
+    }
+}
+
+impl Generator for SubstitutionGenerator {
+    type Genome = SubstitutionCipher;
+
+    fn create_new<R : Rng>(&self, rng : &mut R) -> SubstitutionCipher {
+        let cipher = SubstitutionCipher::new(self.alphabet.clone(), rng);
+        match self.crib {
+            Some(ref crib) => cipher.with_crib(crib.clone()),
+            None => cipher
+        }
     }
 }
 
 impl Display for SubstitutionCipher {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        for (index, character) in (0..26).zip((b'a'..b'z'+1)) {
-           write!(f, "{}: {}, ", character as char, self.mapping[index] as char);
+        for (index, &character) in self.alphabet.symbols().iter().enumerate() {
+           write!(f, "{}: {}, ", character, self.alphabet.symbol(self.mapping[index]))?;
         }
         write!(f, "\n")
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GA::seeded_rng;
+
+    #[test]
+    fn keeps_case_digits_and_punctuation() {
+        let cipher = SubstitutionCipher::new(Arc::new(Alphabet::english()), &mut seeded_rng(3));
+        let text = "Meet me at 10:30, Platform 9!".to_string();
+        let encrypted = cipher.encipher(&text);
+
+        assert_eq!(encrypted.len(), text.len());
+        assert_eq!(&encrypted[10..16], " 10:30");
+        assert!(encrypted.starts_with(char::is_uppercase));
+        assert_eq!(cipher.decipher(&encrypted), text);
+    }
+
+    #[test]
+    fn permutes_extended_alphabet_and_round_trips() {
+        let alphabet = Arc::new(Alphabet::extended_latin());
+        let crib = Arc::new(Crib::parse("é=e", alphabet.clone()).unwrap());
+        let cipher = SubstitutionCipher::new(alphabet, &mut seeded_rng(4)).with_crib(crib);
+        let text = "Où est la rue Ça-ira ? Là-bas, près de l'Œil.".to_string();
+
+        assert_eq!(cipher.decipher(&cipher.encipher(&text)), text);
+        assert_eq!(cipher.decipher("é"), "e");
+        assert_eq!(SubstitutionCipher::deserialise(&cipher.serialise()), Some(cipher));
+    }
+
+    #[test]
+    fn english_keys_keep_their_old_form() {
+        let key = "zyxwvutsrqponmlkjihgfedcba";
+        let cipher = SubstitutionCipher::from_key(Arc::new(Alphabet::english()), key).unwrap();
+        assert_eq!(cipher.serialise(), key);
+        assert_eq!(SubstitutionCipher::deserialise(key), Some(cipher));
+        assert_eq!(SubstitutionCipher::deserialise("abc"), None);
+    }
+}
@@ -0,0 +1,186 @@
+use ::std::collections::HashMap;
+
+const ENGLISH : &str = "abcdefghijklmnopqrstuvwxyz";
+const EXTENDED_LATIN : &str = "abcdefghijklmnopqrstuvwxyzàáâãäåæçèéêëìíîïðñòóôõöøùúûüýþÿœß";
+
+/// The ordered set of symbols ciphers permute and language models count.
+///
+/// Symbols are stored lower-case. Where a symbol has a single-character
+/// upper-case form that is not itself a symbol, that form is accepted too
+/// and treated as the same symbol, so case can be restored after enciphering.
+/// Characters outside the alphabet are passed through untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    spec : String,
+    symbols : Vec<char>,
+    index : HashMap<char, (usize, bool)>
+}
+
+impl Alphabet {
+    /// Builds an alphabet from `symbols` in order, rejecting empty or repeated sets.
+    pub fn from_symbols(spec : &str, symbols : &str) -> Result<Alphabet, String> {
+        let symbols : Vec<char> = symbols.chars().collect();
+        let mut index : HashMap<char, (usize, bool)> = HashMap::new();
+
+        if symbols.len() < 2 {
+            return Err("an alphabet needs at least two symbols".to_string());
+        }
+
+        for (i, &symbol) in symbols.iter().enumerate() {
+            if index.insert(symbol, (i, false)).is_some() {
+                return Err(format!("symbol {} appears twice", symbol));
+            }
+        }
+        for (i, &symbol) in symbols.iter().enumerate() {
+            let mut upper = symbol.to_uppercase();
+            if let (Some(upper), None) = (upper.next(), upper.next()) {
+                if upper != symbol && !index.contains_key(&upper) {
+                    index.insert(upper, (i, true));
+                }
+            }
+        }
+
+        Ok(Alphabet {
+            spec : spec.to_string(),
+            symbols,
+            index
+        })
+    }
+
+    /// The letters a-z, case preserved.
+    pub fn english() -> Alphabet {
+        Alphabet::from_symbols("english", ENGLISH).unwrap()
+    }
+
+    /// The letters a-z followed by the accented letters and ligatures of
+    /// Western European languages, case preserved.
+    pub fn extended_latin() -> Alphabet {
+        Alphabet::from_symbols("extended-latin", EXTENDED_LATIN).unwrap()
+    }
+
+    /// Parses `english`, `extended-latin` or `symbols:<the symbols in order>`.
+    pub fn from_spec(spec : &str) -> Result<Alphabet, String> {
+        match spec {
+            "english" => Ok(Alphabet::english()),
+            "extended-latin" => Ok(Alphabet::extended_latin()),
+            _ if spec.starts_with("symbols:") => Alphabet::from_symbols(spec, &spec["symbols:".len()..]),
+            _ => Err(format!("unknown alphabet {}", spec))
+        }
+    }
+
+    /// The string `from_spec` rebuilds this alphabet from.
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn symbol(&self, index : usize) -> char {
+        self.symbols[index]
+    }
+
+    pub fn symbols(&self) -> &[char] {
+        &self.symbols
+    }
+
+    /// Index of the symbol `character` stands for, ignoring case.
+    pub fn index_of(&self, character : char) -> Option<usize> {
+        self.index.get(&character).map(|&(index, _)| index)
+    }
+
+    pub fn contains(&self, character : char) -> bool {
+        self.index.contains_key(&character)
+    }
+
+    /// Keeps only the symbols of `text`, folded to their canonical case.
+    pub fn normalise(&self, text : &str) -> String {
+        text.chars()
+            .filter_map(|character| self.index_of(character).map(|index| self.symbols[index]))
+            .collect()
+    }
+
+    /// Replaces every symbol of `text` by the symbol at `f(index)`, keeping
+    /// its case, and leaves every other character where it is.
+    pub fn map_symbols<F : FnMut(usize) -> usize>(&self, text : &str, mut f : F) -> String {
+        text.chars().map(|character| {
+            match self.index.get(&character) {
+                Some(&(index, upper)) => self.render(f(index), upper),
+                None => character
+            }
+        }).collect()
+    }
+
+    /// Prefixes a serialised key with the spec of the alphabet it belongs to,
+    /// separated by a tab. English keys are left bare, as they always were.
+    pub fn tag(&self, body : &str) -> String {
+        if self.spec == "english" {
+            body.to_string()
+        } else {
+            format!("{}\t{}", self.spec, body)
+        }
+    }
+
+    /// Splits text written by `tag` back into its alphabet and key.
+    pub fn untag(text : &str) -> Option<(Alphabet, &str)> {
+        let text = text.trim_end_matches(['\n', '\r']);
+        let mut parts = text.splitn(2, '\t');
+        match (parts.next(), parts.next()) {
+            (Some(spec), Some(body)) => Alphabet::from_spec(spec).ok().map(|alphabet| (alphabet, body)),
+            _ => Some((Alphabet::english(), text.trim()))
+        }
+    }
+
+    /// `symbol` in upper case when `upper` is set and it has a single-character
+    /// upper-case form that belongs to this alphabet.
+    fn render(&self, index : usize, upper : bool) -> char {
+        let symbol = self.symbols[index];
+        if upper {
+            let mut upper = symbol.to_uppercase();
+            if let (Some(upper), None) = (upper.next(), upper.next()) {
+                if self.index.get(&upper) == Some(&(index, true)) {
+                    return upper;
+                }
+            }
+        }
+        symbol
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserves_case_and_punctuation() {
+        let alphabet = Alphabet::english();
+        let shifted = alphabet.map_symbols("Hello, World 42!", |index| (index + 1) % 26);
+        assert_eq!(shifted, "Ifmmp, Xpsme 42!");
+        assert_eq!(alphabet.normalise("Hello, World 42!"), "helloworld");
+    }
+
+    #[test]
+    fn extended_latin_handles_multibyte_letters() {
+        let alphabet = Alphabet::extended_latin();
+        assert_eq!(alphabet.normalise("Ça déjà vu, Œuvre"), "çadéjàvuœuvre");
+        assert_eq!(alphabet.map_symbols("Éa", |index| index), "Éa");
+        // 'š' is not a symbol, and must not be mistaken for 'a' like `as u8` would
+        assert_eq!(alphabet.normalise("š"), "");
+    }
+
+    #[test]
+    fn user_defined_symbols_keep_distinct_cases() {
+        let alphabet = Alphabet::from_spec("symbols:aAb").unwrap();
+        assert_eq!(alphabet.len(), 3);
+        assert_eq!(alphabet.index_of('A'), Some(1));
+        assert_eq!(alphabet.index_of('B'), Some(2));
+        assert!(Alphabet::from_spec("symbols:aa").is_err());
+        assert_eq!(Alphabet::from_spec(alphabet.spec()).unwrap(), alphabet);
+    }
+}
@@ -0,0 +1,346 @@
+extern crate rand;
+
+use ::std::io::{
+    Write,
+    Error
+};
+use ::std::sync::Arc;
+use ::std::sync::atomic::{
+    AtomicBool,
+    AtomicUsize,
+    Ordering
+};
+use ::std::time::Instant;
+use self::rand::Rng;
+use alphabet::Alphabet;
+use ciphers::{
+    Cipher,
+    CipherEvaluator
+};
+use cracker::{
+    StrategyConfig,
+    StrategyKind
+};
+use scoring::Scorer;
+use GA::{
+    Generator,
+    Evaluator,
+    GeneticAlgorithm,
+    Monitor,
+    Termination,
+    seeded_rng
+};
+
+/// What to benchmark: every strategy is run once per seed on every passage
+/// of every length, each run stopping after roughly `budget` evaluations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkConfig {
+    pub strategies : Vec<StrategyConfig>,
+    pub lengths : Vec<usize>,
+    pub passages : usize,
+    pub seeds : Vec<usize>,
+    pub budget : usize,
+    /// Seeds the choice of passages and the keys they are encrypted with.
+    pub seed : usize
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> BenchmarkConfig {
+        let strategy = |kind| StrategyConfig { kind, iterations : u32::MAX, samples : 10 };
+
+        BenchmarkConfig {
+            strategies : vec![
+                strategy(StrategyKind::HillClimbing),
+                strategy(StrategyKind::SteepestAscent),
+                strategy(StrategyKind::SteepestAscentReplacement)
+            ],
+            lengths : vec![100, 250, 500, 1000],
+            passages : 3,
+            seeds : vec![1, 2, 3],
+            budget : 20000,
+            seed : 0
+        }
+    }
+}
+
+/// The outcome of one strategy run on one passage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub strategy : StrategyConfig,
+    pub length : usize,
+    pub passage : usize,
+    pub seed : usize,
+    pub evaluations : usize,
+    /// Whether the recovered key decrypts the passage exactly. Keys can differ
+    /// on symbols the passage never uses, so the keys themselves are not compared.
+    pub recovered : bool,
+    /// Fraction of characters of the passage decrypted correctly.
+    pub accuracy : f64,
+    pub fitness : f64,
+    pub seconds : f64
+}
+
+/// Results of one strategy at one passage length, over all passages and seeds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub strategy : StrategyConfig,
+    pub length : usize,
+    pub runs : usize,
+    pub recovery_rate : f64,
+    pub mean_accuracy : f64,
+    pub mean_seconds : f64,
+    pub mean_evaluations : f64
+}
+
+/// Counts evaluations and raises `exhausted` once `budget` have been made, so
+/// a `Termination` watching the flag stops the strategy at the end of that
+/// generation.
+pub struct BudgetedEvaluator<E : Evaluator> {
+    inner : E,
+    budget : usize,
+    evaluations : AtomicUsize,
+    exhausted : Arc<AtomicBool>
+}
+
+impl <E : Evaluator> BudgetedEvaluator<E> {
+    pub fn new(inner : E, budget : usize) -> BudgetedEvaluator<E> {
+        BudgetedEvaluator {
+            inner,
+            budget,
+            evaluations : AtomicUsize::new(0),
+            exhausted : Arc::new(AtomicBool::new(budget == 0))
+        }
+    }
+
+    pub fn exhausted(&self) -> Arc<AtomicBool> {
+        self.exhausted.clone()
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.load(Ordering::SeqCst)
+    }
+}
+
+impl <E : Evaluator> Evaluator for BudgetedEvaluator<E> {
+    type Genome = E::Genome;
+
+    fn fitness(&self, genome : &E::Genome) -> f64 {
+        if self.evaluations.fetch_add(1, Ordering::SeqCst) + 1 >= self.budget {
+            self.exhausted.store(true, Ordering::SeqCst);
+        }
+        self.inner.fitness(genome)
+    }
+}
+
+/// Splits a corpus into the text a model is trained on and the last
+/// `held_out` fraction that passages are drawn from.
+pub fn split_corpus(corpus : &str, held_out : f64) -> (String, String) {
+    let length = corpus.chars().count();
+    let split = length - (length as f64 * held_out.clamp(0.0, 1.0)) as usize;
+
+    (corpus.chars().take(split).collect(), corpus.chars().skip(split).collect())
+}
+
+/// Runs the benchmark described by `config`. Passages are cut from
+/// `held_out`, encrypted with keys drawn from the generator `generator`
+/// builds, and attacked with `scorer`, which should not have seen them.
+pub fn run_benchmark<G, F>(generator : F,
+                           scorer : Arc<dyn Scorer>,
+                           alphabet : Arc<Alphabet>,
+                           held_out : &str,
+                           config : &BenchmarkConfig) -> Result<Vec<RunResult>, String>
+    where G : 'static + Generator,
+          G::Genome : 'static + Cipher,
+          F : Fn() -> G
+{
+    let text : Vec<char> = held_out.chars().collect();
+    let mut rng = seeded_rng(config.seed);
+    let mut results : Vec<RunResult> = vec!();
+
+    for &length in config.lengths.iter() {
+        if length == 0 || length > text.len() {
+            return Err(format!("held-out text has {} characters, too few for passages of {}", text.len(), length));
+        }
+
+        for passage in 0..config.passages {
+            let offset = rng.gen_range(0, text.len() - length + 1);
+            let plaintext : String = text[offset..offset + length].iter().collect();
+            let ciphertext = generator().create_new(&mut rng).encipher(&plaintext);
+
+            for strategy in config.strategies.iter() {
+                for &seed in config.seeds.iter() {
+                    let evaluator = BudgetedEvaluator::new(
+                        CipherEvaluator::new(Box::new(scorer.clone()), alphabet.clone(), ciphertext.clone()),
+                        config.budget
+                    );
+                    let monitor = Monitor::new().termination(Termination::new().interrupt(evaluator.exhausted()));
+                    let mut search = strategy.build(seeded_rng(seed), monitor);
+                    let mut genetic_algorithm = GeneticAlgorithm::new(generator(), evaluator);
+
+                    let started = Instant::now();
+                    let best = genetic_algorithm.execute_strategy(&mut *search);
+                    let seconds = started.elapsed().as_secs_f64();
+
+                    let evaluations = genetic_algorithm.evaluator().evaluations();
+                    let decrypted = best.decipher(&ciphertext);
+                    let accuracy = character_accuracy(&decrypted, &plaintext);
+
+                    results.push(RunResult {
+                        strategy : *strategy,
+                        length,
+                        passage,
+                        seed,
+                        evaluations,
+                        recovered : decrypted == plaintext,
+                        accuracy,
+                        fitness : genetic_algorithm.evaluate(&best),
+                        seconds
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Fraction of positions at which `decrypted` has the character of `plaintext`.
+pub fn character_accuracy(decrypted : &str, plaintext : &str) -> f64 {
+    let length = plaintext.chars().count();
+    if length == 0 {
+        return 1.0;
+    }
+
+    let correct = decrypted.chars().zip(plaintext.chars()).filter(|&(a, b)| a == b).count();
+    correct as f64 / length as f64
+}
+
+/// Groups results by strategy and passage length, in the order they first appear.
+pub fn summarise(results : &[RunResult]) -> Vec<Summary> {
+    let mut groups : Vec<(StrategyConfig, usize, Vec<&RunResult>)> = vec!();
+
+    for result in results.iter() {
+        match groups.iter_mut().find(|group| group.0 == result.strategy && group.1 == result.length) {
+            Some(group) => group.2.push(result),
+            None => groups.push((result.strategy, result.length, vec![result]))
+        }
+    }
+
+    groups.into_iter().map(|(strategy, length, runs)| {
+        let count = runs.len() as f64;
+        let mean = |value : &dyn Fn(&RunResult) -> f64| runs.iter().map(|run| value(run)).sum::<f64>() / count;
+
+        Summary {
+            strategy,
+            length,
+            runs : runs.len(),
+            recovery_rate : mean(&|run| if run.recovered { 1.0 } else { 0.0 }),
+            mean_accuracy : mean(&|run| run.accuracy),
+            mean_seconds : mean(&|run| run.seconds),
+            mean_evaluations : mean(&|run| run.evaluations as f64)
+        }
+    }).collect()
+}
+
+/// Writes one CSV row per run, with a header.
+pub fn write_runs<W : Write>(writer : &mut W, results : &[RunResult]) -> Result<(), Error> {
+    writeln!(writer, "strategy,samples,length,passage,seed,evaluations,recovered,accuracy,fitness,seconds")?;
+    for run in results.iter() {
+        writeln!(writer, "{},{},{},{},{},{},{},{:.4},{:.4},{:.4}",
+                 run.strategy.kind, samples(&run.strategy), run.length, run.passage, run.seed,
+                 run.evaluations, run.recovered, run.accuracy, run.fitness, run.seconds)?;
+    }
+    Ok(())
+}
+
+/// Writes one CSV row per strategy and passage length, with a header.
+pub fn write_summary<W : Write>(writer : &mut W, summaries : &[Summary]) -> Result<(), Error> {
+    writeln!(writer, "strategy,samples,length,runs,recovery_rate,mean_accuracy,mean_seconds,mean_evaluations")?;
+    for summary in summaries.iter() {
+        writeln!(writer, "{},{},{},{},{:.4},{:.4},{:.4},{:.1}",
+                 summary.strategy.kind, samples(&summary.strategy), summary.length, summary.runs,
+                 summary.recovery_rate, summary.mean_accuracy, summary.mean_seconds, summary.mean_evaluations)?;
+    }
+    Ok(())
+}
+
+/// Plain hill climbing ignores the sample count, so it is left blank.
+fn samples(strategy : &StrategyConfig) -> String {
+    match strategy.kind {
+        StrategyKind::HillClimbing => String::new(),
+        _ => strategy.samples.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciphers::{AffineGenerator, SubstitutionGenerator};
+    use ngram::NgramFrequency;
+
+    const ENGLISH : &str = "it is a truth universally acknowledged that a single man in possession of a good \
+        fortune must be in want of a wife however little known the feelings or views of such a man may be on \
+        his first entering a neighbourhood this truth is so well fixed in the minds of the surrounding families \
+        that he is considered as the rightful property of some one or other of their daughters my dear mr bennet \
+        said his lady to him one day have you heard that netherfield park is let at last mr bennet replied that \
+        he had not but it is returned she for mrs long has just been here and she told me all about it";
+
+    fn config(kind : StrategyKind, budget : usize) -> BenchmarkConfig {
+        BenchmarkConfig {
+            strategies : vec![StrategyConfig { kind, iterations : u32::MAX, samples : 5 }],
+            lengths : vec![40, 80],
+            passages : 2,
+            seeds : vec![1, 2],
+            budget,
+            seed : 7
+        }
+    }
+
+    #[test]
+    fn budget_limits_evaluations() {
+        let alphabet = Arc::new(Alphabet::english());
+        let corpus = alphabet.normalise(ENGLISH);
+        let scorer : Arc<dyn Scorer> = Arc::new(NgramFrequency::generate_from(&corpus, 3));
+        let generator = || SubstitutionGenerator { alphabet : alphabet.clone(), crib : None };
+
+        let results = run_benchmark(generator, scorer, alphabet.clone(), &corpus, &config(StrategyKind::SteepestAscentReplacement, 50)).unwrap();
+
+        assert_eq!(results.len(), 2 * 2 * 2);
+        for run in results.iter() {
+            // a generation in progress when the budget runs out is finished
+            assert!(run.evaluations >= 50 && run.evaluations < 50 + 5 + 2);
+            assert!(run.accuracy >= 0.0 && run.accuracy <= 1.0);
+        }
+    }
+
+    #[test]
+    fn recovers_affine_keys_and_summarises() {
+        let alphabet = Arc::new(Alphabet::english());
+        let (training, held_out) = split_corpus(&alphabet.normalise(ENGLISH), 0.3);
+        let scorer : Arc<dyn Scorer> = Arc::new(NgramFrequency::generate_from(&training, 3));
+        let generator = || AffineGenerator { alphabet : alphabet.clone() };
+
+        let results = run_benchmark(generator, scorer, alphabet.clone(), &held_out, &config(StrategyKind::HillClimbing, 2000)).unwrap();
+        let summaries = summarise(&results);
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].runs, 4);
+        assert!(summaries.iter().all(|summary| summary.recovery_rate == 1.0 && summary.mean_accuracy == 1.0));
+
+        let mut csv : Vec<u8> = vec!();
+        write_summary(&mut csv, &summaries).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(1).unwrap().starts_with("hill-climbing,,40,4,1.0000,1.0000,"));
+    }
+
+    #[test]
+    fn rejects_passages_longer_than_held_out_text() {
+        let alphabet = Arc::new(Alphabet::english());
+        let scorer : Arc<dyn Scorer> = Arc::new(NgramFrequency::generate_from("abc", 2));
+        let generator = || AffineGenerator { alphabet : alphabet.clone() };
+
+        assert!(run_benchmark(generator, scorer, alphabet.clone(), "short", &config(StrategyKind::HillClimbing, 10)).is_err());
+    }
+}
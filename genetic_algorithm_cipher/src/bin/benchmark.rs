@@ -0,0 +1,82 @@
+extern crate genetic_algorithm_cipher;
+
+use genetic_algorithm_cipher::benchmark::{
+    run_benchmark,
+    split_corpus,
+    summarise,
+    write_runs,
+    write_summary,
+    RunResult
+};
+use genetic_algorithm_cipher::cli::benchmark::{
+    BenchmarkOptions,
+    BENCHMARK_USAGE
+};
+use genetic_algorithm_cipher::ciphers::{
+    CipherFamily,
+    SubstitutionGenerator,
+    VigenereGenerator,
+    TranspositionGenerator,
+    AffineGenerator
+};
+use genetic_algorithm_cipher::corpus::load_corpus;
+use genetic_algorithm_cipher::ngram::NgramFrequency;
+use genetic_algorithm_cipher::scoring::Scorer;
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::process;
+use std::sync::Arc;
+
+fn fail(message : &str) -> ! {
+    eprintln!("{}\n\n{}", message, BENCHMARK_USAGE);
+    process::exit(2)
+}
+
+fn main() {
+    let options = BenchmarkOptions::parse(::std::env::args().skip(1)).unwrap_or_else(|e| fail(&e));
+    if options.help {
+        println!("{}", BENCHMARK_USAGE);
+        return;
+    }
+
+    let alphabet = Arc::new(options.alphabet.clone());
+    let corpus = load_corpus(&options.corpus, &alphabet).unwrap_or_else(|e| fail(&format!("Couldn't load corpus: {}", e)));
+    let (training, held_out) = split_corpus(&corpus, options.held_out);
+    let scorer : Arc<dyn Scorer> = Arc::new(NgramFrequency::generate_from(&training, options.order));
+    eprintln!("Trained on {} characters, drawing passages from {}", training.chars().count(), held_out.chars().count());
+
+    let config = &options.config;
+    let results : Result<Vec<RunResult>, String> = match options.family {
+        CipherFamily::Substitution =>
+            run_benchmark(|| SubstitutionGenerator { alphabet : alphabet.clone(), crib : None }, scorer, alphabet.clone(), &held_out, config),
+        CipherFamily::Vigenere =>
+            run_benchmark(|| VigenereGenerator { alphabet : alphabet.clone(), key_length : options.key_length }, scorer, alphabet.clone(), &held_out, config),
+        CipherFamily::Transposition =>
+            run_benchmark(|| TranspositionGenerator { alphabet : alphabet.clone(), columns : options.columns }, scorer, alphabet.clone(), &held_out, config),
+        CipherFamily::Affine =>
+            run_benchmark(|| AffineGenerator { alphabet : alphabet.clone() }, scorer, alphabet.clone(), &held_out, config),
+        CipherFamily::Playfair => unreachable!("rejected while parsing options")
+    };
+    let results = results.unwrap_or_else(|e| fail(&e));
+
+    if let Some(ref path) = options.runs {
+        if let Err(e) = File::create(path).and_then(|mut file| write_runs(&mut file, &results)) {
+            eprintln!("Couldn't write {}: {}", path, e);
+        }
+    }
+
+    let summaries = summarise(&results);
+    let written = match options.output {
+        Some(ref path) => File::create(path).and_then(|mut file| write_summary(&mut file, &summaries)),
+        None => {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            write_summary(&mut stdout, &summaries).and_then(|_| stdout.flush())
+        }
+    };
+    if let Err(e) = written {
+        eprintln!("Couldn't write summary: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,157 @@
+extern crate rand;
+
+use ::std::sync::Arc;
+use self::rand::Rng;
+use alphabet::Alphabet;
+use GA::interface::{
+    Genome,
+    Generator,
+    SerialisableGenome
+};
+use super::Cipher;
+
+/// The multipliers with an inverse modulo `size`.
+fn multipliers(size : usize) -> Vec<usize> {
+    (1..size).filter(|&a| gcd(a, size) == 1).collect()
+}
+
+fn gcd(a : usize, b : usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Enciphers each symbol `x` as `(a·x + b) mod n`, `n` being the size of
+/// the alphabet, leaving other characters alone.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AffineCipher {
+    alphabet : Arc<Alphabet>,
+    a : usize,
+    b : usize
+}
+
+impl AffineCipher {
+    /// Returns `None` if `a` has no inverse modulo the alphabet size.
+    pub fn new(alphabet : Arc<Alphabet>, a : usize, b : usize) -> Option<AffineCipher> {
+        let size = alphabet.len();
+        if gcd(a % size, size) == 1 {
+            Some(AffineCipher { alphabet, a : a % size, b : b % size })
+        } else {
+            None
+        }
+    }
+
+    pub fn random<R : Rng>(alphabet : Arc<Alphabet>, rng : &mut R) -> AffineCipher {
+        let multipliers = multipliers(alphabet.len());
+        AffineCipher {
+            a : multipliers[rng.gen_range(0, multipliers.len())],
+            b : rng.gen_range(0, alphabet.len()),
+            alphabet
+        }
+    }
+
+    fn inverse(&self) -> usize {
+        let size = self.alphabet.len();
+        (1..size).find(|inverse| (self.a * inverse) % size == 1).unwrap()
+    }
+}
+
+impl Genome for AffineCipher {
+    fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        if rng.gen::<bool>() {
+            let multipliers = multipliers(self.alphabet.len());
+            self.a = multipliers[rng.gen_range(0, multipliers.len())];
+        } else {
+            self.b = rng.gen_range(0, self.alphabet.len());
+        }
+    }
+
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+        let alphabet = parent_a.alphabet.clone();
+        if rng.gen::<bool>() {
+            AffineCipher { alphabet, a : parent_a.a, b : parent_b.b }
+        } else {
+            AffineCipher { alphabet, a : parent_b.a, b : parent_a.b }
+        }
+    }
+
+    fn clone(&self) -> Self {
+        AffineCipher { alphabet : self.alphabet.clone(), a : self.a, b : self.b }
+    }
+}
+
+impl SerialisableGenome for AffineCipher {
+    fn serialise(&self) -> String {
+        self.alphabet.tag(&format!("{},{}", self.a, self.b))
+    }
+
+    fn deserialise(text : &str) -> Option<Self> {
+        let (alphabet, body) = Alphabet::untag(text)?;
+        let mut parts = body.split(',');
+        let a = parts.next()?.parse::<usize>().ok()?;
+        let b = parts.next()?.parse::<usize>().ok()?;
+        if parts.next().is_some() || b >= alphabet.len() {
+            return None;
+        }
+        AffineCipher::new(Arc::new(alphabet), a, b)
+    }
+}
+
+impl Cipher for AffineCipher {
+    fn encipher(&self, text : &str) -> String {
+        let (a, b, size) = (self.a, self.b, self.alphabet.len());
+        self.alphabet.map_symbols(text, |x| (a * x + b) % size)
+    }
+
+    fn decipher(&self, text : &str) -> String {
+        let (inverse, b, size) = (self.inverse(), self.b, self.alphabet.len());
+        self.alphabet.map_symbols(text, |y| (inverse * (y + size - b)) % size)
+    }
+}
+
+pub struct AffineGenerator {
+    pub alphabet : Arc<Alphabet>
+}
+
+impl Generator for AffineGenerator {
+    type Genome = AffineCipher;
+
+    fn create_new<R : Rng>(&self, rng : &mut R) -> AffineCipher {
+        AffineCipher::random(self.alphabet.clone(), rng)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Arc<Alphabet> {
+        Arc::new(Alphabet::english())
+    }
+
+    #[test]
+    fn rejects_non_invertible_multipliers() {
+        assert_eq!(AffineCipher::new(english(), 13, 1), None);
+        assert_eq!(AffineCipher::new(english(), 2, 1), None);
+        assert!(AffineCipher::new(Arc::new(Alphabet::from_spec("symbols:abcde").unwrap()), 2, 1).is_some());
+    }
+
+    #[test]
+    fn decipher_inverts_encipher() {
+        let text = "Affine ciphers, e.g. (5x + 8) mod 26".to_string();
+        let cipher = AffineCipher::new(english(), 5, 8).unwrap();
+        let encrypted = cipher.encipher(&text);
+
+        assert_eq!(&encrypted[..6], "Ihhwvc");
+        assert_eq!(cipher.decipher(&encrypted), text);
+    }
+
+    #[test]
+    fn key_round_trips() {
+        let cipher = AffineCipher::new(english(), 21, 4).unwrap();
+        assert_eq!(cipher.serialise(), "21,4");
+        assert_eq!(AffineCipher::deserialise(&cipher.serialise()), Some(cipher));
+
+        let cipher = AffineCipher::new(Arc::new(Alphabet::extended_latin()), 7, 40).unwrap();
+        assert_eq!(AffineCipher::deserialise(&cipher.serialise()), Some(cipher));
+    }
+}
@@ -0,0 +1,165 @@
+extern crate rand;
+
+use ::std::marker::PhantomData;
+use ::std::str::FromStr;
+use ::std::sync::Arc;
+use self::rand::Rng;
+use GA::interface::{
+    Genome,
+    Evaluator
+};
+use scoring::Scorer;
+use alphabet::Alphabet;
+
+pub mod affine;
+pub mod playfair;
+pub mod transposition;
+pub mod vigenere;
+
+pub use self::affine::{AffineCipher, AffineGenerator};
+pub use self::playfair::{PlayfairCipher, PlayfairGenerator};
+pub use self::transposition::{ColumnarTranspositionCipher, TranspositionGenerator};
+pub use self::vigenere::{VigenereCipher, VigenereGenerator};
+pub use alphabet::{SubstitutionCipher, SubstitutionGenerator};
+
+/// A cipher key that can be searched for by a `GeneticAlgorithm`.
+pub trait Cipher : Genome {
+    fn encipher(&self, text : &str) -> String;
+    fn decipher(&self, text : &str) -> String;
+}
+
+/// Rates a key by how language-like its decryption of a fixed ciphertext is.
+/// The decryption is reduced to the symbols of `alphabet`, folded to one
+/// case, before scoring, matching how corpora are prepared.
+pub struct CipherEvaluator<T : Cipher> {
+    genome : PhantomData<T>,
+    scorer : Box<dyn Scorer>,
+    alphabet : Arc<Alphabet>,
+    encrypted_text : String
+}
+
+impl <T : Cipher> CipherEvaluator<T> {
+    pub fn new(scorer : Box<dyn Scorer>, alphabet : Arc<Alphabet>, encrypted_text : String) -> CipherEvaluator<T> {
+        CipherEvaluator {
+            genome : PhantomData,
+            scorer,
+            alphabet,
+            encrypted_text
+        }
+    }
+
+    pub fn alphabet(&self) -> &Arc<Alphabet> {
+        &self.alphabet
+    }
+
+    pub fn encrypted_text(&self) -> &String {
+        &self.encrypted_text
+    }
+}
+
+impl <T : Cipher> Evaluator for CipherEvaluator<T> {
+    type Genome = T;
+    fn fitness(&self, genome : &T) -> f64 {
+        self.scorer.score(&self.alphabet.normalise(&genome.decipher(&self.encrypted_text)))
+    }
+}
+
+/// The cipher families the cracker knows how to attack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherFamily {
+    Substitution,
+    Vigenere,
+    Transposition,
+    Playfair,
+    Affine
+}
+
+impl FromStr for CipherFamily {
+    type Err = String;
+
+    fn from_str(name : &str) -> Result<CipherFamily, String> {
+        match name {
+            "substitution" => Ok(CipherFamily::Substitution),
+            "vigenere" => Ok(CipherFamily::Vigenere),
+            "transposition" => Ok(CipherFamily::Transposition),
+            "playfair" => Ok(CipherFamily::Playfair),
+            "affine" => Ok(CipherFamily::Affine),
+            _ => Err(format!("unknown cipher family {}", name))
+        }
+    }
+}
+
+/// One-point order crossover for keys that are permutations: the child takes
+/// `parent_a` up to a random cut, then the remaining symbols in the order they
+/// appear in `parent_b`.
+pub fn permutation_crossover<T : Copy + PartialEq, R : Rng>(parent_a : &[T], parent_b : &[T], rng : &mut R) -> Vec<T> {
+    let cut = rng.gen_range(0, parent_a.len() + 1);
+    let mut child : Vec<T> = parent_a[..cut].to_vec();
+
+    for symbol in parent_b.iter() {
+        if !child.contains(symbol) {
+            child.push(*symbol);
+        }
+    }
+
+    child
+}
+
+/// Swaps two random positions of a permutation key.
+pub fn permutation_mutate<T, R : Rng>(key : &mut [T], rng : &mut R) {
+    if key.len() < 2 {
+        return;
+    }
+
+    let a = rng.gen_range(0, key.len());
+    let b = rng.gen_range(0, key.len());
+    key.swap(a, b);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GA::{
+        GeneticAlgorithm,
+        seeded_rng
+    };
+    use GA::strategy::HillClimbing::HillClimbingStrategy;
+    use ngram::NgramFrequency;
+
+    pub const ENGLISH : &str = "it was the best of times it was the worst of times it was the age of wisdom \
+        it was the age of foolishness it was the epoch of belief it was the epoch of incredulity it was the \
+        season of light it was the season of darkness it was the spring of hope it was the winter of despair \
+        we had everything before us we had nothing before us we were all going direct to heaven we were all \
+        going direct the other way in short the period was so far like the present period that some of its \
+        noisiest authorities insisted on its being received for good or for evil in the superlative degree \
+        of comparison only";
+
+    #[test]
+    fn permutation_crossover_keeps_a_permutation() {
+        let mut rng = seeded_rng(5);
+        let a : Vec<usize> = (0..10).collect();
+        let b : Vec<usize> = (0..10).rev().collect();
+
+        for _ in 0..20 {
+            let mut child = permutation_crossover(&a, &b, &mut rng);
+            child.sort();
+            assert_eq!(child, a);
+        }
+    }
+
+    #[test]
+    fn hill_climbing_breaks_affine_cipher() {
+        let alphabet = Arc::new(Alphabet::english());
+        let plaintext = ENGLISH.to_string();
+        let key = AffineCipher::new(alphabet.clone(), 7, 3).unwrap();
+        let encrypted = key.encipher(&plaintext);
+
+        let table = NgramFrequency::generate_from(&alphabet.normalise(&plaintext), 3);
+        let evaluator : CipherEvaluator<AffineCipher> = CipherEvaluator::new(Box::new(table), alphabet.clone(), encrypted);
+        let mut algorithm = GeneticAlgorithm::new(AffineGenerator { alphabet }, evaluator);
+        let mut strategy = HillClimbingStrategy::new(2000, seeded_rng(11));
+
+        assert_eq!(algorithm.execute_strategy(&mut strategy).decipher(&key.encipher(&plaintext)), plaintext);
+    }
+}
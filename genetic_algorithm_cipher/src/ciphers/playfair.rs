@@ -0,0 +1,195 @@
+extern crate rand;
+
+use self::rand::Rng;
+use alphabet::Alphabet;
+use GA::interface::{
+    Genome,
+    Generator,
+    SerialisableGenome
+};
+use super::{
+    Cipher,
+    permutation_crossover,
+    permutation_mutate
+};
+
+const SIDE : usize = 5;
+
+/// Playfair digraph cipher over a 5x5 key square of the letters a-z without j,
+/// which is folded into i. The square only has room for English, so unlike the
+/// other ciphers this ignores the configured alphabet and drops case, digits
+/// and punctuation.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PlayfairCipher {
+    square : [u8; SIDE * SIDE]
+}
+
+impl PlayfairCipher {
+    /// Builds the square from a keyword in the usual way: its distinct letters
+    /// first, then the rest of the alphabet.
+    pub fn from_keyword(keyword : &str) -> PlayfairCipher {
+        let mut letters : Vec<u8> = vec!();
+
+        for character in Alphabet::english().normalise(keyword).bytes().chain(b'a'..b'z' + 1) {
+            let character = if character == b'j' { b'i' } else { character };
+            if !letters.contains(&character) {
+                letters.push(character);
+            }
+        }
+
+        let mut square = [0u8; SIDE * SIDE];
+        square.copy_from_slice(&letters);
+        PlayfairCipher { square }
+    }
+
+    pub fn random<R : Rng>(rng : &mut R) -> PlayfairCipher {
+        let mut cipher = PlayfairCipher::from_keyword("");
+        rng.shuffle(&mut cipher.square);
+        cipher
+    }
+
+    fn position(&self, character : u8) -> (usize, usize) {
+        let index = self.square.iter().position(|&cell| cell == character).unwrap();
+        (index / SIDE, index % SIDE)
+    }
+
+    fn at(&self, row : usize, column : usize) -> char {
+        self.square[(row % SIDE) * SIDE + column % SIDE] as char
+    }
+
+    /// Letters of `text` with j folded into i.
+    fn prepare(text : &str) -> Vec<u8> {
+        Alphabet::english().normalise(text).bytes().map(|character| if character == b'j' { b'i' } else { character }).collect()
+    }
+
+    /// Moves each digraph one step along its row or column (`shift` of 1 to
+    /// encipher, `SIDE - 1` to decipher), or swaps columns for rectangles.
+    fn transform(&self, pairs : &[u8], shift : usize) -> String {
+        let mut result = String::with_capacity(pairs.len());
+
+        for pair in pairs.chunks(2) {
+            let (row_a, column_a) = self.position(pair[0]);
+            let (row_b, column_b) = self.position(pair[1]);
+
+            if row_a == row_b {
+                result.push(self.at(row_a, column_a + shift));
+                result.push(self.at(row_b, column_b + shift));
+            } else if column_a == column_b {
+                result.push(self.at(row_a + shift, column_a));
+                result.push(self.at(row_b + shift, column_b));
+            } else {
+                result.push(self.at(row_a, column_b));
+                result.push(self.at(row_b, column_a));
+            }
+        }
+
+        result
+    }
+}
+
+impl Genome for PlayfairCipher {
+    fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        match rng.gen_range(0, 10) {
+            // occasionally move whole rows or columns, which keeps most digraphs intact
+            0 => {
+                let (a, b) = (rng.gen_range(0, SIDE), rng.gen_range(0, SIDE));
+                for column in 0..SIDE {
+                    self.square.swap(a * SIDE + column, b * SIDE + column);
+                }
+            },
+            1 => {
+                let (a, b) = (rng.gen_range(0, SIDE), rng.gen_range(0, SIDE));
+                for row in 0..SIDE {
+                    self.square.swap(row * SIDE + a, row * SIDE + b);
+                }
+            },
+            _ => permutation_mutate(&mut self.square, rng)
+        }
+    }
+
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+        let mut square = [0u8; SIDE * SIDE];
+        square.copy_from_slice(&permutation_crossover(&parent_a.square, &parent_b.square, rng));
+        PlayfairCipher { square }
+    }
+
+    fn clone(&self) -> Self {
+        PlayfairCipher { square : self.square }
+    }
+}
+
+impl SerialisableGenome for PlayfairCipher {
+    fn serialise(&self) -> String {
+        self.square.iter().map(|&cell| cell as char).collect()
+    }
+
+    fn deserialise(text : &str) -> Option<Self> {
+        let text = text.trim();
+        let cipher = PlayfairCipher::from_keyword(text);
+        if text.len() == SIDE * SIDE && cipher.serialise() == text {
+            Some(cipher)
+        } else {
+            None
+        }
+    }
+}
+
+impl Cipher for PlayfairCipher {
+    fn encipher(&self, text : &str) -> String {
+        let letters = PlayfairCipher::prepare(text);
+        let mut pairs : Vec<u8> = Vec::with_capacity(letters.len() + 1);
+
+        for &character in letters.iter() {
+            if pairs.len() % 2 == 1 && pairs[pairs.len() - 1] == character {
+                pairs.push(if character == b'x' { b'q' } else { b'x' });
+            }
+            pairs.push(character);
+        }
+        if pairs.len() % 2 == 1 {
+            pairs.push(if pairs[pairs.len() - 1] == b'x' { b'q' } else { b'x' });
+        }
+
+        self.transform(&pairs, 1)
+    }
+
+    fn decipher(&self, text : &str) -> String {
+        let mut pairs = PlayfairCipher::prepare(text);
+        if pairs.len() % 2 == 1 {
+            pairs.push(b'x');
+        }
+
+        self.transform(&pairs, SIDE - 1)
+    }
+}
+
+pub struct PlayfairGenerator;
+
+impl Generator for PlayfairGenerator {
+    type Genome = PlayfairCipher;
+
+    fn create_new<R : Rng>(&self, rng : &mut R) -> PlayfairCipher {
+        PlayfairCipher::random(rng)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enciphers_textbook_example() {
+        let cipher = PlayfairCipher::from_keyword("playfair example");
+        let encrypted = cipher.encipher("Hide the gold in the tree stump");
+
+        assert_eq!(encrypted, "bmodzbxdnabekudmuixmmouvif");
+        assert_eq!(cipher.decipher(&encrypted), "hidethegoldinthetrexestump");
+    }
+
+    #[test]
+    fn key_round_trips() {
+        let cipher = PlayfairCipher::from_keyword("monarchy");
+        assert_eq!(PlayfairCipher::deserialise(&cipher.serialise()), Some(cipher));
+        assert_eq!(PlayfairCipher::deserialise("abc"), None);
+    }
+}
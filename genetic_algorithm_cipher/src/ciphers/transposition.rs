@@ -0,0 +1,185 @@
+extern crate rand;
+
+use ::std::sync::Arc;
+use self::rand::Rng;
+use alphabet::Alphabet;
+use GA::interface::{
+    Genome,
+    Generator,
+    SerialisableGenome
+};
+use super::{
+    Cipher,
+    permutation_crossover,
+    permutation_mutate
+};
+
+/// Columnar transposition: the symbols of the text are written row by row
+/// into `order.len()` columns and read out column by column, `order[k]` being
+/// the column read k-th. The rearranged symbols are written back into the
+/// positions symbols occupied, so other characters stay where they were.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ColumnarTranspositionCipher {
+    alphabet : Arc<Alphabet>,
+    order : Vec<usize>
+}
+
+impl ColumnarTranspositionCipher {
+    /// Returns `None` unless `order` is a permutation of `0..order.len()`.
+    pub fn new(alphabet : Arc<Alphabet>, order : Vec<usize>) -> Option<ColumnarTranspositionCipher> {
+        let mut sorted = order.clone();
+        sorted.sort();
+        if order.is_empty() || sorted.iter().enumerate().any(|(i, &column)| i != column) {
+            return None;
+        }
+
+        Some(ColumnarTranspositionCipher { alphabet, order })
+    }
+
+    pub fn random<R : Rng>(alphabet : Arc<Alphabet>, columns : usize, rng : &mut R) -> ColumnarTranspositionCipher {
+        let mut order : Vec<usize> = (0..columns.max(1)).collect();
+        rng.shuffle(&mut order);
+        ColumnarTranspositionCipher { alphabet, order }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Number of letters that end up in `column` for a text of `length` letters.
+    fn column_length(&self, column : usize, length : usize) -> usize {
+        let columns = self.columns();
+        length / columns + if column < length % columns { 1 } else { 0 }
+    }
+
+    /// Rearranges the symbols of `text` with `permute`, leaving every other
+    /// character in place.
+    fn rearrange<F : Fn(&[char]) -> Vec<char>>(&self, text : &str, permute : F) -> String {
+        let symbols : Vec<char> = text.chars().filter(|&character| self.alphabet.contains(character)).collect();
+        let mut permuted = permute(&symbols).into_iter();
+
+        text.chars().map(|character| {
+            if self.alphabet.contains(character) { permuted.next().unwrap() } else { character }
+        }).collect()
+    }
+}
+
+impl Genome for ColumnarTranspositionCipher {
+    fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        permutation_mutate(&mut self.order, rng);
+    }
+
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+        ColumnarTranspositionCipher {
+            alphabet : parent_a.alphabet.clone(),
+            order : permutation_crossover(&parent_a.order, &parent_b.order, rng)
+        }
+    }
+
+    fn clone(&self) -> Self {
+        ColumnarTranspositionCipher { alphabet : self.alphabet.clone(), order : self.order.clone() }
+    }
+}
+
+impl SerialisableGenome for ColumnarTranspositionCipher {
+    fn serialise(&self) -> String {
+        self.alphabet.tag(&self.order.iter().map(|column| column.to_string()).collect::<Vec<_>>().join(","))
+    }
+
+    fn deserialise(text : &str) -> Option<Self> {
+        let (alphabet, body) = Alphabet::untag(text)?;
+        let order = body.split(',')
+            .map(|column| column.parse::<usize>().ok())
+            .collect::<Option<Vec<usize>>>()?;
+        ColumnarTranspositionCipher::new(Arc::new(alphabet), order)
+    }
+}
+
+impl Cipher for ColumnarTranspositionCipher {
+    fn encipher(&self, text : &str) -> String {
+        self.rearrange(text, |letters| {
+            let columns = self.columns();
+            let mut result = Vec::with_capacity(letters.len());
+
+            for &column in self.order.iter() {
+                result.extend(letters.iter().skip(column).step_by(columns));
+            }
+
+            result
+        })
+    }
+
+    fn decipher(&self, text : &str) -> String {
+        self.rearrange(text, |letters| {
+            let columns = self.columns();
+            let mut grid : Vec<Vec<char>> = vec![vec!(); columns];
+
+            let mut offset = 0;
+            for &column in self.order.iter() {
+                let length = self.column_length(column, letters.len());
+                grid[column] = letters[offset..offset + length].to_vec();
+                offset += length;
+            }
+
+            (0..letters.len()).map(|i| grid[i % columns][i / columns]).collect()
+        })
+    }
+}
+
+pub struct TranspositionGenerator {
+    pub alphabet : Arc<Alphabet>,
+    pub columns : usize
+}
+
+impl Generator for TranspositionGenerator {
+    type Genome = ColumnarTranspositionCipher;
+
+    fn create_new<R : Rng>(&self, rng : &mut R) -> ColumnarTranspositionCipher {
+        ColumnarTranspositionCipher::random(self.alphabet.clone(), self.columns, rng)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Arc<Alphabet> {
+        Arc::new(Alphabet::english())
+    }
+
+    #[test]
+    fn enciphers_by_columns() {
+        // w e a r e d / i s c o v e / r e d f l e / e a t o n c / e
+        let cipher = ColumnarTranspositionCipher::new(english(), vec![2, 5, 1, 3, 0, 4]).unwrap();
+        let text = "wearediscoveredfleeatonce".to_string();
+
+        assert_eq!(cipher.encipher(&text), "acdtdeecesearofowireeevln");
+    }
+
+    #[test]
+    fn keeps_punctuation_in_place() {
+        let cipher = ColumnarTranspositionCipher::new(english(), vec![2, 5, 1, 3, 0, 4]).unwrap();
+        let text = "We are discovered, flee at once!".to_string();
+        let encrypted = cipher.encipher(&text);
+
+        assert_eq!(encrypted, "ac dtd eecesearof, oWir ee evln!");
+        assert_eq!(cipher.decipher(&encrypted), text);
+    }
+
+    #[test]
+    fn decipher_inverts_encipher_for_ragged_columns() {
+        let cipher = ColumnarTranspositionCipher::new(english(), vec![3, 0, 4, 1, 2]).unwrap();
+        for length in 0..23 {
+            let text : String = "thequickbrownfoxjumpsov".chars().take(length).collect();
+            assert_eq!(cipher.decipher(&cipher.encipher(&text)), text);
+        }
+    }
+
+    #[test]
+    fn rejects_non_permutations() {
+        assert_eq!(ColumnarTranspositionCipher::new(english(), vec![0, 0, 1]), None);
+        assert_eq!(ColumnarTranspositionCipher::deserialise("0,2"), None);
+        assert!(ColumnarTranspositionCipher::deserialise("1,2,0").is_some());
+    }
+}
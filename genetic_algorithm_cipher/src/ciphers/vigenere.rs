@@ -0,0 +1,224 @@
+extern crate rand;
+
+use ::std::collections::HashMap;
+use ::std::sync::Arc;
+use self::rand::Rng;
+use alphabet::Alphabet;
+use GA::interface::{
+    Genome,
+    Generator,
+    SerialisableGenome
+};
+use scoring::index_of_coincidence;
+use super::Cipher;
+
+/// Vigenère cipher: the i-th symbol of the text is shifted by the
+/// `i mod key.len()`-th key symbol. Other characters pass through and do not
+/// advance the key.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VigenereCipher {
+    alphabet : Arc<Alphabet>,
+    key : Vec<usize>
+}
+
+impl VigenereCipher {
+    /// Returns `None` unless `keyword` is a non-empty run of symbols of `alphabet`.
+    pub fn from_keyword(alphabet : Arc<Alphabet>, keyword : &str) -> Option<VigenereCipher> {
+        let key = keyword.chars()
+            .map(|character| alphabet.index_of(character))
+            .collect::<Option<Vec<usize>>>()?;
+        if key.is_empty() {
+            return None;
+        }
+
+        Some(VigenereCipher { alphabet, key })
+    }
+
+    pub fn random<R : Rng>(alphabet : Arc<Alphabet>, key_length : usize, rng : &mut R) -> VigenereCipher {
+        VigenereCipher {
+            key : (0..key_length.max(1)).map(|_| rng.gen_range(0, alphabet.len())).collect(),
+            alphabet
+        }
+    }
+
+    pub fn keyword(&self) -> String {
+        self.key.iter().map(|&shift| self.alphabet.symbol(shift)).collect()
+    }
+
+    fn shift(&self, text : &str, forward : bool) -> String {
+        let size = self.alphabet.len();
+        let mut position = 0;
+
+        self.alphabet.map_symbols(text, |index| {
+            let shift = self.key[position % self.key.len()];
+            let shift = if forward { shift } else { size - shift };
+            position += 1;
+            (index + shift) % size
+        })
+    }
+}
+
+impl Genome for VigenereCipher {
+    fn mutate<R : Rng>(&mut self, rng : &mut R) {
+        let position = rng.gen_range(0, self.key.len());
+        self.key[position] = rng.gen_range(0, self.alphabet.len());
+    }
+
+    fn crossover<R : Rng>(parent_a : &Self, parent_b : &Self, rng : &mut R) -> Self {
+        let key = parent_a.key.iter().enumerate().map(|(i, &shift)| {
+            match parent_b.key.get(i) {
+                Some(&other) if rng.gen::<bool>() => other,
+                _ => shift
+            }
+        }).collect();
+
+        VigenereCipher { alphabet : parent_a.alphabet.clone(), key }
+    }
+
+    fn clone(&self) -> Self {
+        VigenereCipher { alphabet : self.alphabet.clone(), key : self.key.clone() }
+    }
+}
+
+impl SerialisableGenome for VigenereCipher {
+    fn serialise(&self) -> String {
+        self.alphabet.tag(&self.keyword())
+    }
+
+    fn deserialise(text : &str) -> Option<Self> {
+        let (alphabet, body) = Alphabet::untag(text)?;
+        VigenereCipher::from_keyword(Arc::new(alphabet), body)
+    }
+}
+
+impl Cipher for VigenereCipher {
+    fn encipher(&self, text : &str) -> String {
+        self.shift(text, true)
+    }
+
+    fn decipher(&self, text : &str) -> String {
+        self.shift(text, false)
+    }
+}
+
+pub struct VigenereGenerator {
+    pub alphabet : Arc<Alphabet>,
+    pub key_length : usize
+}
+
+impl Generator for VigenereGenerator {
+    type Genome = VigenereCipher;
+
+    fn create_new<R : Rng>(&self, rng : &mut R) -> VigenereCipher {
+        VigenereCipher::random(self.alphabet.clone(), self.key_length, rng)
+    }
+}
+
+/// Kasiski examination: for every trigram repeated in the ciphertext, the
+/// distance between occurrences is likely a multiple of the key length.
+/// Returns candidate lengths in `2..max_length + 1` with how many distances
+/// they divide, most likely first.
+pub fn kasiski_key_lengths(text : &str, alphabet : &Alphabet, max_length : usize) -> Vec<(usize, usize)> {
+    let letters : Vec<char> = alphabet.normalise(text).chars().collect();
+    let mut last_seen : HashMap<&[char], usize> = HashMap::new();
+    let mut votes : Vec<usize> = vec![0; max_length + 1];
+
+    for i in 0..letters.len().saturating_sub(2) {
+        let trigram = &letters[i..i + 3];
+        if let Some(previous) = last_seen.insert(trigram, i) {
+            let distance = i - previous;
+            for (length, count) in votes.iter_mut().enumerate().skip(2) {
+                if distance % length == 0 {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let mut candidates : Vec<(usize, usize)> = (2..max_length + 1)
+        .map(|length| (length, votes[length]))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    candidates
+}
+
+/// Mean index of coincidence of the `length` interleaved columns of the
+/// symbols of `text`.
+pub fn column_index_of_coincidence(text : &str, alphabet : &Alphabet, length : usize) -> f64 {
+    let letters : Vec<char> = alphabet.normalise(text).chars().collect();
+
+    let total : f64 = (0..length).map(|column| {
+        let column : String = letters.iter().skip(column).step_by(length).collect();
+        index_of_coincidence(&column)
+    }).sum();
+
+    total / length as f64
+}
+
+/// Estimates the key length as the shortest length in `1..max_length + 1`
+/// whose columns have a mean index of coincidence within 10% of the best.
+/// Multiples of the true length score just as well, hence the shortest.
+pub fn estimate_key_length(text : &str, alphabet : &Alphabet, max_length : usize) -> usize {
+    let max_length = max_length.max(1);
+    let scores : Vec<f64> = (1..max_length + 1)
+        .map(|length| column_index_of_coincidence(text, alphabet, length))
+        .collect();
+    let best = scores.iter().cloned().fold(0.0, f64::max);
+
+    (1..max_length + 1)
+        .find(|&length| scores[length - 1] >= best * 0.9)
+        .unwrap_or(1)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciphers::tests::ENGLISH;
+
+    fn english() -> Arc<Alphabet> {
+        Arc::new(Alphabet::english())
+    }
+
+    #[test]
+    fn enciphers_textbook_example() {
+        let cipher = VigenereCipher::from_keyword(english(), "lemon").unwrap();
+        let encrypted = cipher.encipher("Attack at dawn!");
+
+        assert_eq!(encrypted, "Lxfopv ef rnhr!");
+        assert_eq!(cipher.decipher(&encrypted), "Attack at dawn!");
+    }
+
+    #[test]
+    fn estimates_key_length() {
+        let alphabet = english();
+        let cipher = VigenereCipher::from_keyword(alphabet.clone(), "cipher").unwrap();
+        let encrypted = cipher.encipher(ENGLISH);
+
+        assert_eq!(estimate_key_length(&encrypted, &alphabet, 12), 6);
+        assert!(kasiski_key_lengths(&encrypted, &alphabet, 12).iter().take(3).any(|&(length, _)| length == 6 || length == 3));
+    }
+
+    #[test]
+    fn crossover_keeps_first_parent_length() {
+        let a = VigenereCipher::from_keyword(english(), "abcd").unwrap();
+        let b = VigenereCipher::from_keyword(english(), "zz").unwrap();
+        let child = VigenereCipher::crossover(&a, &b, &mut ::GA::seeded_rng(1));
+
+        assert_eq!(child.keyword().len(), 4);
+        assert_eq!(&child.keyword()[2..], "cd");
+    }
+
+    #[test]
+    fn shifts_over_extended_alphabet() {
+        let alphabet = Arc::new(Alphabet::extended_latin());
+        let cipher = VigenereCipher::from_keyword(alphabet.clone(), "clé").unwrap();
+        let text = "Déjà vu, Ærø!".to_string();
+        let encrypted = cipher.encipher(&text);
+
+        assert_eq!(encrypted.chars().count(), text.chars().count());
+        assert_eq!(cipher.decipher(&encrypted), text);
+        assert_eq!(VigenereCipher::deserialise(&cipher.serialise()), Some(cipher));
+    }
+}
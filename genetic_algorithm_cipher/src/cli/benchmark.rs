@@ -0,0 +1,155 @@
+use ::std::str::FromStr;
+use alphabet::Alphabet;
+use benchmark::BenchmarkConfig;
+use ciphers::CipherFamily;
+use cracker::StrategyKind;
+use super::number;
+
+pub const BENCHMARK_USAGE : &str = "usage: benchmark [options]
+
+Encrypts passages of held-out corpus text with random keys, attacks them with
+each strategy and writes a CSV summary of key recovery, accuracy and time.
+
+corpus and model:
+  --corpus PATH           corpus file or directory (default corpus.txt)
+  --alphabet SPEC         english, extended-latin or symbols:<symbols in order>
+  --order N               n-gram order (default 3)
+  --held-out F            fraction of the corpus kept out of the model (default 0.1)
+
+passages:
+  --cipher FAMILY         substitution, vigenere, transposition or affine
+  --key-length N          vigenere key length (default 5)
+  --columns N             transposition column count (default 5)
+  --lengths N[,N...]      passage lengths (default 100,250,500,1000)
+  --passages N            passages per length (default 3)
+  --seed N                seed for choosing passages and keys (default 0)
+
+search:
+  --strategies NAME[,..]  strategies to compare (default all)
+  --samples N             neighbours sampled per iteration (default 10)
+  --budget N              evaluations per run (default 20000)
+  --seeds N[,N...]        search seeds, one run each (default 1,2,3)
+
+output:
+  --output PATH           write the summary CSV here instead of stdout
+  --runs PATH             also write one CSV row per run here";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkOptions {
+    pub help : bool,
+    pub corpus : String,
+    pub alphabet : Alphabet,
+    pub order : usize,
+    pub held_out : f64,
+    pub family : CipherFamily,
+    pub key_length : usize,
+    pub columns : usize,
+    pub config : BenchmarkConfig,
+    pub output : Option<String>,
+    pub runs : Option<String>
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> BenchmarkOptions {
+        BenchmarkOptions {
+            help : false,
+            corpus : "corpus.txt".to_string(),
+            alphabet : Alphabet::english(),
+            order : 3,
+            held_out : 0.1,
+            family : CipherFamily::Substitution,
+            key_length : 5,
+            columns : 5,
+            config : BenchmarkConfig::default(),
+            output : None,
+            runs : None
+        }
+    }
+}
+
+impl BenchmarkOptions {
+    pub fn parse<I : Iterator<Item=String>>(args : I) -> Result<BenchmarkOptions, String> {
+        let mut options = BenchmarkOptions::default();
+        let mut samples : Option<u32> = None;
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--help" | "-h" => options.help = true,
+                "--corpus" => options.corpus = value()?,
+                "--alphabet" => options.alphabet = Alphabet::from_spec(&value()?)?,
+                "--order" => options.order = number(&arg, value()?)?,
+                "--held-out" => options.held_out = number(&arg, value()?)?,
+                "--cipher" => options.family = value()?.parse()?,
+                "--key-length" => options.key_length = number(&arg, value()?)?,
+                "--columns" => options.columns = number(&arg, value()?)?,
+                "--lengths" => options.config.lengths = list(&arg, value()?)?,
+                "--passages" => options.config.passages = number(&arg, value()?)?,
+                "--seed" => options.config.seed = number(&arg, value()?)?,
+                "--strategies" => {
+                    let kinds = value()?.split(',').map(|name| name.trim().parse()).collect::<Result<Vec<StrategyKind>, String>>()?;
+                    let template = options.config.strategies[0];
+                    options.config.strategies = kinds.into_iter().map(|kind| { let mut strategy = template; strategy.kind = kind; strategy }).collect();
+                },
+                "--samples" => samples = Some(number(&arg, value()?)?),
+                "--budget" => options.config.budget = number(&arg, value()?)?,
+                "--seeds" => options.config.seeds = list(&arg, value()?)?,
+                "--output" => options.output = Some(value()?),
+                "--runs" => options.runs = Some(value()?),
+                _ => return Err(format!("unknown argument {}", arg))
+            }
+        }
+
+        if let Some(samples) = samples {
+            for strategy in options.config.strategies.iter_mut() {
+                strategy.samples = samples;
+            }
+        }
+        if options.family == CipherFamily::Playfair {
+            return Err("playfair pads and folds its plaintext, so it can't be scored against the passage".to_string());
+        }
+        if !(0.0..1.0).contains(&options.held_out) || options.held_out == 0.0 {
+            return Err("--held-out must be between 0 and 1".to_string());
+        }
+        if options.config.lengths.is_empty() || options.config.seeds.is_empty() || options.config.strategies.is_empty() {
+            return Err("need at least one length, seed and strategy".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn list<T : FromStr>(arg : &str, value : String) -> Result<Vec<T>, String> {
+    value.split(',').map(|item| number(arg, item.trim().to_string())).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args : &[&str]) -> Result<BenchmarkOptions, String> {
+        BenchmarkOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_lists_and_applies_samples_to_every_strategy() {
+        let options = parse(&["--strategies", "hill-climbing,steepest-ascent", "--samples", "4", "--lengths", "50, 75", "--seeds", "9"]).unwrap();
+
+        assert_eq!(options.config.strategies.len(), 2);
+        assert_eq!(options.config.strategies[1].kind, StrategyKind::SteepestAscent);
+        assert!(options.config.strategies.iter().all(|strategy| strategy.samples == 4 && strategy.iterations == u32::MAX));
+        assert_eq!(options.config.lengths, vec![50, 75]);
+        assert_eq!(options.config.seeds, vec![9]);
+    }
+
+    #[test]
+    fn rejects_unusable_settings() {
+        assert!(parse(&["--cipher", "playfair"]).is_err());
+        assert!(parse(&["--held-out", "1.5"]).is_err());
+        assert!(parse(&["--lengths", "10,x"]).is_err());
+        assert!(parse(&["--strategies", "annealing"]).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+pub mod benchmark;
+
+use ::std::str::FromStr;
+use ::std::sync::Arc;
+use ::std::time::Duration;
+use alphabet::{
+    Alphabet,
+    Crib
+};
+use ciphers::CipherFamily;
+use cracker::{
+    StrategyConfig,
+    StrategyKind
+};
+
+pub const USAGE : &str = "usage: genetic_algorithm_cipher [options]
+
+input/output:
+  --input PATH            ciphertext to attack (default encrypted.txt)
+  --output PATH           write the best decryption here
+  --top K                 print the K best distinct keys (default 1)
+  --checkpoint PATH       resume from and save the search state here
+
+search:
+  --cipher FAMILY         substitution, vigenere, transposition, playfair or affine
+  --strategy NAME         hill-climbing, steepest-ascent or steepest-ascent-replacement
+  --iterations N          iterations per restart (default 20)
+  --samples N             neighbours sampled per iteration (default 10)
+  --restarts N            independent runs from fresh keys (default 1)
+  --seed N                seed for reproducible runs
+  --key-length N          vigenere key length (estimated if omitted)
+  --columns N             transposition column count (default 5)
+  --alphabet SPEC         english, extended-latin or symbols:<symbols in order>
+                          (default: the language's alphabet, usually english)
+
+substitution cribs:
+  --crib [OFFSET:]TEXT    known plaintext starting at ciphertext letter OFFSET,
+                          or wherever its letter pattern first fits
+  --fix C=P[,C=P...]      ciphertext letter C always decrypts to P
+
+language model:
+  --scorer NAME           ngram, interpolated, chi-squared or ioc (default ngram)
+  --order N               n-gram order
+  --language NAME         language section of the languages file
+  --languages PATH        languages file (default languages.conf)
+  --corpus PATH           corpus file or directory
+  --model PATH            precomputed n-gram table
+
+termination:
+  --target F              stop once fitness reaches F
+  --stagnation N          stop after N generations without improvement
+  --time SECONDS          stop after this much wall-clock time per restart
+  --verbose               print per-generation fitness";
+
+/// Known plaintext supplied with `--crib`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownPlaintext {
+    At(usize, String),
+    Anywhere(String)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub help : bool,
+    pub input : String,
+    pub output : Option<String>,
+    pub top : usize,
+    pub checkpoint : Option<String>,
+
+    pub family : CipherFamily,
+    pub strategy : StrategyConfig,
+    pub restarts : u32,
+    pub seed : Option<usize>,
+    pub key_length : Option<usize>,
+    pub columns : usize,
+
+    pub alphabet : Option<Alphabet>,
+
+    pub known_plaintext : Vec<KnownPlaintext>,
+    /// `--fix` mappings, parsed once the alphabet is known.
+    pub fixed : Vec<String>,
+
+    pub scorer : String,
+    pub order : Option<usize>,
+    pub language : Option<String>,
+    pub languages : Option<String>,
+    pub corpus : Option<String>,
+    pub model : Option<String>,
+
+    pub target : Option<f64>,
+    pub stagnation : Option<u32>,
+    pub time : Option<Duration>,
+    pub verbose : bool
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            help : false,
+            input : "encrypted.txt".to_string(),
+            output : None,
+            top : 1,
+            checkpoint : None,
+            family : CipherFamily::Substitution,
+            strategy : StrategyConfig {
+                kind : StrategyKind::SteepestAscentReplacement,
+                iterations : 20,
+                samples : 10
+            },
+            restarts : 1,
+            seed : None,
+            key_length : None,
+            columns : 5,
+            alphabet : None,
+            known_plaintext : vec!(),
+            fixed : vec!(),
+            scorer : "ngram".to_string(),
+            order : None,
+            language : None,
+            languages : None,
+            corpus : None,
+            model : None,
+            target : None,
+            stagnation : None,
+            time : None,
+            verbose : false
+        }
+    }
+}
+
+impl Options {
+    pub fn parse<I : Iterator<Item=String>>(args : I) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--help" | "-h" => options.help = true,
+                "--input" => options.input = value()?,
+                "--output" => options.output = Some(value()?),
+                "--top" => options.top = number(&arg, value()?)?,
+                "--checkpoint" => options.checkpoint = Some(value()?),
+                "--cipher" => options.family = value()?.parse()?,
+                "--strategy" => options.strategy.kind = value()?.parse()?,
+                "--iterations" => options.strategy.iterations = number(&arg, value()?)?,
+                "--samples" => options.strategy.samples = number(&arg, value()?)?,
+                "--restarts" => options.restarts = number(&arg, value()?)?,
+                "--seed" => options.seed = Some(number(&arg, value()?)?),
+                "--key-length" => options.key_length = Some(number(&arg, value()?)?),
+                "--columns" => options.columns = number(&arg, value()?)?,
+                "--crib" => options.known_plaintext.push(known_plaintext(&value()?)),
+                "--alphabet" => options.alphabet = Some(Alphabet::from_spec(&value()?)?),
+                "--fix" => options.fixed.push(value()?),
+                "--scorer" => options.scorer = value()?,
+                "--order" => options.order = Some(number(&arg, value()?)?),
+                "--language" => options.language = Some(value()?),
+                "--languages" => options.languages = Some(value()?),
+                "--corpus" => options.corpus = Some(value()?),
+                "--model" => options.model = Some(value()?),
+                "--target" => options.target = Some(number(&arg, value()?)?),
+                "--stagnation" => options.stagnation = Some(number(&arg, value()?)?),
+                "--time" => options.time = Some(Duration::from_secs(number(&arg, value()?)?)),
+                "--verbose" => options.verbose = true,
+                _ => return Err(format!("unknown argument {}", arg))
+            }
+        }
+
+        if options.top == 0 {
+            return Err("--top must be at least 1".to_string());
+        }
+        if options.order == Some(0) {
+            return Err("--order must be at least 1".to_string());
+        }
+        if options.family != CipherFamily::Substitution && (!options.known_plaintext.is_empty() || !options.fixed.is_empty()) {
+            return Err("cribs are only supported for substitution ciphers".to_string());
+        }
+        if options.family == CipherFamily::Playfair && options.alphabet.as_ref().is_some_and(|alphabet| *alphabet != Alphabet::english()) {
+            return Err("playfair only supports the english alphabet".to_string());
+        }
+
+        Ok(options)
+    }
+
+    /// Combines `--fix` mappings with `--crib` fragments lined up against
+    /// `ciphertext`, both in terms of the symbols of `alphabet`.
+    pub fn crib(&self, ciphertext : &str, alphabet : Arc<Alphabet>) -> Result<Crib, String> {
+        let mut crib = Crib::new(alphabet.clone());
+
+        for fixed in self.fixed.iter() {
+            let fixed = Crib::parse(fixed, alphabet.clone())?;
+            for cipher in 0..alphabet.len() {
+                if let Some(plain) = fixed.get(cipher) {
+                    crib.fix(alphabet.symbol(cipher), alphabet.symbol(plain))?;
+                }
+            }
+        }
+
+        for known in self.known_plaintext.iter() {
+            match *known {
+                KnownPlaintext::At(offset, ref text) => crib.add_known_plaintext(ciphertext, offset, text)?,
+                KnownPlaintext::Anywhere(ref text) => {
+                    let offset = crib.locate(ciphertext, text)
+                        .ok_or_else(|| format!("crib {} fits nowhere in the ciphertext", text))?;
+                    crib.add_known_plaintext(ciphertext, offset, text)?;
+                }
+            }
+        }
+
+        Ok(crib)
+    }
+}
+
+fn number<T : FromStr>(arg : &str, value : String) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("{} expects a number, got {}", arg, value))
+}
+
+fn known_plaintext(value : &str) -> KnownPlaintext {
+    let mut parts = value.splitn(2, ':');
+    match (parts.next().map(|offset| offset.parse::<usize>()), parts.next()) {
+        (Some(Ok(offset)), Some(text)) => KnownPlaintext::At(offset, text.to_string()),
+        _ => KnownPlaintext::Anywhere(value.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args : &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults_match_previous_behaviour() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.alphabet, None);
+        assert_eq!(options.input, "encrypted.txt");
+        assert_eq!(options.strategy, StrategyConfig { kind : StrategyKind::SteepestAscentReplacement, iterations : 20, samples : 10 });
+    }
+
+    #[test]
+    fn parses_search_options() {
+        let options = parse(&["--strategy", "hill-climbing", "--iterations", "500", "--restarts", "4", "--top", "3", "--order", "4"]).unwrap();
+        assert_eq!(options.strategy.kind, StrategyKind::HillClimbing);
+        assert_eq!(options.strategy.iterations, 500);
+        assert_eq!(options.restarts, 4);
+        assert_eq!(options.top, 3);
+        assert_eq!(options.order, Some(4));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["--iterations", "many"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--order", "0"]).is_err());
+        assert!(parse(&["--cipher", "vigenere", "--fix", "a=b"]).is_err());
+        assert!(parse(&["--alphabet", "klingon"]).is_err());
+        assert!(parse(&["--cipher", "playfair", "--alphabet", "extended-latin"]).is_err());
+    }
+
+    #[test]
+    fn combines_cribs() {
+        let english = Arc::new(Alphabet::english());
+        let options = parse(&["--fix", "q=e", "--crib", "2:th", "--crib", "ll"]).unwrap();
+        let crib = options.crib("xyab qrstu vkcccq", english.clone()).unwrap();
+        assert_eq!(crib.to_string(), "a=t,b=h,c=l,q=e");
+
+        let options = parse(&["--fix", "q=e", "--crib", "see"]).unwrap();
+        assert_eq!(options.crib("xyab qrstu vkcccq", english.clone()).unwrap_err(), "crib see fits nowhere in the ciphertext");
+
+        let options = parse(&["--fix", "q=e", "--fix", "x=e"]).unwrap();
+        assert!(options.crib("xq", english).is_err());
+    }
+}
@@ -1,91 +1,50 @@
-extern crate futures;
-extern crate hyper;
-extern crate tokio_core;
-
-use ::std::io::{Write, Read, Error, ErrorKind};
-use ::std::fs::File;
-use self::futures::{Future, Stream};
-use self::hyper::Client;
-use self::tokio_core::reactor::Core;
+use ::std::io::{Read, Error, ErrorKind};
+use ::std::fs::{self, File};
+use ::std::path::Path;
+use alphabet::Alphabet;
 
+pub const DEFAULT_CORPUS : &str = "corpus.txt";
 
+/// Loads the default corpus from `./corpus.txt`.
 pub fn get_corpus() -> Result<String, Error> {
-    println!("Loading local copy of corpus");
-    match open_local_copy() {
-        Ok(mut file) => {
-            let mut result : String = String::new();
-
-            file.read_to_string(&mut result)?;
-
-            Ok(format_text(&result))
-        },
-        Err(_e) => {
-            let string = make_request()?;
-            save_local_copy(&string)?;
-            Ok(format_text(&string))
-        }
-    }
+    load_corpus(DEFAULT_CORPUS, &Alphabet::english())
 }
 
-
-fn format_text(text: &String) -> String {
-    text.chars().map(|mut character| {
-     let mut char_u8 : u8 = character as u8;   
-     if char_u8 >= b'A' && char_u8 - b'A' < 26 {
-        char_u8 = char_u8 - b'A' + b'a';
-        character = char_u8 as char;
-     } 
-
-     character
-    }).filter(|character| {
-        let char_u8 : u8 = *character as u8;
-        
-        char_u8 >= b'a' && char_u8 - b'a' < 26
-    }).collect::<String>()
-}
-
-fn open_local_copy() -> Result<File, Error> {
-    File::open("corpus.txt")
-}
-
-fn save_local_copy(text : &String) -> Result<(), Error> {
-    match open_local_copy() {
-        Ok(file) => {
-          write_local_copy(file, text)
-        },
-        Err(e) => {
-            match e.kind() {
-                ErrorKind::NotFound => {
-                  let file = File::create("corpus.txt")?;
-                  write_local_copy(file, text)
-                },
-                _ => Err(e.into())
-            }
+/// Loads a corpus from a local file, or from every file in a directory
+/// (in name order), and reduces it to the symbols of `alphabet` for n-gram
+/// analysis.
+pub fn load_corpus<P : AsRef<Path>>(path : P, alphabet : &Alphabet) -> Result<String, Error> {
+    let path = path.as_ref();
+    eprintln!("Loading local copy of corpus from {}", path.display());
+
+    let raw = if path.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, Error>>()?;
+        entries.sort();
+
+        let mut result : String = String::new();
+        for entry in entries.iter().filter(|entry| entry.is_file()) {
+            result.push_str(&read_file(entry)?);
+            result.push('\n');
         }
+        result
+    } else {
+        read_file(path)?
+    };
+
+    let formatted = alphabet.normalise(&raw);
+    if formatted.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, format!("corpus at {} contains no usable text", path.display())));
     }
-}
 
-
-fn write_local_copy(mut local_file : File, text : &String) -> Result<(), Error> {
-    local_file.write_all(text.as_bytes())
+    Ok(formatted)
 }
 
-fn make_request() -> Result<String,Error> {
-    println!("Downloading Alice in Wonderland From Internet");
-    let mut core = Core::new()?;
-    let client = Client::new(&core.handle());
-
-    let uri = "http://www.gutenberg.org/files/11/11.txt".parse().expect("Could not parse the url");
-    let work = client.get(uri).and_then(|res| {
-        println!("Response: {}", res.status());
-
-        res.body().concat2().map(|chunk| {
-            let v = chunk.to_vec();
-            String::from_utf8_lossy(&v).to_string()
-        })
-    });
 
-    let result = core.run(work).expect("Error while running the task");
+fn read_file(path : &Path) -> Result<String, Error> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
 
-    Ok(result)
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
 }
@@ -0,0 +1,197 @@
+extern crate rand;
+
+use ::std::fmt::{
+    Display,
+    Formatter,
+};
+use ::std::fmt;
+use ::std::str::FromStr;
+use self::rand::Rng;
+use ciphers::{
+    Cipher,
+    CipherEvaluator
+};
+use GA::{
+    Generator,
+    Evaluator,
+    EvolutionaryAlgorithmStrategy,
+    GeneticAlgorithm,
+    SerialisableGenome,
+    Checkpoint,
+    Monitor,
+    seeded_rng
+};
+use GA::strategy::HillClimbing::HillClimbingStrategy;
+use GA::strategy::SteepestAscentHillClimbing::SteepestAscentHillClimbingStrategy;
+use GA::strategy::SteepestAscentReplacementHillClimbing::SteepestAscentReplacementHillClimbingStrategy;
+
+/// The search strategies the cracker can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyKind {
+    HillClimbing,
+    SteepestAscent,
+    SteepestAscentReplacement
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(name : &str) -> Result<StrategyKind, String> {
+        match name {
+            "hill-climbing" => Ok(StrategyKind::HillClimbing),
+            "steepest-ascent" => Ok(StrategyKind::SteepestAscent),
+            "steepest-ascent-replacement" => Ok(StrategyKind::SteepestAscentReplacement),
+            _ => Err(format!("unknown strategy {}", name))
+        }
+    }
+}
+
+impl Display for StrategyKind {
+    fn fmt(&self, f : &mut Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            StrategyKind::HillClimbing => "hill-climbing",
+            StrategyKind::SteepestAscent => "steepest-ascent",
+            StrategyKind::SteepestAscentReplacement => "steepest-ascent-replacement"
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrategyConfig {
+    pub kind : StrategyKind,
+    pub iterations : u32,
+    /// Neighbours sampled per iteration; unused by plain hill climbing.
+    pub samples : u32
+}
+
+impl StrategyConfig {
+    /// Builds the configured strategy, reporting to `monitor`.
+    pub fn build<T, G, E, R>(&self, rng : R, monitor : Monitor) -> Box<dyn EvolutionaryAlgorithmStrategy<Genome=T, Generator=G, Evaluator=E>>
+        where T : 'static + ::GA::Genome,
+              G : 'static + Generator<Genome=T>,
+              E : 'static + Evaluator<Genome=T>,
+              R : 'static + Rng
+    {
+        match self.kind {
+            StrategyKind::HillClimbing =>
+                Box::new(HillClimbingStrategy::new(self.iterations, rng).with_monitor(monitor)),
+            StrategyKind::SteepestAscent =>
+                Box::new(SteepestAscentHillClimbingStrategy::new(self.iterations, self.samples, rng).with_monitor(monitor)),
+            StrategyKind::SteepestAscentReplacement =>
+                Box::new(SteepestAscentReplacementHillClimbingStrategy::new(self.iterations, self.samples, rng).with_monitor(monitor))
+        }
+    }
+}
+
+/// A key found by one restart, with its fitness and decryption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub key : String,
+    pub fitness : f64,
+    pub plaintext : String
+}
+
+/// Outcome of `crack`: distinct keys found, best first, and the state of the
+/// restart that found the best one.
+pub struct CrackResult<T : ::GA::Genome> {
+    pub candidates : Vec<Candidate>,
+    pub checkpoint : Option<Checkpoint<T>>
+}
+
+/// Runs the configured strategy `restarts` times from independent seeds
+/// derived from `seed`, the first restart continuing from `resume` if given.
+/// `monitor` is called once per restart to build that run's monitor.
+pub fn crack<G, F>(generator : G,
+                   evaluator : CipherEvaluator<G::Genome>,
+                   config : &StrategyConfig,
+                   restarts : u32,
+                   seed : usize,
+                   mut monitor : F,
+                   mut resume : Option<Checkpoint<G::Genome>>) -> CrackResult<G::Genome>
+    where G : 'static + Generator,
+          G::Genome : 'static + Cipher + SerialisableGenome,
+          F : FnMut() -> Monitor
+{
+    let encrypted_text = evaluator.encrypted_text().clone();
+    let mut genetic_algorithm = GeneticAlgorithm::new(generator, evaluator);
+    let mut seeds = seeded_rng(seed);
+
+    let mut candidates : Vec<Candidate> = vec!();
+    let mut checkpoint : Option<Checkpoint<G::Genome>> = None;
+
+    for _ in 0..restarts.max(1) {
+        let mut strategy = config.build(seeded_rng(seeds.gen::<usize>()), monitor());
+        if let Some(state) = resume.take() {
+            strategy.resume_from(state);
+        }
+
+        let best = genetic_algorithm.execute_strategy(&mut *strategy);
+        let fitness = genetic_algorithm.evaluate(&best);
+
+        if candidates.iter().all(|candidate| fitness > candidate.fitness) {
+            checkpoint = strategy.checkpoint().cloned();
+        }
+
+        let key = best.serialise();
+        if !candidates.iter().any(|candidate| candidate.key == key) {
+            candidates.push(Candidate {
+                key,
+                fitness,
+                plaintext : best.decipher(&encrypted_text)
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).expect("Couldn't compare fitnesses"));
+
+    CrackResult {
+        candidates,
+        checkpoint
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::sync::Arc;
+    use alphabet::{Alphabet, Crib, SubstitutionGenerator};
+    use ciphers::{AffineCipher, AffineGenerator};
+    use ngram::NgramFrequency;
+
+    const TEXT : &str = "the quick brown fox jumps over the lazy dog while the cat sleeps in the warm sun";
+
+    #[test]
+    fn restarts_return_distinct_candidates_best_first() {
+        let alphabet = Arc::new(Alphabet::english());
+        let encrypted = AffineCipher::new(alphabet.clone(), 5, 8).unwrap().encipher(TEXT);
+        let table = NgramFrequency::generate_from(&alphabet.normalise(TEXT), 3);
+        let evaluator = CipherEvaluator::new(Box::new(table), alphabet.clone(), encrypted);
+        let config = StrategyConfig { kind : StrategyKind::HillClimbing, iterations : 5, samples : 0 };
+
+        let result = crack(AffineGenerator { alphabet }, evaluator, &config, 6, 1, Monitor::new, None);
+
+        assert!(!result.candidates.is_empty());
+        assert!(result.checkpoint.is_some());
+        for pair in result.candidates.windows(2) {
+            assert!(pair[0].fitness >= pair[1].fitness);
+            assert!(pair[0].key != pair[1].key);
+        }
+    }
+
+    #[test]
+    fn crib_constrains_every_candidate() {
+        let alphabet = Arc::new(Alphabet::english());
+        let crib = Crib::parse("t=q,h=r", alphabet.clone()).unwrap();
+        let generator = SubstitutionGenerator { alphabet : alphabet.clone(), crib : Some(Arc::new(crib)) };
+        let table = NgramFrequency::generate_from(&alphabet.normalise(TEXT), 3);
+        let evaluator = CipherEvaluator::new(Box::new(table), alphabet, "Th, th!".to_string());
+        let config = StrategyConfig { kind : StrategyKind::SteepestAscentReplacement, iterations : 20, samples : 5 };
+
+        let result = crack(generator, evaluator, &config, 3, 2, Monitor::new, None);
+
+        for candidate in result.candidates.iter() {
+            assert_eq!(candidate.plaintext, "Qr, qr!");
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use ::std::fs::File;
+use ::std::io::{
+    Read,
+    Error,
+    ErrorKind
+};
+use ::std::path::{
+    Path,
+    PathBuf
+};
+use alphabet::Alphabet;
+use corpus::{
+    load_corpus,
+    DEFAULT_CORPUS
+};
+use ngram::{
+    NgramFrequency,
+    TableFormat
+};
+
+/// Where to find the language model for one language.
+///
+/// A model is read from the precomputed table at `model` when it exists,
+/// otherwise it is generated from `corpus` (a file or directory) and, if
+/// `model` is set, written there for next time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageConfig {
+    pub name : String,
+    pub corpus : PathBuf,
+    pub model : Option<PathBuf>,
+    pub order : usize,
+    pub alphabet : Alphabet
+}
+
+impl LanguageConfig {
+    pub fn english() -> LanguageConfig {
+        LanguageConfig {
+            name : "english".to_string(),
+            corpus : PathBuf::from(DEFAULT_CORPUS),
+            model : None,
+            order : 3,
+            alphabet : Alphabet::english()
+        }
+    }
+
+    /// Reads every language from a configuration file of the form
+    ///
+    /// ```text
+    /// [french]
+    /// corpus = corpora/french
+    /// model = models/french.ngram
+    /// order = 4
+    /// alphabet = extended-latin
+    /// ```
+    ///
+    /// Relative paths are resolved against the directory of the file. The
+    /// alphabet is `english`, `extended-latin` or `symbols:<symbols>`, and
+    /// defaults to English.
+    pub fn read_all<P : AsRef<Path>>(path : P) -> Result<Vec<LanguageConfig>, Error> {
+        let path = path.as_ref();
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+
+        let mut languages : Vec<LanguageConfig> = vec!();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                languages.push(LanguageConfig {
+                    name : line[1..line.len() - 1].trim().to_string(),
+                    corpus : PathBuf::new(),
+                    model : None,
+                    order : 3,
+                    alphabet : Alphabet::english()
+                });
+                continue;
+            }
+
+            let error = |message : &str| Error::new(ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), index + 1, message));
+            let language = languages.last_mut().ok_or_else(|| error("setting outside of a [language] section"))?;
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(|| error("expected key = value"))?.trim();
+
+            match key {
+                "corpus" => language.corpus = base.join(value),
+                "model" => language.model = Some(base.join(value)),
+                "order" => language.order = value.parse().ok()
+                    .filter(|&order| order > 0)
+                    .ok_or_else(|| error("order must be a positive integer"))?,
+                "alphabet" => language.alphabet = Alphabet::from_spec(value).map_err(|e| error(&e))?,
+                _ => return Err(error("unknown setting"))
+            }
+        }
+
+        for language in languages.iter() {
+            if language.corpus.as_os_str().is_empty() && language.model.is_none() {
+                return Err(Error::new(ErrorKind::InvalidData, format!("language {} has neither a corpus nor a model", language.name)));
+            }
+        }
+
+        Ok(languages)
+    }
+
+    /// Picks the language called `name` out of a configuration file.
+    pub fn select<P : AsRef<Path>>(path : P, name : &str) -> Result<LanguageConfig, Error> {
+        LanguageConfig::read_all(path)?
+            .into_iter()
+            .find(|language| language.name == name)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("no language named {}", name)))
+    }
+
+    pub fn load_model(&self) -> Result<NgramFrequency, Error> {
+        if let Some(ref model) = self.model {
+            if model.is_file() {
+                let table = NgramFrequency::load(model)?;
+                if table.slice_size() == self.order {
+                    return Ok(table);
+                }
+                eprintln!("Ignoring {}: built for order {} not {}", model.display(), table.slice_size(), self.order);
+            }
+        }
+
+        let corpus = load_corpus(&self.corpus, &self.alphabet)?;
+        let table = NgramFrequency::generate_from(&corpus, self.order);
+
+        if let Some(ref model) = self.model {
+            table.save(model, TableFormat::Binary)?;
+        }
+
+        Ok(table)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::fs;
+    use ::std::io::Write;
+
+    #[test]
+    fn reads_language_sections() {
+        let dir = ::std::env::temp_dir().join("genetic_algorithm_cipher_language_test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("languages.conf");
+        File::create(&path).unwrap().write_all(b"# comment\n[english]\ncorpus = corpus.txt\n\n[french]\ncorpus = fr\nmodel = fr.ngram\norder = 4\nalphabet = extended-latin\n").unwrap();
+
+        let languages = LanguageConfig::read_all(&path).unwrap();
+        let french = LanguageConfig::select(&path, "french").unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(languages.len(), 2);
+        assert_eq!(languages[0].corpus, dir.join("corpus.txt"));
+        assert_eq!(french.model, Some(dir.join("fr.ngram")));
+        assert_eq!(french.order, 4);
+        assert_eq!(french.alphabet, Alphabet::extended_latin());
+        assert_eq!(languages[0].alphabet, Alphabet::english());
+    }
+
+    #[test]
+    fn rejects_order_zero() {
+        let dir = ::std::env::temp_dir().join("genetic_algorithm_cipher_order_test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("languages.conf");
+        File::create(&path).unwrap().write_all(b"[english]\ncorpus = corpus.txt\norder = 0\n").unwrap();
+
+        let result = LanguageConfig::read_all(&path);
+        let _ = fs::remove_dir_all(&dir);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builds_and_caches_model_from_corpus_directory() {
+        let dir = ::std::env::temp_dir().join("genetic_algorithm_cipher_model_test");
+        let corpus = dir.join("corpus");
+        fs::create_dir_all(&corpus).unwrap();
+        File::create(corpus.join("a.txt")).unwrap().write_all("Le chat est sur la table.".as_bytes()).unwrap();
+        File::create(corpus.join("b.txt")).unwrap().write_all("La table est dans la cuisine, près du café.".as_bytes()).unwrap();
+
+        let language = LanguageConfig {
+            name : "french".to_string(),
+            corpus : corpus.clone(),
+            model : Some(dir.join("french.ngram")),
+            order : 2,
+            alphabet : Alphabet::extended_latin()
+        };
+        let generated = language.load_model().unwrap();
+        let cached_exists = dir.join("french.ngram").is_file();
+        let cached = language.load_model().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(cached_exists);
+        let text = "latableestprèsducafé".to_string();
+        assert_eq!(generated.score_text(&text), cached.score_text(&text));
+        assert!(generated.score_text("café") > generated.score_text("cafe"));
+    }
+}
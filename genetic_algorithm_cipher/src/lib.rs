@@ -1,4 +1,10 @@
 pub mod alphabet;
+pub mod ciphers;
 pub mod corpus;
 pub mod ngram;
+pub mod language;
+pub mod scoring;
+pub mod cracker;
+pub mod benchmark;
+pub mod cli;
 pub mod GA;
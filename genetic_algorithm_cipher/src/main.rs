@@ -1,131 +1,186 @@
 extern crate genetic_algorithm_cipher;
-use genetic_algorithm_cipher::corpus::{
-   get_corpus
+extern crate rand;
+extern crate ctrlc;
+use genetic_algorithm_cipher::cli::{
+    Options,
+    USAGE
+};
+use genetic_algorithm_cipher::alphabet::Alphabet;
+use genetic_algorithm_cipher::language::{
+    LanguageConfig
 };
-use genetic_algorithm_cipher::alphabet::{
-    SubstitutionCipher
+use genetic_algorithm_cipher::ciphers::{
+    Cipher,
+    CipherEvaluator,
+    CipherFamily,
+    SubstitutionGenerator,
+    VigenereGenerator,
+    TranspositionGenerator,
+    PlayfairGenerator,
+    AffineGenerator
 };
+use genetic_algorithm_cipher::ciphers::vigenere::{
+    estimate_key_length,
+    kasiski_key_lengths
+};
+use genetic_algorithm_cipher::cracker::crack;
 use genetic_algorithm_cipher::ngram::{
-    NgramFrequency
+    InterpolatedModel
+};
+use genetic_algorithm_cipher::corpus::{
+    load_corpus
+};
+use genetic_algorithm_cipher::scoring::{
+    Scorer,
+    ChiSquaredScorer,
+    IndexOfCoincidenceScorer
 };
 
 use genetic_algorithm_cipher::GA::{
-    Genome,
     Generator,
-    Evaluator,
-    EvolutionaryAlgorithmStrategy,
-    GeneticAlgorithm
+    SerialisableGenome,
+    Checkpoint,
+    Monitor,
+    Termination
 };
+use genetic_algorithm_cipher::GA::progress::PrintObserver;
+use rand::Rng;
 
 use std::fs::File;
-use std::io::Read;
-use genetic_algorithm_cipher::GA::strategy::HillClimbing::{ HillClimbingStrategy};
-use genetic_algorithm_cipher::GA::strategy::SteepestAscentHillClimbing::{ SteepestAscentHillClimbingStrategy };
-use genetic_algorithm_cipher::GA::strategy::SteepestAscentReplacementHillClimbing::{ SteepestAscentReplacementHillClimbingStrategy};
-
-pub struct CipherGenome {
-    cipher : SubstitutionCipher
-}
-
-impl Genome for CipherGenome {
-    fn mutate(&mut self) {
-        self.cipher.mutate();
-    }
-    fn crossover(parent_a : &Self, parent_b : &Self) -> Self{
-        let cipher = SubstitutionCipher::crossover(&parent_a.cipher, &parent_b.cipher);
-        CipherGenome {
-            cipher
+use std::io::{Read, Write};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Searches for the key of `encrypted_text` among the keys `generator`
+/// produces, prints the best candidates and writes out the best decryption.
+fn run<G>(generator : G, scorer : Box<dyn Scorer>, alphabet : Arc<Alphabet>, encrypted_text : String, seed : usize, options : &Options, interrupted : &Arc<AtomicBool>)
+    where G : 'static + Generator,
+          G::Genome : 'static + Cipher + SerialisableGenome
+{
+    let evaluator : CipherEvaluator<G::Genome> = CipherEvaluator::new(scorer, alphabet, encrypted_text);
+
+    let resume = options.checkpoint.as_ref().and_then(|path| {
+        match Checkpoint::load(path) {
+            Ok(checkpoint) => {
+                println!("Resuming from generation {} of {}", checkpoint.generation, path);
+                Some(checkpoint)
+            },
+            Err(e) => {
+                eprintln!("Not resuming from {}: {}", path, e);
+                None
+            }
         }
-    }
-    fn clone(&self) -> Self {
-       let cipher = self.cipher.clone();
-       CipherGenome {
-            cipher
-       }
-    }
-}
-
-pub struct CipherEvaluator {
-    frequency_table : NgramFrequency,
-    encrypted_text : String
-}
+    });
 
-impl Evaluator for CipherEvaluator {
-    type Genome = CipherGenome;
-    fn fitness(&self, genome : &Self::Genome) -> f64 {
-       let converted_text = genome.cipher.apply(&self.encrypted_text);
+    let monitor = || {
+        let mut termination = Termination::new().interrupt(interrupted.clone());
+        if let Some(target) = options.target { termination = termination.target_fitness(target); }
+        if let Some(generations) = options.stagnation { termination = termination.stagnation(generations); }
+        if let Some(budget) = options.time { termination = termination.time_budget(budget); }
 
-       self.frequency_table.score_text(&converted_text)
-    }
-}
+        let monitor = Monitor::new().termination(termination);
+        if options.verbose { monitor.observer(Box::new(PrintObserver)) } else { monitor }
+    };
 
-pub struct CipherGenerator {}
+    let result = crack(generator, evaluator, &options.strategy, options.restarts, seed, monitor, resume);
 
-impl Generator for CipherGenerator {
-    type Genome = CipherGenome;
+    if let (Some(path), Some(checkpoint)) = (options.checkpoint.as_ref(), result.checkpoint.as_ref()) {
+        if let Err(e) = checkpoint.save(path) {
+            eprintln!("Couldn't save checkpoint to {}: {}", path, e);
+        }
+    }
 
-    fn create_new(&self) -> Self::Genome {
-        let cipher = SubstitutionCipher::new();
+    for (rank, candidate) in result.candidates.iter().take(options.top).enumerate() {
+        println!("#{} score {:.4} key {}", rank + 1, candidate.fitness, candidate.key);
+        println!("    {}", candidate.plaintext);
+    }
 
-        CipherGenome {
-            cipher 
+    if let (Some(path), Some(best)) = (options.output.as_ref(), result.candidates.first()) {
+        if let Err(e) = File::create(path).and_then(|mut file| file.write_all(best.plaintext.as_bytes())) {
+            eprintln!("Couldn't write {}: {}", path, e);
         }
     }
 }
 
+fn fail(message : &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2)
+}
 
 fn main() {
-//    println!("{}", get_corpus().unwrap());
-//    let cipherA: SubstitutionCipher = SubstitutionCipher::new();    
-//    let cipherB: SubstitutionCipher = SubstitutionCipher::new();    
-//    let cipherC: SubstitutionCipher = SubstitutionCipher::crossover(&cipherA, &cipherB);
-//    println!("{}", cipherA);
-//    println!("{}", cipherB);
-//    println!("{}", cipherC);
-//    let mut text = "kiran has written a simple substitution cipher!".to_string();
-//
-//    println!("stringI: {}", text);
-//
-//    let mut text_A = cipherA.apply(&text);
-//    println!("cipherA: {}", cipherA.apply(&text));
-//    println!("cipherB: {}", cipherB.apply(&text));
-//    println!("cipherC: {}", cipherC.apply(&text));
-//    
-//    let corpus = get_corpus().unwrap();
-//    println!("formatted corpus: {}", corpus);
-//
-//    let frequency = NgramFrequency::generate_from(&corpus, 4);
-//    println!("frequency analysis: {}", frequency);
-//    println!("corpus score: {}", frequency.score_text(&corpus));
-//    println!("text score: {}", frequency.score_text(&text));
-//    println!("cipherA score: {}", frequency.score_text(&text_A));
-//    let mut text = "ljsboibtxsjuufobtjnqmftvctujuvujpodjqifs".to_string();
-//    let mut copy = text.clone();
-    let corpus = get_corpus().unwrap();
-    let frequency = NgramFrequency::generate_from(&corpus, 3);
-
-    let mut encrypted_text = if let Ok(mut file) = File::open("encrypted.txt") {
-        let mut result: String = String::new();
-
-        file.read_to_string(&mut result);
-
-        result
-    } else {
-        println!("Couldn't find ./encrypted.txt");
-
-        "".to_string()
-    };
+    let options = Options::parse(::std::env::args().skip(1)).unwrap_or_else(|e| fail(&e));
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
 
-//    println!("Original: {}", alternate);
-    let generator = CipherGenerator {};
-    let evaluator = CipherEvaluator {
-        frequency_table: frequency,
-        encrypted_text: encrypted_text.clone()
+    let mut language = match (options.languages.as_ref(), options.language.as_ref()) {
+        (Some(path), Some(name)) => LanguageConfig::select(path, name),
+        (None, Some(name)) => LanguageConfig::select("languages.conf", name),
+        _ => Ok(LanguageConfig::english())
+    }.unwrap_or_else(|e| fail(&format!("Couldn't select language: {}", e)));
+    if let Some(ref path) = options.corpus { language.corpus = path.into(); }
+    if let Some(ref path) = options.model { language.model = Some(path.into()); }
+    if let Some(order) = options.order { language.order = order; }
+    if let Some(ref alphabet) = options.alphabet { language.alphabet = alphabet.clone(); }
+    let alphabet = Arc::new(language.alphabet.clone());
+
+    println!("Language: {} ({} alphabet)", language.name, alphabet.spec());
+    let scorer : Box<dyn Scorer> = match options.scorer.as_str() {
+        "ngram" => Box::new(language.load_model().unwrap_or_else(|e| fail(&format!("Couldn't load language model: {}", e)))),
+        name => {
+            let corpus = load_corpus(&language.corpus, &alphabet).unwrap_or_else(|e| fail(&format!("Couldn't load corpus: {}", e)));
+            match name {
+                "interpolated" => Box::new(InterpolatedModel::generate_from(&corpus, language.order)),
+                "chi-squared" => Box::new(ChiSquaredScorer::from_corpus(&corpus, &alphabet)),
+                "ioc" => Box::new(IndexOfCoincidenceScorer::from_corpus(&corpus)),
+                _ => fail(&format!("unknown scorer {}", name))
+            }
+        }
     };
 
-    let mut genetic_algorithm = GeneticAlgorithm::new(generator, evaluator);
-    let mut hill_climbing = SteepestAscentReplacementHillClimbingStrategy::new(20, 10);
-    let best = genetic_algorithm.execute_strategy(&mut hill_climbing);
-    println!("Decrypted: {}", best.cipher.apply(&encrypted_text));
+    let mut encrypted_text = String::new();
+    if let Err(e) = File::open(&options.input).and_then(|mut file| file.read_to_string(&mut encrypted_text)) {
+        fail(&format!("Couldn't read {}: {}", options.input, e));
+    }
 
+    let interrupted = Arc::new(AtomicBool::new(false));
+    {
+        let interrupted = interrupted.clone();
+        if let Err(e) = ctrlc::set_handler(move || interrupted.store(true, Ordering::SeqCst)) {
+            eprintln!("Couldn't install Ctrl-C handler: {}", e);
+        }
+    }
+
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen::<usize>());
+    println!("Seed: {}", seed);
+
+    match options.family {
+        CipherFamily::Substitution => {
+            let crib = options.crib(&encrypted_text, alphabet.clone()).unwrap_or_else(|e| fail(&e));
+            if !crib.is_empty() {
+                println!("Crib: {}", crib);
+            }
+            let generator = SubstitutionGenerator {
+                alphabet : alphabet.clone(),
+                crib : if crib.is_empty() { None } else { Some(Arc::new(crib)) }
+            };
+            run(generator, scorer, alphabet, encrypted_text, seed, &options, &interrupted)
+        },
+        CipherFamily::Vigenere => {
+            let key_length = options.key_length.unwrap_or_else(|| {
+                println!("Kasiski candidates: {:?}", kasiski_key_lengths(&encrypted_text, &alphabet, 20).iter().take(5).collect::<Vec<_>>());
+                estimate_key_length(&encrypted_text, &alphabet, 20)
+            });
+            println!("Key length: {}", key_length);
+            run(VigenereGenerator { alphabet : alphabet.clone(), key_length }, scorer, alphabet, encrypted_text, seed, &options, &interrupted)
+        },
+        CipherFamily::Transposition => {
+            let generator = TranspositionGenerator { alphabet : alphabet.clone(), columns : options.columns };
+            run(generator, scorer, alphabet, encrypted_text, seed, &options, &interrupted)
+        },
+        CipherFamily::Playfair => run(PlayfairGenerator, scorer, alphabet, encrypted_text, seed, &options, &interrupted),
+        CipherFamily::Affine => run(AffineGenerator { alphabet : alphabet.clone() }, scorer, alphabet, encrypted_text, seed, &options, &interrupted)
+    }
 }
@@ -0,0 +1,148 @@
+use ::std::collections::HashMap;
+use scoring::Scorer;
+use super::ngrams;
+
+/// Character language model interpolating every order from unigrams up to
+/// `order`-grams (Jelinek-Mercer smoothing).
+///
+/// The probability of a character given its history is
+/// `λn·Pml(c | h) + (1 - λn)·P(n-1)(c | h')`, where `Pml` is the relative
+/// frequency from the corpus, `h'` drops the oldest character of `h`, and the
+/// recursion bottoms out in a uniform distribution over the characters seen.
+/// Orders whose history was never observed defer entirely to the order below.
+pub struct InterpolatedModel {
+    counts : Vec<HashMap<String,u32>>,
+    contexts : Vec<HashMap<String,u32>>,
+    total : u32,
+    weights : Vec<f64>,
+    vocabulary : usize
+}
+
+impl InterpolatedModel {
+    pub fn generate_from(text : &str, order : usize) -> InterpolatedModel {
+        assert!(order > 0, "an interpolated model needs at least unigrams");
+
+        let counts = (1..order + 1).map(|size| {
+            let mut table : HashMap<String,u32> = HashMap::new();
+            for slice in ngrams(text, size) {
+                *table.entry(slice).or_insert(0) += 1;
+            }
+            table
+        }).collect::<Vec<_>>();
+
+        // how often each history is followed by some character, per order above unigrams
+        let contexts = counts.iter().skip(1).map(|table| {
+            let mut contexts : HashMap<String,u32> = HashMap::new();
+            for (gram, count) in table.iter() {
+                let mut history = gram.clone();
+                history.pop();
+                *contexts.entry(history).or_insert(0) += *count;
+            }
+            contexts
+        }).collect::<Vec<_>>();
+
+        let total = counts[0].values().sum();
+        let vocabulary = counts[0].len().max(1);
+
+        InterpolatedModel {
+            counts,
+            contexts,
+            total,
+            weights : vec![0.9; order],
+            vocabulary
+        }
+    }
+
+    /// Sets λ for each order, unigrams first. Each weight must lie in [0, 1].
+    pub fn with_weights(mut self, weights : Vec<f64>) -> InterpolatedModel {
+        assert_eq!(weights.len(), self.counts.len(), "need one weight per order");
+        assert!(weights.iter().all(|weight| *weight >= 0.0 && *weight <= 1.0), "weights must lie in [0, 1]");
+        self.weights = weights;
+        self
+    }
+
+    pub fn order(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Probability of `next` following `history` (only the last `order - 1`
+    /// characters of the history are used).
+    pub fn probability(&self, history : &[char], next : char) -> f64 {
+        let usable = history.len().min(self.order() - 1);
+        let history = &history[history.len() - usable..];
+
+        let mut probability = 1.0 / self.vocabulary as f64;
+        let mut gram : String = String::new();
+        gram.push(next);
+
+        for size in 1..usable + 2 {
+            if size > 1 {
+                gram.insert(0, history[history.len() - (size - 1)]);
+            }
+
+            let context = if size == 1 {
+                self.total
+            } else {
+                let context : String = gram.chars().take(size - 1).collect();
+                *self.contexts[size - 2].get(&context).unwrap_or(&0)
+            };
+            if context == 0 {
+                continue;
+            }
+
+            let count = *self.counts[size - 1].get(&gram).unwrap_or(&0);
+            let weight = self.weights[size - 1];
+            probability = weight * (count as f64 / context as f64) + (1.0 - weight) * probability;
+        }
+
+        probability
+    }
+
+    /// Log10 likelihood of `text`, predicting each character from the ones before it.
+    pub fn score_text(&self, text : &str) -> f64 {
+        let chars : Vec<char> = text.chars().collect();
+
+        (0..chars.len())
+            .map(|i| self.probability(&chars[..i], chars[i]).log10())
+            .sum()
+    }
+}
+
+impl Scorer for InterpolatedModel {
+    fn score(&self, text : &str) -> f64 {
+        self.score_text(text)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> InterpolatedModel {
+        InterpolatedModel::generate_from("thecatsatonthematthedogsatonthelog", 4)
+    }
+
+    #[test]
+    fn probabilities_sum_to_one() {
+        let model = model();
+        let alphabet : Vec<char> = "thecasondgml".chars().collect();
+
+        for history in ["", "t", "th", "the", "xqz"].iter() {
+            let history : Vec<char> = history.chars().collect();
+            let total : f64 = alphabet.iter().map(|c| model.probability(&history, *c)).sum();
+            assert!((total - 1.0).abs() < 1e-9, "history {:?} sums to {}", history, total);
+        }
+    }
+
+    #[test]
+    fn prefers_seen_text() {
+        let model = model();
+        assert!(model.score_text("thecat") > model.score_text("tacteh"));
+    }
+
+    #[test]
+    fn unseen_characters_have_nonzero_probability() {
+        assert!(model().score_text("thez").is_finite());
+    }
+}
@@ -1,11 +1,41 @@
 use ::std::collections::HashMap;
+use ::std::fs::File;
+use ::std::io::{
+    BufRead,
+    BufReader,
+    BufWriter,
+    Read,
+    Write,
+    Error,
+    ErrorKind
+};
+use ::std::path::Path;
 use ::std::fmt::{
     Display,
     Formatter,
 };
 use ::std::fmt;
+use scoring::Scorer;
+
+pub mod interpolated;
+
+pub use self::interpolated::InterpolatedModel;
 
 
+const BINARY_MAGIC : &[u8; 4] = b"NGRB";
+const TEXT_HEADER : &str = "# ngram table";
+
+/// On-disk encodings of an `NgramFrequency` table.
+///
+/// The text form is a header line followed by `slice_size total floor` and one
+/// `ngram score` pair per line. The binary form is the magic `NGRB`, then
+/// `slice_size` and `total` as little-endian u32s, `floor` as an f64, an entry
+/// count as a u32 and each entry as a length byte, the n-gram's utf-8 bytes and an f64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Text,
+    Binary
+}
 
 pub struct NgramFrequency {
     frequency: HashMap<String,f64>,
@@ -16,31 +46,22 @@ pub struct NgramFrequency {
 
 
 impl NgramFrequency {
-    pub fn generate_from(text : &String, slice_size: usize) -> NgramFrequency {
-        let max = text.len() - slice_size;
+    /// Builds a table of log10 probabilities of each `slice_size`-gram in `text`.
+    /// N-grams that never occur are scored with `floor`, a hundredth of the
+    /// probability of an n-gram seen once.
+    pub fn generate_from(text : &str, slice_size: usize) -> NgramFrequency {
         let mut freq : HashMap<String,f64> = HashMap::new();
 
-        for i in 0..max {
-            let slice = text.as_str()[i..i+slice_size].to_string();
+        for slice in ngrams(text, slice_size) {
             let fr = freq.entry(slice).or_insert(0.0);
             *fr += 1.0;
         }
-        let mut total : u32 = 0;
 
-        for (key,count) in freq.iter() {
-            total = total + *count as u32;
-        }
-
-        let mut floor = 0.01 / (total as f64);
-        floor.log(10.0);
+        let total : u32 = freq.values().map(|count| *count as u32).sum();
+        let floor = (0.01 / (total.max(1) as f64)).log10();
 
-        for (key, mut count) in (&mut freq).into_iter() {
-            if *count == 1.0 {
-                *count = floor;
-            } else {
-                *count = *count / (total as f64);
-                count.log(10.0);
-            }
+        for count in freq.values_mut() {
+            *count = (*count / (total as f64)).log10();
         }
 
         NgramFrequency {
@@ -52,18 +73,157 @@ impl NgramFrequency {
     }
 
 
-    pub fn score_text(&self, text : &String) -> f64 {
-        let max = text.len() - self.slice_size;
-        let mut score : f64 = 0.0;
-        for i in 0..max {
-            let slice = text.as_str()[i..i+self.slice_size].to_string();
-            match self.frequency.get(&slice) {
-                Some(count) => { score = score +  count;}
-                None => { score = score + self.floor; }
+    /// Log10 likelihood of `text` under the table; higher is more language-like.
+    pub fn score_text(&self, text : &str) -> f64 {
+        ngrams(text, self.slice_size)
+            .map(|slice| *self.frequency.get(&slice).unwrap_or(&self.floor))
+            .sum()
+    }
+
+    pub fn slice_size(&self) -> usize {
+        self.slice_size
+    }
+
+    pub fn save<P : AsRef<Path>>(&self, path : P, format : TableFormat) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut entries : Vec<(&String, &f64)> = self.frequency.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        match format {
+            TableFormat::Text => {
+                writeln!(writer, "{}", TEXT_HEADER)?;
+                writeln!(writer, "{} {} {}", self.slice_size, self.total, self.floor)?;
+                for (key, value) in entries {
+                    writeln!(writer, "{} {}", key, value)?;
+                }
+            },
+            TableFormat::Binary => {
+                writer.write_all(BINARY_MAGIC)?;
+                writer.write_all(&(self.slice_size as u32).to_le_bytes())?;
+                writer.write_all(&self.total.to_le_bytes())?;
+                writer.write_all(&self.floor.to_le_bytes())?;
+                writer.write_all(&(entries.len() as u32).to_le_bytes())?;
+                for (key, value) in entries {
+                    if key.len() > u8::MAX as usize {
+                        return Err(invalid("ngram too long for binary table"));
+                    }
+                    writer.write_all(&[key.len() as u8])?;
+                    writer.write_all(key.as_bytes())?;
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Loads a table written by `save`, detecting its format from the first bytes.
+    pub fn load<P : AsRef<Path>>(path : P) -> Result<NgramFrequency, Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let is_binary = reader.fill_buf()?.starts_with(BINARY_MAGIC);
+        if is_binary {
+            NgramFrequency::read_binary(reader)
+        } else {
+            NgramFrequency::read_text(reader)
+        }
+    }
+
+    fn read_text<R : BufRead>(reader : R) -> Result<NgramFrequency, Error> {
+        let mut lines = reader.lines();
+
+        if lines.next().transpose()?.as_deref() != Some(TEXT_HEADER) {
+            return Err(invalid("missing ngram table header"));
+        }
+
+        let header = lines.next().transpose()?.ok_or_else(|| invalid("missing table parameters"))?;
+        let mut parameters = header.split_whitespace();
+        let slice_size = parse_field::<usize>(parameters.next())?;
+        let total = parse_field::<u32>(parameters.next())?;
+        let floor = parse_field::<f64>(parameters.next())?;
+
+        let mut frequency : HashMap<String,f64> = HashMap::new();
+        for line in lines {
+            let line = line?;
+            // the value comes last, as user-defined alphabets may put spaces in the key
+            let mut fields = line.rsplitn(2, ' ');
+            let value = parse_field::<f64>(fields.next())?;
+            let key = fields.next().ok_or_else(|| invalid("empty ngram entry"))?.to_string();
+            if key.chars().count() != slice_size {
+                return Err(invalid("ngram length does not match table"));
             }
+            frequency.insert(key, value);
+        }
+
+        Ok(NgramFrequency {
+            frequency,
+            slice_size,
+            total,
+            floor
+        })
+    }
+
+    fn read_binary<R : Read>(mut reader : R) -> Result<NgramFrequency, Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        let slice_size = read_u32(&mut reader)? as usize;
+        let total = read_u32(&mut reader)?;
+        let floor = read_f64(&mut reader)?;
+        let count = read_u32(&mut reader)?;
+
+        let mut frequency : HashMap<String,f64> = HashMap::new();
+        for _ in 0..count {
+            let mut length = [0u8; 1];
+            reader.read_exact(&mut length)?;
+            let mut key = vec![0u8; length[0] as usize];
+            reader.read_exact(&mut key)?;
+            let value = read_f64(&mut reader)?;
+            let key = String::from_utf8(key).map_err(|_| invalid("ngram is not valid utf-8"))?;
+            frequency.insert(key, value);
         }
 
-        score
+        Ok(NgramFrequency {
+            frequency,
+            slice_size,
+            total,
+            floor
+        })
+    }
+}
+
+/// Every run of `size` consecutive characters in `text`, in order.
+pub fn ngrams<'a>(text : &'a str, size : usize) -> impl Iterator<Item=String> + 'a {
+    let chars : Vec<char> = text.chars().collect();
+    let count = if size == 0 || chars.len() < size { 0 } else { chars.len() - size + 1 };
+
+    (0..count).map(move |i| chars[i..i + size].iter().collect::<String>())
+}
+
+fn invalid(message : &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_field<T : ::std::str::FromStr>(field : Option<&str>) -> Result<T, Error> {
+    field.and_then(|field| field.parse::<T>().ok()).ok_or_else(|| invalid("malformed ngram table field"))
+}
+
+fn read_u32<R : Read>(reader : &mut R) -> Result<u32, Error> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64<R : Read>(reader : &mut R) -> Result<f64, Error> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+
+impl Scorer for NgramFrequency {
+    fn score(&self, text : &str) -> f64 {
+        self.score_text(text)
     }
 }
 
@@ -85,3 +245,50 @@ impl Display for NgramFrequency {
         write!(f, "\n")
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(format : TableFormat, name : &str) {
+        let table = NgramFrequency::generate_from("thequickbrownfoxjumpsoverthelazydogthethe", 3);
+        let path = ::std::env::temp_dir().join(name);
+        table.save(&path, format).unwrap();
+        let loaded = NgramFrequency::load(&path).unwrap();
+        let _ = ::std::fs::remove_file(&path);
+
+        assert_eq!(loaded.slice_size, table.slice_size);
+        assert_eq!(loaded.total, table.total);
+        assert_eq!(loaded.floor, table.floor);
+        assert_eq!(loaded.frequency, table.frequency);
+    }
+
+    #[test]
+    fn scores_are_log_probabilities() {
+        let table = NgramFrequency::generate_from("abababac", 2);
+
+        // 7 bigrams: ab x3, ba x3, ac x1
+        assert_eq!(table.total, 7);
+        assert!((table.frequency["ab"] - (3.0f64 / 7.0).log10()).abs() < 1e-12);
+        assert!((table.frequency["ac"] - (1.0f64 / 7.0).log10()).abs() < 1e-12);
+        assert!(table.frequency["ac"] > table.floor);
+        assert!((table.score_text("abc") - (table.frequency["ab"] + table.floor)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn short_text_scores_zero() {
+        let table = NgramFrequency::generate_from("abababac", 3);
+        assert_eq!(table.score_text("ab"), 0.0);
+    }
+
+    #[test]
+    fn text_table_round_trips() {
+        round_trip(TableFormat::Text, "genetic_algorithm_cipher_ngram_text_test");
+    }
+
+    #[test]
+    fn binary_table_round_trips() {
+        round_trip(TableFormat::Binary, "genetic_algorithm_cipher_ngram_binary_test");
+    }
+}
@@ -0,0 +1,143 @@
+use ::std::collections::HashMap;
+use ::std::sync::Arc;
+use alphabet::Alphabet;
+
+/// Rates how much a candidate decryption looks like natural language.
+/// Higher scores are better.
+pub trait Scorer {
+    fn score(&self, text : &str) -> f64;
+}
+
+/// Lets one model be shared by several evaluators, e.g. across benchmark runs.
+impl <S : Scorer + ?Sized> Scorer for Arc<S> {
+    fn score(&self, text : &str) -> f64 {
+        (**self).score(text)
+    }
+}
+
+/// Counts of each symbol of `alphabet` in `text`, ignoring everything else.
+pub fn letter_counts(text : &str, alphabet : &Alphabet) -> Vec<u32> {
+    let mut counts = vec!(0u32; alphabet.len());
+
+    for character in text.chars() {
+        if let Some(index) = alphabet.index_of(character) {
+            counts[index] += 1;
+        }
+    }
+
+    counts
+}
+
+/// Probability that two characters drawn from `text` without replacement are
+/// equal. Every character counts, so `text` should already be reduced to the
+/// symbols of its alphabet.
+pub fn index_of_coincidence(text : &str) -> f64 {
+    let mut counts : HashMap<char, u64> = HashMap::new();
+    for character in text.chars() {
+        *counts.entry(character).or_insert(0) += 1;
+    }
+    let total : u64 = counts.values().sum();
+
+    if total < 2 {
+        return 0.0;
+    }
+
+    let matching : u64 = counts.values().map(|&count| count * count.saturating_sub(1)).sum();
+    matching as f64 / (total * (total - 1)) as f64
+}
+
+/// Scores text by the negated chi-squared distance between its symbol
+/// frequencies and those of a reference corpus.
+pub struct ChiSquaredScorer {
+    alphabet : Alphabet,
+    expected : Vec<f64>
+}
+
+impl ChiSquaredScorer {
+    pub fn from_corpus(corpus : &str, alphabet : &Alphabet) -> ChiSquaredScorer {
+        let counts = letter_counts(corpus, alphabet);
+        let total : u32 = counts.iter().sum();
+        let smoothing = alphabet.len() as f64 * 0.5;
+
+        // keep symbols absent from the corpus from dividing by zero
+        let expected = counts.iter()
+            .map(|&count| (count as f64 + 0.5) / (total as f64 + smoothing))
+            .collect();
+
+        ChiSquaredScorer {
+            alphabet : alphabet.clone(),
+            expected
+        }
+    }
+
+    pub fn chi_squared(&self, text : &str) -> f64 {
+        let counts = letter_counts(text, &self.alphabet);
+        let total : u32 = counts.iter().sum();
+
+        counts.iter().zip(self.expected.iter())
+            .map(|(&observed, &frequency)| {
+                let expected = frequency * total as f64;
+                (observed as f64 - expected).powi(2) / expected
+            })
+            .sum()
+    }
+}
+
+impl Scorer for ChiSquaredScorer {
+    fn score(&self, text : &str) -> f64 {
+        -self.chi_squared(text)
+    }
+}
+
+/// Scores text by how close its index of coincidence is to a target value,
+/// typically that of the reference language. This is invariant under
+/// monoalphabetic substitution, so it is mainly useful for transposition and
+/// polyalphabetic ciphers.
+pub struct IndexOfCoincidenceScorer {
+    target : f64
+}
+
+impl IndexOfCoincidenceScorer {
+    pub fn new(target : f64) -> IndexOfCoincidenceScorer {
+        IndexOfCoincidenceScorer {
+            target
+        }
+    }
+
+    pub fn from_corpus(corpus : &str) -> IndexOfCoincidenceScorer {
+        IndexOfCoincidenceScorer::new(index_of_coincidence(corpus))
+    }
+}
+
+impl Scorer for IndexOfCoincidenceScorer {
+    fn score(&self, text : &str) -> f64 {
+        -(index_of_coincidence(text) - self.target).abs()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH : &str = "itwasthebestoftimesitwastheworstoftimesitwastheageofwisdomitwastheageoffoolishness";
+
+    #[test]
+    fn index_of_coincidence_of_uniform_text_is_zero() {
+        assert_eq!(index_of_coincidence("abcdefghijklmnopqrstuvwxyz"), 0.0);
+        assert_eq!(index_of_coincidence("aaaa"), 1.0);
+        assert_eq!(index_of_coincidence("éèéè"), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn chi_squared_prefers_english_letter_frequencies() {
+        let scorer = ChiSquaredScorer::from_corpus(ENGLISH, &Alphabet::english());
+        assert!(scorer.score("thetimesofwisdom") > scorer.score("qzxjvkqzxjvkwqzx"));
+    }
+
+    #[test]
+    fn index_of_coincidence_scorer_prefers_target() {
+        let scorer = IndexOfCoincidenceScorer::from_corpus(ENGLISH);
+        assert!(scorer.score(ENGLISH) > scorer.score("abcdefghijklmnopqrstuvwxyz"));
+    }
+}
@@ -0,0 +1,198 @@
+use matrix::Matrix;
+use std::fmt;
+use std::ops::Add;
+
+/// An edge weight that paths can be measured in.
+pub trait Weight: Copy + Ord + fmt::Debug + Add<Output = Self> {
+    fn zero() -> Self;
+
+    /// The largest weight, which searches use for lengths not yet reached.
+    fn max_value() -> Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// `self + other`, or `max_value()` if that overflows, so that lengths
+    /// too long to represent compare as unreached.
+    fn saturating_add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or_else(Self::max_value)
+    }
+}
+
+macro_rules! impl_weight {
+    ($($weight:ty),*) => {$(
+        impl Weight for $weight {
+            fn zero() -> Self {
+                0
+            }
+
+            fn max_value() -> Self {
+                <$weight>::MAX
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$weight>::checked_add(self, other)
+            }
+        }
+    )*}
+}
+
+impl_weight!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A directed graph on nodes `0..nodes()` with weighted edges, which is all
+/// the path and circuit searches need to know about a graph.
+pub trait WeightedGraph {
+    type Weight: Weight;
+
+    fn nodes(&self) -> usize;
+
+    /// The weight of the edge from `from` to `to`, or `None` if there is none.
+    /// There are no edges from a node to itself.
+    fn edge(&self, from: usize, to: usize) -> Option<Self::Weight>;
+
+    /// Whether every edge has the same weight in both directions. By default
+    /// this checks every pair of nodes.
+    fn is_symmetric(&self) -> bool {
+        (0..self.nodes()).all(|from| (from + 1..self.nodes()).all(|to| self.edge(from, to) == self.edge(to, from)))
+    }
+
+    /// The nodes with an edge from `node`, in increasing order.
+    fn adjacent_nodes(&self, node: usize) -> Vec<usize> {
+        (0..self.nodes()).filter(|&other| self.edge(node, other).is_some()).collect()
+    }
+
+    /// The length of `path`, or `None` unless it visits every node exactly
+    /// once and each step is an edge.
+    fn path_length(&self, path: &[usize]) -> Option<Self::Weight> {
+        if path.len() != self.nodes() {
+            return None;
+        }
+        let mut seen = vec![false; self.nodes()];
+        for &node in path {
+            if node >= self.nodes() || seen[node] {
+                return None;
+            }
+            seen[node] = true;
+        }
+        path.windows(2).try_fold(Self::Weight::zero(), |length, step| length.checked_add(self.edge(step[0], step[1])?))
+    }
+
+    /// The length of `circuit`, or `None` unless it visits every node exactly
+    /// once and each step, including back to the start, is an edge.
+    fn circuit_length(&self, circuit: &[usize]) -> Option<Self::Weight> {
+        let path = self.path_length(circuit)?;
+        if circuit.len() < 2 {
+            return None;
+        }
+        path.checked_add(self.edge(circuit[circuit.len() - 1], circuit[0])?)
+    }
+}
+
+/// A weighted graph stored as an adjacency matrix, with `None` where there
+/// is no edge, so that any weight, zero included, can be an edge.
+#[derive(Debug, Clone)]
+pub struct MatrixGraph<T> {
+    nodes: usize,
+    edges: Matrix<Option<T>>,
+    symmetric: bool,
+}
+
+impl<T: fmt::Display> fmt::Display for MatrixGraph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Graph {}:", self.nodes)?;
+        for from in 0..self.nodes {
+            let row: Vec<String> = (0..self.nodes).map(|to| match self.edges[(from, to)] {
+                Some(ref weight) => weight.to_string(),
+                None => "-".to_string(),
+            }).collect();
+            writeln!(f, "\t{}", row.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Clone> MatrixGraph<T> {
+    /// A graph with no edges. Setting an edge of a `symmetric` graph sets it
+    /// in both directions.
+    pub fn new(nodes: usize, symmetric: bool) -> Self {
+        MatrixGraph {
+            nodes,
+            symmetric,
+            edges: Matrix::new(nodes, nodes)
+        }
+    }
+
+    pub fn set_edge(&mut self, from: usize, to: usize, weight: T) {
+        if from != to {
+            if self.symmetric {
+                self.edges[(to, from)] = Some(weight.clone());
+            }
+            self.edges[(from, to)] = Some(weight);
+        }
+    }
+}
+
+impl<T: Weight> WeightedGraph for MatrixGraph<T> {
+    type Weight = T;
+
+    fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    fn edge(&self, from: usize, to: usize) -> Option<T> {
+        self.edges[(from, to)]
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.symmetric
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Only the trait's required methods, as another crate's graph would be.
+    struct Ring(usize);
+
+    impl WeightedGraph for Ring {
+        type Weight = u64;
+
+        fn nodes(&self) -> usize {
+            self.0
+        }
+
+        fn edge(&self, from: usize, to: usize) -> Option<u64> {
+            if (from + 1) % self.0 == to { Some(from as u64 + 1) } else { None }
+        }
+    }
+
+    #[test]
+    fn measures_paths_and_circuits() {
+        let ring = Ring(4);
+        assert!(!ring.is_symmetric());
+        assert_eq!(ring.adjacent_nodes(3), vec![0]);
+        assert_eq!(ring.path_length(&[0, 1, 2, 3]), Some(6));
+        assert_eq!(ring.circuit_length(&[1, 2, 3, 0]), Some(10));
+        assert_eq!(ring.circuit_length(&[0, 2, 1, 3]), None);
+        assert_eq!(ring.path_length(&[0, 1, 1, 3]), None);
+    }
+
+    #[test]
+    fn only_set_edges_exist() {
+        let mut graph: MatrixGraph<i32> = MatrixGraph::new(3, true);
+        graph.set_edge(0, 1, 5);
+        graph.set_edge(1, 2, -2);
+        graph.set_edge(2, 2, 7);
+        assert_eq!(graph.edge(1, 0), Some(5));
+        assert_eq!(graph.edge(2, 1), Some(-2));
+        assert_eq!(graph.edge(0, 2), None);
+        assert_eq!(graph.edge(2, 2), None);
+        assert_eq!(graph.path_length(&[0, 1, 2]), Some(3));
+
+        graph.set_edge(0, 2, 0);
+        assert_eq!(graph.edge(2, 0), Some(0));
+        assert_eq!(graph.circuit_length(&[0, 1, 2]), Some(3));
+        assert_eq!(graph.to_string(), "Graph 3:\n\t-, 5, 0\n\t5, -, -2\n\t0, -2, -\n");
+    }
+}
@@ -0,0 +1,6 @@
+//! Matrix and graph primitives shared by the graph crates in this repository:
+//! a generic dense `Matrix`, and the `WeightedGraph` trait that searches over
+//! paths and circuits are written against, with `MatrixGraph` implementing it.
+
+pub mod matrix;
+pub mod graph;
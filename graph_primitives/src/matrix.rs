@@ -0,0 +1,118 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A dense `rows` by `cols` matrix, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    buffer: Vec<T>
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Matrix {}x{} [", self.rows, self.cols)?;
+        for row in self.buffer.chunks(self.cols.max(1)).take(self.rows) {
+            write!(f, "\t")?;
+            for (col, value) in row.iter().enumerate() {
+                if col + 1 < self.cols {
+                    write!(f, "{}, ", value)?;
+                } else {
+                    writeln!(f, "{},", value)?;
+                }
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: Default> Matrix<T> {
+    /// A matrix with every entry `T::default()`.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let mut buffer = Vec::with_capacity(rows * cols);
+        buffer.resize_with(rows * cols, Default::default);
+        Matrix { rows, cols, buffer }
+    }
+}
+
+impl<T> Matrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            Some(&self.buffer[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.rows && col < self.cols {
+            Some(&mut self.buffer[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    /// The entry at (`row`, `col`) without checking `col` is in range, so an
+    /// out of range `col` reads an entry of a later row.
+    ///
+    /// # Safety
+    ///
+    /// `row * cols + col` must be inside the matrix; the buffer itself is still
+    /// bounds checked, so breaking this panics rather than reading past it.
+    pub unsafe fn get_unchecked(&self, row: usize, col: usize) -> &T {
+        &self.buffer[row * self.cols + col]
+    }
+
+    /// Like `get_unchecked`, for writing.
+    pub fn get_mut_unchecked(&mut self, row: usize, col: usize) -> &mut T {
+        &mut self.buffer[row * self.cols + col]
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(row < self.rows && col < self.cols, "({}, {}) is outside a {}x{} matrix", row, col, self.rows, self.cols);
+        &self.buffer[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(row < self.rows && col < self.cols, "({}, {}) is outside a {}x{} matrix", row, col, self.rows, self.cols);
+        &mut self.buffer[row * self.cols + col]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_row_by_row() {
+        let mut matrix: Matrix<u32> = Matrix::new(2, 3);
+        matrix[(1, 0)] = 4;
+        *matrix.get_mut(0, 2).unwrap() = 2;
+        assert_eq!(matrix.get(1, 0), Some(&4));
+        assert_eq!(matrix.get(2, 0), None);
+        assert_eq!(unsafe { *matrix.get_unchecked(0, 3) }, 4);
+        assert_eq!(matrix.to_string(), "Matrix 2x3 [\n\t0, 0, 2,\n\t4, 0, 0,\n]");
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_outside_the_matrix() {
+        let matrix: Matrix<u32> = Matrix::new(2, 3);
+        let _ = matrix[(0, 3)];
+    }
+}
@@ -0,0 +1,324 @@
+//! Exact shortest Hamiltonian circuits. Every solver returns the length of
+//! the shortest circuit and the circuit itself, starting from node 0, or
+//! `None` if there is no circuit through every node. Lengths that overflow
+//! the graph's weight type are treated as no circuit at all.
+
+use graph::{Weight, WeightedGraph};
+
+/// Held-Karp keeps a table of `2^(n-1) * (n-1)` lengths, about 350MB at this
+/// size with `u64` weights.
+pub const HELD_KARP_MAX_NODES: usize = 22;
+
+/// Naive shortest hamiltonian circuit calculator
+pub fn find_shortest_hamiltonian_circuit<G: WeightedGraph>(graph: &G, path: Vec<usize>, count: G::Weight) -> Option<(G::Weight, Vec<usize>)> {
+    let mut nodes_to_try = Vec::new();
+
+    // if we've formed a complete circuit, check if it works
+    if path.len() == graph.nodes() {
+        if let Some(weight) = graph.edge(path[path.len()-1], path[0]) {
+            return count.checked_add(weight).map(|length| (length, path));
+        } else {
+            return None;
+        }
+    }
+
+    let current_head = path[path.len() - 1];
+
+    // find which nodes to try
+    for i in 0..graph.nodes() {
+        if !path.contains(&i) && graph.edge(current_head, i).is_some() {
+            nodes_to_try.push(i);
+        }
+    }
+
+    let mut best_seen = None;
+    // for each node, recurse, and try
+    for node in nodes_to_try {
+        let mut vector = path.clone();
+        vector.push(node);
+        let new_count = match count.checked_add(graph.edge(current_head, node).unwrap()) {
+            Some(new_count) => new_count,
+            None => continue,
+        };
+
+        if let Some((cost, result_path)) = find_shortest_hamiltonian_circuit(graph, vector, new_count) {
+            match best_seen.take() {
+                Some((old_cost, old_result_path)) => {
+                    if old_cost > cost {
+                        best_seen = Some((cost, result_path));
+                    } else {
+                        best_seen = Some((old_cost, old_result_path));
+                    }
+                },
+                None => {
+                    best_seen = Some((cost, result_path));
+                }
+            }
+        }
+    }
+
+    best_seen
+}
+
+/// Held-Karp dynamic programming over subsets, in `O(2^n * n^2)` time.
+/// Panics on graphs of more than `HELD_KARP_MAX_NODES` nodes.
+pub fn held_karp<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>)> {
+    let nodes = graph.nodes();
+    assert!(nodes <= HELD_KARP_MAX_NODES, "Held-Karp is limited to {} nodes, not {}", HELD_KARP_MAX_NODES, nodes);
+    if nodes < 2 {
+        return None;
+    }
+    let unreachable = G::Weight::max_value();
+
+    // node `k + 1` is bit `k` of a subset; node 0 is always the start
+    let others = nodes - 1;
+    let subsets = 1usize << others;
+    // shortest[subset * others + last]: the shortest path from 0 through
+    // exactly the nodes in `subset`, ending at `last`
+    let mut shortest = vec![unreachable; subsets * others];
+    for last in 0..others {
+        if let Some(weight) = graph.edge(0, last + 1) {
+            shortest[(1 << last) * others + last] = weight;
+        }
+    }
+
+    for subset in 1..subsets {
+        for last in 0..others {
+            let length = shortest[subset * others + last];
+            if subset & (1 << last) == 0 || length == unreachable {
+                continue;
+            }
+            for next in 0..others {
+                if subset & (1 << next) != 0 {
+                    continue;
+                }
+                if let Some(weight) = graph.edge(last + 1, next + 1) {
+                    let entry = &mut shortest[(subset | 1 << next) * others + next];
+                    *entry = (*entry).min(length.saturating_add(weight));
+                }
+            }
+        }
+    }
+
+    let everything = subsets - 1;
+    let (length, mut last) = (0..others)
+        .filter(|&last| shortest[everything * others + last] != unreachable)
+        .filter_map(|last| graph.edge(last + 1, 0).map(|weight| (shortest[everything * others + last].saturating_add(weight), last)))
+        .filter(|&(length, _)| length != unreachable)
+        .min()?;
+
+    // walk back through the table to recover the circuit
+    let mut circuit = vec![];
+    let mut subset = everything;
+    loop {
+        circuit.push(last + 1);
+        let rest = subset & !(1 << last);
+        if rest == 0 {
+            break;
+        }
+        let here = shortest[subset * others + last];
+        last = (0..others)
+            .find(|&previous| {
+                let before = shortest[rest * others + previous];
+                rest & (1 << previous) != 0 && before != unreachable
+                    && graph.edge(previous + 1, last + 1).map(|weight| before.saturating_add(weight)) == Some(here)
+            })
+            .expect("every reachable entry has a predecessor");
+        subset = rest;
+    }
+    circuit.push(0);
+    circuit.reverse();
+
+    Some((length, circuit))
+}
+
+/// The cheaper direction of the edge between `a` and `b`, so that bounds
+/// built from undirected trees also hold on asymmetric graphs.
+fn relaxed_edge<G: WeightedGraph>(graph: &G, a: usize, b: usize) -> Option<G::Weight> {
+    match (graph.edge(a, b), graph.edge(b, a)) {
+        (Some(x), Some(y)) => Some(x.min(y)),
+        (x, y) => x.or(y),
+    }
+}
+
+/// The weight of a minimum spanning tree over `nodes` by Prim's algorithm,
+/// or `None` if they aren't connected.
+fn spanning_tree_weight<G: WeightedGraph>(graph: &G, nodes: &[usize]) -> Option<G::Weight> {
+    if nodes.is_empty() {
+        return Some(G::Weight::zero());
+    }
+    let mut in_tree = vec![false; nodes.len()];
+    let mut distance: Vec<Option<G::Weight>> = vec![None; nodes.len()];
+    distance[0] = Some(G::Weight::zero());
+    let mut total = G::Weight::zero();
+
+    for _ in 0..nodes.len() {
+        let (weight, closest) = (0..nodes.len())
+            .filter(|&i| !in_tree[i])
+            .filter_map(|i| distance[i].map(|weight| (weight, i)))
+            .min()?;
+        in_tree[closest] = true;
+        total = total.saturating_add(weight);
+
+        for i in 0..nodes.len() {
+            if !in_tree[i] {
+                if let Some(weight) = relaxed_edge(graph, nodes[closest], nodes[i]) {
+                    distance[i] = Some(distance[i].map_or(weight, |distance| distance.min(weight)));
+                }
+            }
+        }
+    }
+    Some(total)
+}
+
+/// A lower bound on the length of any circuit: a 1-tree, the minimum
+/// spanning tree of every node but 0 plus the two shortest edges at 0.
+fn one_tree_bound<G: WeightedGraph>(graph: &G) -> Option<G::Weight> {
+    let rest: Vec<usize> = (1..graph.nodes()).collect();
+    let tree = spanning_tree_weight(graph, &rest)?;
+    let mut edges: Vec<G::Weight> = rest.iter().filter_map(|&node| relaxed_edge(graph, 0, node)).collect();
+    if edges.len() < 2 {
+        return None;
+    }
+    edges.sort_unstable();
+    Some(tree.saturating_add(edges[0]).saturating_add(edges[1]))
+}
+
+struct BranchAndBound<'a, G: WeightedGraph + 'a> {
+    graph: &'a G,
+    path: Vec<usize>,
+    visited: Vec<bool>,
+    best: Option<(G::Weight, Vec<usize>)>,
+    /// No circuit is shorter than this, so one this long ends the search.
+    lower_bound: G::Weight
+}
+
+impl<'a, G: WeightedGraph> BranchAndBound<'a, G> {
+    fn best_length(&self) -> G::Weight {
+        self.best.as_ref().map_or(G::Weight::max_value(), |&(length, _)| length)
+    }
+
+    /// Extends the path, which has length `length`, to every circuit that
+    /// could beat the best so far.
+    fn search(&mut self, length: G::Weight) {
+        if self.best_length() == self.lower_bound {
+            return;
+        }
+        let head = self.path[self.path.len() - 1];
+
+        if self.path.len() == self.graph.nodes() {
+            if let Some(weight) = self.graph.edge(head, 0) {
+                if length.saturating_add(weight) < self.best_length() {
+                    self.best = Some((length + weight, self.path.clone()));
+                }
+            }
+            return;
+        }
+
+        // the rest of the circuit is a path from the head through every
+        // unvisited node back to 0, so it weighs at least their spanning tree
+        let mut remaining: Vec<usize> = (0..self.graph.nodes()).filter(|&node| !self.visited[node]).collect();
+        remaining.push(head);
+        remaining.push(0);
+        match spanning_tree_weight(self.graph, &remaining) {
+            Some(bound) if length.saturating_add(bound) < self.best_length() => (),
+            _ => return,
+        }
+
+        let mut next: Vec<(G::Weight, usize)> = (0..self.graph.nodes())
+            .filter(|&node| !self.visited[node])
+            .filter_map(|node| self.graph.edge(head, node).map(|weight| (weight, node)))
+            .collect();
+        // trying the closest first finds a good circuit early, so more is pruned
+        next.sort_unstable();
+
+        for (weight, node) in next {
+            self.visited[node] = true;
+            self.path.push(node);
+            self.search(length.saturating_add(weight));
+            self.path.pop();
+            self.visited[node] = false;
+        }
+    }
+}
+
+/// Depth first branch and bound, pruning any partial circuit whose length
+/// plus a minimum spanning tree bound on the rest can't beat the best circuit
+/// found so far. Asymmetric graphs are bounded by the cheaper direction of
+/// each edge.
+pub fn branch_and_bound<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>)> {
+    if graph.nodes() < 2 {
+        return None;
+    }
+    // two nodes make a circuit of the same pair of nodes twice, which a
+    // 1-tree can't describe; otherwise without a 1-tree there's no circuit
+    let lower_bound = if graph.nodes() > 2 { one_tree_bound(graph)? } else { G::Weight::zero() };
+
+    let mut visited = vec![false; graph.nodes()];
+    visited[0] = true;
+    let mut search = BranchAndBound { graph, path: vec![0], visited, best: None, lower_bound };
+    search.search(G::Weight::zero());
+    search.best
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::{Graph, MatrixGraph};
+    use testing::{random_graph, Lcg};
+
+    #[test]
+    fn agree_with_the_naive_search() {
+        let mut rng = Lcg(17);
+        for round in 0..60 {
+            let graph = random_graph(&mut rng, 2 + round % 7, round % 2 == 0, 2 + round as u32 % 6);
+            let expected = find_shortest_hamiltonian_circuit(&graph, vec![0], 0).map(|(length, _)| length);
+
+            for (name, found) in [("held_karp", held_karp(&graph)), ("branch_and_bound", branch_and_bound(&graph))] {
+                assert_eq!(found.as_ref().map(|&(length, _)| length), expected, "{} on {}", name, graph);
+                if let Some((length, ref circuit)) = found {
+                    assert_eq!(circuit[0], 0);
+                    assert_eq!(graph.circuit_length(circuit), Some(length), "{} on {}", name, graph);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solves_larger_asymmetric_graphs() {
+        let mut rng = Lcg(5);
+        let graph = random_graph(&mut rng, 14, false, 10);
+        let (length, circuit) = held_karp(&graph).unwrap();
+        assert_eq!(graph.circuit_length(&circuit), Some(length));
+        assert_eq!(branch_and_bound(&graph).map(|(length, _)| length), Some(length));
+    }
+
+    #[test]
+    fn finds_no_circuit_through_a_dead_end() {
+        // node 3 hangs off node 0 alone
+        let mut graph = Graph::new(4, true);
+        graph.set_edge(0, 1, 1);
+        graph.set_edge(1, 2, 1);
+        graph.set_edge(2, 0, 1);
+        graph.set_edge(0, 3, 1);
+        assert_eq!(held_karp(&graph), None);
+        assert_eq!(branch_and_bound(&graph), None);
+    }
+
+    #[test]
+    fn measures_in_the_graph_weight_type() {
+        // too long for u32, and too long for u8 all the way round
+        let mut wide: MatrixGraph<u64> = MatrixGraph::new(4, true);
+        let mut narrow: MatrixGraph<u8> = MatrixGraph::new(4, true);
+        for &(a, b, weight) in &[(0, 1, 100), (1, 2, 100), (2, 3, 100), (3, 0, 100), (0, 2, 150), (1, 3, 150)] {
+            wide.set_edge(a, b, weight << 32);
+            narrow.set_edge(a, b, weight as u8);
+        }
+        for found in [held_karp(&wide), branch_and_bound(&wide), find_shortest_hamiltonian_circuit(&wide, vec![0], 0)] {
+            assert_eq!(found.map(|(length, _)| length), Some(400 << 32));
+        }
+        assert_eq!(held_karp(&narrow), None);
+        assert_eq!(branch_and_bound(&narrow), None);
+    }
+}
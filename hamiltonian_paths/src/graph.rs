@@ -0,0 +1,5 @@
+pub use graph_primitives::graph::{MatrixGraph, Weight, WeightedGraph};
+
+/// The graph problems are read into, with `u32` weights. The searches accept
+/// any `WeightedGraph`.
+pub type Graph = MatrixGraph<u32>;
@@ -0,0 +1,498 @@
+//! Approximate shortest Hamiltonian circuits, for graphs too large for the
+//! exact solvers. Constructive heuristics build a circuit from nothing and
+//! return `None` if they can't close one; improvement heuristics shorten a
+//! circuit they're given. Like the exact solvers, circuits start from node 0.
+
+use graph::{Weight, WeightedGraph};
+use std::cmp;
+
+/// Christofides matches up to this many odd degree nodes exactly, and any
+/// more greedily, losing its guarantee of 1.5 times the optimum.
+pub const CHRISTOFIDES_EXACT_MATCHING: usize = 20;
+
+/// How Christofides paired up the odd degree nodes of its spanning tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matching {
+    /// A minimum matching, so the tour is within 1.5 times the optimum.
+    Exact,
+    /// More than `CHRISTOFIDES_EXACT_MATCHING` nodes, matched cheapest pair
+    /// first, without that guarantee.
+    Greedy,
+}
+
+/// Rotates `circuit` to start from node 0 and measures it.
+fn finish<G: WeightedGraph>(graph: &G, mut circuit: Vec<usize>) -> Option<(G::Weight, Vec<usize>)> {
+    if let Some(start) = circuit.iter().position(|&node| node == 0) {
+        circuit.rotate_left(start);
+    }
+    graph.circuit_length(&circuit).map(|length| (length, circuit))
+}
+
+/// Always goes to the closest unvisited node, starting from node 0.
+pub fn nearest_neighbour<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>)> {
+    if graph.nodes() < 2 {
+        return None;
+    }
+    let mut visited = vec![false; graph.nodes()];
+    visited[0] = true;
+    let mut circuit = vec![0];
+
+    while circuit.len() < graph.nodes() {
+        let head = circuit[circuit.len() - 1];
+        let (_, next) = (0..graph.nodes())
+            .filter(|&node| !visited[node])
+            .filter_map(|node| graph.edge(head, node).map(|weight| (weight, node)))
+            .min()?;
+        visited[next] = true;
+        circuit.push(next);
+    }
+    finish(graph, circuit)
+}
+
+fn find_root(parent: &mut [usize], node: usize) -> usize {
+    let mut root = node;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = node;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+/// Takes edges shortest first, skipping any that would give a node a second
+/// way in or out or close a circuit early, until they form a single path, and
+/// closes that path.
+pub fn greedy_edge<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>)> {
+    let nodes = graph.nodes();
+    if nodes < 3 {
+        return finish(graph, (0..nodes).collect());
+    }
+    let symmetric = graph.is_symmetric();
+
+    let mut edges: Vec<(G::Weight, usize, usize)> = vec![];
+    for from in 0..nodes {
+        for to in 0..nodes {
+            if symmetric && to < from {
+                continue;
+            }
+            if let Some(weight) = graph.edge(from, to) {
+                edges.push((weight, from, to));
+            }
+        }
+    }
+    edges.sort_unstable();
+
+    // undirected, a node's two neighbours; directed, where it goes and comes from
+    let mut next: Vec<Option<usize>> = vec![None; nodes];
+    let mut previous: Vec<Option<usize>> = vec![None; nodes];
+    let mut component: Vec<usize> = (0..nodes).collect();
+    let mut taken = 0;
+
+    for (_, from, to) in edges {
+        if taken == nodes - 1 {
+            break;
+        }
+        let free = if symmetric {
+            (next[from].is_none() || previous[from].is_none()) && (next[to].is_none() || previous[to].is_none())
+        } else {
+            next[from].is_none() && previous[to].is_none()
+        };
+        let (a, b) = (find_root(&mut component, from), find_root(&mut component, to));
+        if !free || a == b {
+            continue;
+        }
+        component[a] = b;
+        taken += 1;
+
+        if symmetric {
+            for &(node, other) in &[(from, to), (to, from)] {
+                if next[node].is_none() {
+                    next[node] = Some(other);
+                } else {
+                    previous[node] = Some(other);
+                }
+            }
+        } else {
+            next[from] = Some(to);
+            previous[to] = Some(from);
+        }
+    }
+    if taken < nodes - 1 {
+        return None;
+    }
+
+    // walk the path from one of its ends
+    let start = (0..nodes).find(|&node| previous[node].is_none())?;
+    let mut path = vec![start];
+    let mut last = None;
+    let mut current = start;
+    while path.len() < nodes {
+        let step = if symmetric {
+            [next[current], previous[current]].iter().cloned().flatten().find(|&node| Some(node) != last)?
+        } else {
+            next[current]?
+        };
+        last = Some(current);
+        current = step;
+        path.push(current);
+    }
+    finish(graph, path)
+}
+
+/// A minimum spanning tree by Prim's algorithm, as the parent of every node
+/// but 0, or `None` if the graph isn't connected.
+fn spanning_tree<G: WeightedGraph>(graph: &G) -> Option<Vec<(usize, usize)>> {
+    let nodes = graph.nodes();
+    let mut in_tree = vec![false; nodes];
+    let mut closest: Vec<Option<(G::Weight, usize)>> = vec![None; nodes];
+    closest[0] = Some((G::Weight::zero(), 0));
+    let mut tree = vec![];
+
+    for _ in 0..nodes {
+        let (_, node) = (0..nodes)
+            .filter(|&node| !in_tree[node])
+            .filter_map(|node| closest[node].map(|(weight, _)| (weight, node)))
+            .min()?;
+        in_tree[node] = true;
+        if node != 0 {
+            tree.push((closest[node].unwrap().1, node));
+        }
+        for other in 0..nodes {
+            if let (false, Some(weight)) = (in_tree[other], graph.edge(node, other)) {
+                if closest[other].is_none_or(|(best, _)| weight < best) {
+                    closest[other] = Some((weight, node));
+                }
+            }
+        }
+    }
+    Some(tree)
+}
+
+/// Pairs up `odd` as cheaply as possible: exactly by dynamic programming over
+/// subsets if there are few enough, otherwise greedily, cheapest pair first.
+fn minimum_matching<G: WeightedGraph>(graph: &G, odd: &[usize]) -> (Vec<(usize, usize)>, Matching) {
+    let weight = |a: usize, b: usize| graph.edge(odd[a], odd[b]).unwrap();
+
+    if odd.len() > CHRISTOFIDES_EXACT_MATCHING {
+        let mut pairs: Vec<(G::Weight, usize, usize)> = vec![];
+        for a in 0..odd.len() {
+            for b in a + 1..odd.len() {
+                pairs.push((weight(a, b), a, b));
+            }
+        }
+        pairs.sort_unstable();
+        let mut matched = vec![false; odd.len()];
+        let mut matching = vec![];
+        for (_, a, b) in pairs {
+            if !matched[a] && !matched[b] {
+                matched[a] = true;
+                matched[b] = true;
+                matching.push((odd[a], odd[b]));
+            }
+        }
+        return (matching, Matching::Greedy);
+    }
+
+    // cheapest[subset]: the cheapest matching of the nodes in `subset`, always
+    // pairing off its lowest node, with `partner` recording who with
+    let subsets = 1usize << odd.len();
+    let unreachable = G::Weight::max_value();
+    let mut cheapest = vec![unreachable; subsets];
+    let mut partner = vec![0; subsets];
+    cheapest[0] = G::Weight::zero();
+    for subset in 1..subsets {
+        if subset.count_ones() % 2 != 0 {
+            continue;
+        }
+        let lowest = subset.trailing_zeros() as usize;
+        for other in lowest + 1..odd.len() {
+            if subset & (1 << other) == 0 {
+                continue;
+            }
+            let rest = cheapest[subset & !(1 << lowest) & !(1 << other)];
+            let matched = rest.saturating_add(weight(lowest, other));
+            if rest != unreachable && matched < cheapest[subset] {
+                cheapest[subset] = matched;
+                partner[subset] = other;
+            }
+        }
+    }
+
+    let mut matching = vec![];
+    let mut subset = subsets - 1;
+    while subset != 0 {
+        let lowest = subset.trailing_zeros() as usize;
+        let other = partner[subset];
+        matching.push((odd[lowest], odd[other]));
+        subset &= !(1 << lowest) & !(1 << other);
+    }
+    (matching, Matching::Exact)
+}
+
+/// Christofides' algorithm: a minimum spanning tree, plus a minimum matching
+/// of its odd degree nodes, walked as an Euler circuit that skips nodes it
+/// has already visited. On complete symmetric graphs obeying the triangle
+/// inequality it is within 1.5 times the optimum, as long as the matching is
+/// exact; it returns `None` on asymmetric or incomplete graphs.
+pub fn christofides<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>)> {
+    christofides_matching(graph).map(|(length, circuit, _)| (length, circuit))
+}
+
+/// Christofides' algorithm, also saying whether the odd degree nodes were
+/// matched exactly or had to be matched greedily.
+pub fn christofides_matching<G: WeightedGraph>(graph: &G) -> Option<(G::Weight, Vec<usize>, Matching)> {
+    let nodes = graph.nodes();
+    if !graph.is_symmetric() {
+        return None;
+    }
+    if nodes < 3 {
+        return finish(graph, (0..nodes).collect()).map(|(length, circuit)| (length, circuit, Matching::Exact));
+    }
+    let complete = (0..nodes).all(|from| (from + 1..nodes).all(|to| graph.edge(from, to).is_some()));
+    if !complete {
+        return None;
+    }
+
+    let mut edges = spanning_tree(graph)?;
+    let mut degree = vec![0; nodes];
+    for &(a, b) in &edges {
+        degree[a] += 1;
+        degree[b] += 1;
+    }
+    let odd: Vec<usize> = (0..nodes).filter(|&node| degree[node] % 2 == 1).collect();
+    let (matching, kind) = minimum_matching(graph, &odd);
+    edges.extend(matching);
+
+    // Hierholzer's algorithm over the tree and matching together
+    let mut incident: Vec<Vec<(usize, usize)>> = vec![vec![]; nodes];
+    for (id, &(a, b)) in edges.iter().enumerate() {
+        incident[a].push((b, id));
+        incident[b].push((a, id));
+    }
+    let mut used = vec![false; edges.len()];
+    let mut unused_from = vec![0; nodes];
+    let mut stack = vec![0];
+    let mut euler = vec![];
+    while let Some(&node) = stack.last() {
+        while unused_from[node] < incident[node].len() && used[incident[node][unused_from[node]].1] {
+            unused_from[node] += 1;
+        }
+        if unused_from[node] == incident[node].len() {
+            euler.push(node);
+            stack.pop();
+        } else {
+            let (other, id) = incident[node][unused_from[node]];
+            used[id] = true;
+            stack.push(other);
+        }
+    }
+
+    let mut visited = vec![false; nodes];
+    let circuit = euler.into_iter().filter(|&node| !std::mem::replace(&mut visited[node], true)).collect();
+    finish(graph, circuit).map(|(length, circuit)| (length, circuit, kind))
+}
+
+/// The sum of `weights`, saturating rather than overflowing.
+fn total<W: Weight>(weights: &[W]) -> W {
+    weights.iter().fold(W::zero(), |sum, &weight| sum.saturating_add(weight))
+}
+
+/// Reverses stretches of the circuit while that makes it shorter. On
+/// asymmetric graphs the reversed stretch is driven the other way, which is
+/// accounted for, and stretches that can't be are left alone.
+pub fn two_opt<G: WeightedGraph>(graph: &G, circuit: &[usize]) -> (G::Weight, Vec<usize>) {
+    graph.circuit_length(circuit).expect("2-opt needs a valid circuit to improve");
+    let nodes = circuit.len();
+    let mut circuit = circuit.to_vec();
+    let weight = |from: usize, to: usize| graph.edge(from, to);
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        // the length of the circuit up to each position, forwards and
+        // backwards, and how many backward steps have no edge
+        let mut forwards = vec![G::Weight::zero(); nodes];
+        let mut backwards = vec![G::Weight::zero(); nodes];
+        let mut missing = vec![0; nodes];
+        for i in 1..nodes {
+            forwards[i] = forwards[i - 1] + weight(circuit[i - 1], circuit[i]).unwrap();
+            let back = weight(circuit[i], circuit[i - 1]);
+            backwards[i] = backwards[i - 1].saturating_add(back.unwrap_or(G::Weight::zero()));
+            missing[i] = missing[i - 1] + back.is_none() as usize;
+        }
+
+        'search: for i in 1..nodes - 1 {
+            for j in i + 1..nodes {
+                if missing[j] != missing[i] {
+                    continue;
+                }
+                let (before, after) = (circuit[i - 1], circuit[(j + 1) % nodes]);
+                let (new_in, new_out) = match (weight(before, circuit[j]), weight(circuit[i], after)) {
+                    (Some(new_in), Some(new_out)) => (new_in, new_out),
+                    _ => continue,
+                };
+                // the stretch from i to j weighs forwards[j] - forwards[i] one
+                // way and backwards[j] - backwards[i] the other; both sides
+                // carry the other's prefix so nothing is subtracted
+                let old = [weight(before, circuit[i]).unwrap(), weight(circuit[j], after).unwrap(), forwards[j], backwards[i]];
+                let new = [new_in, new_out, backwards[j], forwards[i]];
+                if total(&new) < total(&old) {
+                    circuit[i..j + 1].reverse();
+                    improved = true;
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    let length = graph.circuit_length(&circuit).unwrap();
+    (length, circuit)
+}
+
+/// Moves stretches of one to three nodes elsewhere in the circuit while that
+/// makes it shorter, also trying them reversed on symmetric graphs.
+pub fn or_opt<G: WeightedGraph>(graph: &G, circuit: &[usize]) -> (G::Weight, Vec<usize>) {
+    graph.circuit_length(circuit).expect("Or-opt needs a valid circuit to improve");
+    let nodes = circuit.len();
+    let mut circuit = circuit.to_vec();
+    let weight = |from: usize, to: usize| graph.edge(from, to);
+    let orientations = if graph.is_symmetric() { 2 } else { 1 };
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        'search: for length in 1..cmp::min(4, nodes - 1) {
+            for start in 1..nodes - length + 1 {
+                let end = start + length;
+                let (before, after) = (circuit[start - 1], circuit[end % nodes]);
+                let (first, last) = (circuit[start], circuit[end - 1]);
+                let bridge = match weight(before, after) {
+                    Some(bridge) => bridge,
+                    None => continue,
+                };
+                // moving it pays off when the edges into its new place, plus
+                // the bridge over its old one, weigh less than the edges into
+                // its old place plus the gap it fills
+                let removed = [weight(before, first).unwrap(), weight(last, after).unwrap()];
+                let rest: Vec<usize> = circuit[..start].iter().chain(circuit[end..].iter()).cloned().collect();
+
+                for at in 0..rest.len() {
+                    // putting it back where it was
+                    if at == start - 1 {
+                        continue;
+                    }
+                    let (left, right) = (rest[at], rest[(at + 1) % rest.len()]);
+                    let gap = weight(left, right).unwrap_or(G::Weight::zero());
+                    for reversed in 0..orientations {
+                        let (head, tail) = if reversed == 1 { (last, first) } else { (first, last) };
+                        let added = match (weight(left, head), weight(tail, right)) {
+                            (Some(into), Some(out_of)) => [into, out_of, bridge],
+                            _ => continue,
+                        };
+                        if total(&added) < total(&[removed[0], removed[1], gap]) {
+                            let mut stretch = circuit[start..end].to_vec();
+                            if reversed == 1 {
+                                stretch.reverse();
+                            }
+                            circuit = rest[..at + 1].iter().chain(stretch.iter()).chain(rest[at + 1..].iter()).cloned().collect();
+                            improved = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    finish(graph, circuit).unwrap()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exact::held_karp;
+    use graph::Graph;
+    use testing::{random_graph, random_metric_graph, Lcg};
+
+    #[test]
+    fn constructions_are_valid_and_no_better_than_optimal() {
+        let mut rng = Lcg(11);
+        for round in 0..40 {
+            let graph = random_graph(&mut rng, 3 + round % 8, round % 2 == 0, 1000);
+            let (optimum, _) = held_karp(&graph).unwrap();
+
+            let mut found = vec![nearest_neighbour(&graph), greedy_edge(&graph)];
+            if graph.is_symmetric() {
+                found.push(christofides(&graph));
+            }
+            for (length, circuit) in found.into_iter().map(Option::unwrap) {
+                assert_eq!(circuit[0], 0);
+                assert_eq!(graph.circuit_length(&circuit), Some(length), "{:?} on {}", circuit, graph);
+                assert!(length >= optimum);
+            }
+        }
+    }
+
+    #[test]
+    fn christofides_is_within_half_again_of_optimal() {
+        let mut rng = Lcg(23);
+        for round in 0..20 {
+            let graph = random_metric_graph(&mut rng, 4 + round % 8);
+            let (optimum, _) = held_karp(&graph).unwrap();
+            let (length, _, matching) = christofides_matching(&graph).unwrap();
+            assert_eq!(matching, Matching::Exact);
+            assert!(2 * length <= 3 * optimum, "{} against an optimum of {} on {}", length, optimum, graph);
+        }
+    }
+
+    #[test]
+    fn christofides_says_when_it_matched_greedily() {
+        let graph = random_metric_graph(&mut Lcg(5), 200);
+        let (length, circuit, matching) = christofides_matching(&graph).unwrap();
+        assert_eq!(matching, Matching::Greedy);
+        assert_eq!(graph.circuit_length(&circuit), Some(length));
+    }
+
+    #[test]
+    fn improvements_never_lengthen_a_circuit() {
+        let mut rng = Lcg(31);
+        for round in 0..40 {
+            let graph = random_graph(&mut rng, 4 + round % 7, round % 2 == 0, 1000);
+            let (optimum, _) = held_karp(&graph).unwrap();
+            let (start, circuit) = nearest_neighbour(&graph).unwrap();
+
+            for (length, improved) in [two_opt(&graph, &circuit), or_opt(&graph, &circuit)] {
+                assert_eq!(improved[0], 0);
+                assert_eq!(graph.circuit_length(&improved), Some(length), "{:?} on {}", improved, graph);
+                assert!(optimum <= length && length <= start);
+            }
+        }
+    }
+
+    #[test]
+    fn two_opt_untangles_a_crossing() {
+        // a square visited corner to corner crosses itself
+        let mut graph = Graph::new(4, true);
+        for &(a, b, weight) in &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 0, 1), (0, 2, 2), (1, 3, 2)] {
+            graph.set_edge(a, b, weight);
+        }
+        assert_eq!(two_opt(&graph, &[0, 2, 1, 3]).0, 4);
+        assert_eq!(or_opt(&graph, &[0, 2, 1, 3]).0, 4);
+    }
+
+    #[test]
+    fn constructions_fail_without_a_circuit() {
+        let mut graph = Graph::new(4, true);
+        graph.set_edge(0, 1, 1);
+        graph.set_edge(1, 2, 1);
+        graph.set_edge(2, 3, 1);
+        assert_eq!(nearest_neighbour(&graph), None);
+        assert_eq!(greedy_edge(&graph), None);
+        assert_eq!(christofides(&graph), None);
+    }
+}
@@ -0,0 +1,13 @@
+//! Shortest Hamiltonian circuits and paths: exact searches, heuristics for
+//! graphs too large for them, and TSPLIB input and output. The searches take
+//! any `WeightedGraph`, measuring lengths in its own weight type.
+
+extern crate graph_primitives;
+
+pub mod graph;
+pub mod exact;
+pub mod heuristics;
+pub mod paths;
+pub mod tsplib;
+#[cfg(test)]
+mod testing;
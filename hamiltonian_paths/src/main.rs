@@ -1,140 +1,245 @@
-mod matrix;
-use matrix::Matrix2D;
-use std::fmt;
-
-#[derive(Debug, Clone)]
-pub struct Graph {
-    nodes: usize,
-    edges: Matrix2D,
-    symmetric: bool,
+extern crate hamiltonian_paths;
+
+use hamiltonian_paths::graph::{Graph, WeightedGraph};
+use hamiltonian_paths::exact::{branch_and_bound, find_shortest_hamiltonian_circuit, held_karp, HELD_KARP_MAX_NODES};
+use hamiltonian_paths::heuristics::{christofides_matching, greedy_edge, nearest_neighbour, or_opt, two_opt, Matching, CHRISTOFIDES_EXACT_MATCHING};
+use hamiltonian_paths::paths::{hamiltonian_paths, has_hamiltonian_path, shortest_path, SHORTEST_PATH_MAX_NODES};
+use hamiltonian_paths::tsplib::{read_tsp, write_tour, Tsp};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::time::Instant;
+
+const USAGE: &str = "usage: hamiltonian_paths [options] FILE
+
+  FILE              a TSPLIB .tsp problem, with EXPLICIT weights or EUC_2D
+                    or GEO coordinates
+  --solver NAME     naive, held-karp, branch-and-bound, nearest-neighbour,
+                    greedy-edge, christofides, 2-opt or or-opt (default
+                    2-opt); 2-opt and or-opt improve a nearest neighbour tour,
+                    and christofides says if it had to fall back to a greedy
+                    matching
+  --tour PATH       write the tour found to PATH as a TSPLIB .tour file
+  --path            find the shortest open path through every node instead
+                    of a tour, exactly; --solver doesn't apply
+  --count-paths     count the open paths through every node instead
+  --start N         with --path or --count-paths, start the path at node N
+  --end N           with --path or --count-paths, end the path at node N
+
+Nodes are numbered from 1, as in the problem file.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Solver {
+    Naive,
+    HeldKarp,
+    BranchAndBound,
+    NearestNeighbour,
+    GreedyEdge,
+    Christofides,
+    TwoOpt,
+    OrOpt,
 }
 
-impl fmt::Display for Graph {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Graph {}:\n", self.nodes);
-        write!(f, "{}", self.edges)
+impl FromStr for Solver {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "naive" => Ok(Solver::Naive),
+            "held-karp" => Ok(Solver::HeldKarp),
+            "branch-and-bound" => Ok(Solver::BranchAndBound),
+            "nearest-neighbour" => Ok(Solver::NearestNeighbour),
+            "greedy-edge" => Ok(Solver::GreedyEdge),
+            "christofides" => Ok(Solver::Christofides),
+            "2-opt" => Ok(Solver::TwoOpt),
+            "or-opt" => Ok(Solver::OrOpt),
+            _ => Err(format!("unknown solver {}", name)),
+        }
     }
 }
 
+impl Solver {
+    /// The shortest circuit this solver finds, or `None` if it finds none.
+    fn solve(self, graph: &Graph) -> Result<Option<(u32, Vec<usize>)>, String> {
+        Ok(match self {
+            Solver::Naive => if graph.nodes() > 0 { find_shortest_hamiltonian_circuit(graph, vec![0], 0) } else { None },
+            Solver::HeldKarp => {
+                if graph.nodes() > HELD_KARP_MAX_NODES {
+                    return Err(format!("held-karp is limited to {} nodes, not {}", HELD_KARP_MAX_NODES, graph.nodes()));
+                }
+                held_karp(graph)
+            }
+            Solver::BranchAndBound => branch_and_bound(graph),
+            Solver::NearestNeighbour => nearest_neighbour(graph),
+            Solver::GreedyEdge => greedy_edge(graph),
+            Solver::Christofides => {
+                if !graph.is_symmetric() {
+                    return Err("christofides needs a symmetric problem".to_string());
+                }
+                let found = christofides_matching(graph);
+                if let Some((_, _, Matching::Greedy)) = found {
+                    eprintln!("christofides: more than {} odd degree nodes, so they were matched greedily \
+                               and the tour may be more than 1.5 times the optimum", CHRISTOFIDES_EXACT_MATCHING);
+                }
+                found.map(|(length, circuit, _)| (length, circuit))
+            }
+            Solver::TwoOpt => nearest_neighbour(graph).map(|(_, circuit)| two_opt(graph, &circuit)),
+            Solver::OrOpt => nearest_neighbour(graph).map(|(_, circuit)| or_opt(graph, &circuit)),
+        })
+    }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Tour,
+    Path,
+    CountPaths,
+}
 
-impl Graph {
-    pub fn new(nodes: usize, symmetric: bool) -> Self {
-        Graph {
-            nodes,
-            symmetric,
-            edges: Matrix2D::new(nodes, nodes)
-        }
-    }
+struct Options {
+    input: PathBuf,
+    mode: Mode,
+    solver: Solver,
+    tour: Option<PathBuf>,
+    start: Option<usize>,
+    end: Option<usize>,
+}
 
-    pub fn set_edge(&mut self, from: usize, to: usize, weight: u32) {
-        if from != to {
-            self.edges[(from,to)] = weight;
+fn value(arg: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} needs a value", arg))
+}
 
-            if self.symmetric {
-                self.edges[(to,from)] = weight;
-            }
-        }
+/// A node numbered from 1, as it's given on the command line, numbered from 0.
+fn node(arg: &str, value: Option<String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+    match value.parse::<usize>() {
+        Ok(node) if node >= 1 => Ok(node - 1),
+        _ => Err(format!("{} is not a valid node for {}", value, arg)),
     }
+}
 
-    pub fn edge(&self, from: usize, to: usize) -> Option<u32> {
-        if from == to {
-            return None;
-        }
-        let result = self.edges[(from,to)];
-
-        if result > 0 {
-            Some(result)
-        } else {
-            None
+fn parse_options<I: Iterator<Item=String>>(mut args: I) -> Result<Options, String> {
+    let mut input = None;
+    let mut mode = Mode::Tour;
+    let mut solver = Solver::TwoOpt;
+    let mut tour = None;
+    let mut start = None;
+    let mut end = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--solver" => solver = value(&arg, args.next())?.parse()?,
+            "--tour" => tour = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--path" => mode = Mode::Path,
+            "--count-paths" => mode = Mode::CountPaths,
+            "--start" => start = Some(node(&arg, args.next())?),
+            "--end" => end = Some(node(&arg, args.next())?),
+            "--help" | "-h" => return Err(String::new()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if input.is_some() => return Err("only one input file can be given".to_string()),
+            _ => input = Some(PathBuf::from(arg)),
         }
     }
 
-    pub fn adjacent_nodes(&self, node: usize) -> Vec<usize> {
-        let mut result = Vec::new();
-
-        for other in 0..self.nodes {
-            if self.edges[(node,other)] > 0 {
-                result.push(other);
-            }
-        }
-        return result;
+    let input = input.ok_or_else(|| "no input file given".to_string())?;
+    if mode != Mode::Tour && tour.is_some() {
+        return Err("--tour only applies when finding a tour".to_string());
+    }
+    if mode == Mode::Tour && (start.is_some() || end.is_some()) {
+        return Err("--start and --end only apply with --path or --count-paths".to_string());
     }
+    Ok(Options { input, mode, solver, tour, start, end })
 }
 
-
-/// Naive shortest hamiltonian circuit calculator
-fn find_shortest_hamiltonian_path(graph: &Graph, path: Vec<usize>, count: u32) -> Option<(u32, Vec<usize>)> {
-    let mut nodes_to_try = Vec::new();
-
-    // if we've formed a complete circuit, check if it works
-    if path.len() == graph.nodes {
-        if graph.edge(path[path.len()-1], path[0]).is_some() {
-            return Some((count + graph.edge(path[path.len() -1], path[0]).unwrap(), path));
-        } else {
-            return None;
-        }
+/// The shortest open path between the chosen ends, failing early if there is
+/// no path at all.
+fn run_path(tsp: &Tsp, options: &Options) -> Result<(), String> {
+    let graph = &tsp.graph;
+    if graph.nodes() > SHORTEST_PATH_MAX_NODES {
+        return Err(format!("the shortest path search is limited to {} nodes, not {}", SHORTEST_PATH_MAX_NODES, graph.nodes()));
     }
+    let started = Instant::now();
+    if !has_hamiltonian_path(graph, options.start, options.end) {
+        return Err(format!("{}: there is no path through every node between those ends", options.input.display()));
+    }
+    let (length, path) = shortest_path(graph, options.start, options.end).expect("a path exists");
+    let numbered: Vec<String> = path.iter().map(|node| (node + 1).to_string()).collect();
+    println!("{}: path of length {} through {} nodes in {:.3}s", tsp.name, length, graph.nodes(), started.elapsed().as_secs_f64());
+    println!("{}", numbered.join(" "));
+    Ok(())
+}
 
-    let current_head = path[path.len() - 1];
-
-    // find which nodes to try
-    for i in 0..graph.nodes {
-        if !path.contains(&i) && graph.edge(current_head, i).is_some() {
-            nodes_to_try.push(i);
+fn run(options: Options) -> Result<(), String> {
+    let tsp = read_tsp(&options.input)?;
+    for &node in options.start.iter().chain(options.end.iter()) {
+        if node >= tsp.graph.nodes() {
+            return Err(format!("{}: there is no node {}", options.input.display(), node + 1));
         }
     }
-
-    let mut best_seen = None;
-    // for each node, recurse, and try
-    for node in nodes_to_try {
-        let mut vector = path.clone();
-        vector.push(node);
-        let new_count = count + graph.edge(current_head, node).unwrap();
-
-        match find_shortest_hamiltonian_path(graph, vector, new_count) {
-            Some((cost, result_path)) => {
-                match best_seen.take() {
-                    Some((old_cost, old_result_path)) => {
-                        if old_cost > cost {
-                            best_seen = Some((cost, result_path));
-                        } else {
-                            best_seen = Some((old_cost, old_result_path));
-                        }
-                    },
-                    None => {
-                        best_seen = Some((cost, result_path));
-                    }
-                }
-            },
-            None => ()
+    match options.mode {
+        Mode::Tour => (),
+        Mode::Path => return run_path(&tsp, &options),
+        Mode::CountPaths => {
+            let count = hamiltonian_paths(&tsp.graph, options.start, options.end).count();
+            println!("{}: {} paths through {} nodes", tsp.name, count, tsp.graph.nodes());
+            return Ok(());
         }
     }
 
-    return best_seen;
-}
+    let started = Instant::now();
+    let (length, circuit) = options.solver.solve(&tsp.graph)?
+        .ok_or_else(|| format!("{}: found no tour through every node", options.input.display()))?;
+    println!("{}: tour of length {} through {} nodes in {:.3}s", tsp.name, length, tsp.graph.nodes(), started.elapsed().as_secs_f64());
 
+    if let Some(path) = options.tour {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let file = File::create(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        write_tour(BufWriter::new(file), &name, &circuit, length).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
 
 fn main() {
-    let mut graph = Graph::new(5,true);
-
-    graph.set_edge(0, 1, 3);
-    graph.set_edge(0, 2, 1);
-    graph.set_edge(0, 3, 4);
-    graph.set_edge(0, 4, 4);
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
 
+    if let Err(e) = run(options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
 
-    graph.set_edge(1, 2, 2);
-    graph.set_edge(1, 3, 2);
-    graph.set_edge(1, 4, 1);
 
-    graph.set_edge(2, 3, 3);
-    graph.set_edge(2, 4, 5);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    graph.set_edge(3, 4, 4);
+    #[test]
+    fn reads_options() {
+        let args = ["--solver", "held-karp", "burma14.tsp", "--tour", "burma14.tour"];
+        let options = parse_options(args.iter().map(|arg| arg.to_string())).unwrap();
+        assert_eq!(options.solver, Solver::HeldKarp);
+        assert_eq!(options.input, PathBuf::from("burma14.tsp"));
+        assert_eq!(options.tour, Some(PathBuf::from("burma14.tour")));
 
-    println!("{}", graph);
-    let path = vec![0];
+        assert!(parse_options(["--solver", "random", "a.tsp"].iter().map(|arg| arg.to_string())).is_err());
+        assert!(parse_options(["a.tsp", "b.tsp"].iter().map(|arg| arg.to_string())).is_err());
+        assert!(parse_options(Vec::<String>::new().into_iter()).is_err());
 
-    println!("{:?}", find_shortest_hamiltonian_path(&graph, path, 0));
+        let options = parse_options(["--path", "--start", "1", "--end", "14", "a.tsp"].iter().map(|arg| arg.to_string())).unwrap();
+        assert_eq!(options.mode, Mode::Path);
+        assert_eq!((options.start, options.end), (Some(0), Some(13)));
+        assert!(parse_options(["--path", "--start", "0", "a.tsp"].iter().map(|arg| arg.to_string())).is_err());
+        assert!(parse_options(["--start", "1", "a.tsp"].iter().map(|arg| arg.to_string())).is_err());
+    }
 }
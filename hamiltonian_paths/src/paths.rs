@@ -0,0 +1,321 @@
+//! Open Hamiltonian paths, which visit every node once without returning to
+//! the start. Each function takes an optional `start` and `end` node that the
+//! path must begin and finish at; `None` leaves that end free.
+
+use graph::{Weight, WeightedGraph};
+
+/// The shortest path search keeps a table of `2^n * n` lengths, about 170MB
+/// at this size with `u64` weights.
+pub const SHORTEST_PATH_MAX_NODES: usize = 20;
+
+fn check_ends<G: WeightedGraph>(graph: &G, start: Option<usize>, end: Option<usize>) {
+    for &node in start.iter().chain(end.iter()) {
+        assert!(node < graph.nodes(), "node {} is not in a graph of {} nodes", node, graph.nodes());
+    }
+}
+
+/// Whether `node` could end a path of `length` nodes.
+fn may_end<G: WeightedGraph>(graph: &G, end: Option<usize>, node: usize, length: usize) -> bool {
+    end.is_none_or(|end| (node == end) == (length == graph.nodes()))
+}
+
+/// The length of the shortest Hamiltonian path and the path itself, by
+/// dynamic programming over subsets in `O(2^n * n^2)` time, or `None` if
+/// there is no such path or its length overflows the weight type. Panics on
+/// graphs of more than `SHORTEST_PATH_MAX_NODES` nodes.
+pub fn shortest_path<G: WeightedGraph>(graph: &G, start: Option<usize>, end: Option<usize>) -> Option<(G::Weight, Vec<usize>)> {
+    let nodes = graph.nodes();
+    assert!(nodes <= SHORTEST_PATH_MAX_NODES, "the shortest path search is limited to {} nodes, not {}", SHORTEST_PATH_MAX_NODES, nodes);
+    check_ends(graph, start, end);
+    if nodes == 0 {
+        return None;
+    }
+    let unreachable = G::Weight::max_value();
+
+    // shortest[subset * nodes + last]: the shortest path from a start through
+    // exactly the nodes in `subset`, ending at `last`
+    let subsets = 1usize << nodes;
+    let mut shortest = vec![unreachable; subsets * nodes];
+    for first in 0..nodes {
+        if start.is_none_or(|start| start == first) && may_end(graph, end, first, 1) {
+            shortest[(1 << first) * nodes + first] = G::Weight::zero();
+        }
+    }
+
+    for subset in 1..subsets {
+        let visited = subset.count_ones() as usize;
+        for last in 0..nodes {
+            let length = shortest[subset * nodes + last];
+            if length == unreachable {
+                continue;
+            }
+            for next in 0..nodes {
+                if subset & (1 << next) != 0 || !may_end(graph, end, next, visited + 1) {
+                    continue;
+                }
+                if let Some(weight) = graph.edge(last, next) {
+                    let entry = &mut shortest[(subset | 1 << next) * nodes + next];
+                    *entry = (*entry).min(length.saturating_add(weight));
+                }
+            }
+        }
+    }
+
+    let everything = subsets - 1;
+    let (length, mut last) = (0..nodes)
+        .map(|last| (shortest[everything * nodes + last], last))
+        .filter(|&(length, _)| length != unreachable)
+        .min()?;
+
+    // walk back through the table to recover the path
+    let mut path = vec![];
+    let mut subset = everything;
+    loop {
+        path.push(last);
+        let rest = subset & !(1 << last);
+        if rest == 0 {
+            break;
+        }
+        let here = shortest[subset * nodes + last];
+        last = (0..nodes)
+            .find(|&previous| {
+                let before = shortest[rest * nodes + previous];
+                before != unreachable && graph.edge(previous, last).map(|weight| before.saturating_add(weight)) == Some(here)
+            })
+            .expect("every reachable entry has a predecessor");
+        subset = rest;
+    }
+    path.reverse();
+
+    Some((length, path))
+}
+
+struct Existence<'a, G: 'a> {
+    graph: &'a G,
+    end: Option<usize>,
+    path: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl<'a, G: WeightedGraph> Existence<'a, G> {
+    /// Whether every unvisited node can still be reached from the head of the
+    /// path through unvisited nodes; if not, no extension can visit them all.
+    fn can_reach_the_rest(&self) -> bool {
+        let head = self.path[self.path.len() - 1];
+        let mut reached = self.visited.clone();
+        let mut stack = vec![head];
+        let mut count = self.path.len();
+        while let Some(node) = stack.pop() {
+            for next in self.graph.adjacent_nodes(node) {
+                if !reached[next] {
+                    reached[next] = true;
+                    count += 1;
+                    stack.push(next);
+                }
+            }
+        }
+        count == self.graph.nodes()
+    }
+
+    /// Whether the path can be extended to a Hamiltonian path, stopping at
+    /// the first one found.
+    fn search(&mut self) -> bool {
+        if self.path.len() == self.graph.nodes() {
+            return true;
+        }
+        if !self.can_reach_the_rest() {
+            return false;
+        }
+        let head = self.path[self.path.len() - 1];
+        for next in self.graph.adjacent_nodes(head) {
+            if self.visited[next] || !may_end(self.graph, self.end, next, self.path.len() + 1) {
+                continue;
+            }
+            self.visited[next] = true;
+            self.path.push(next);
+            if self.search() {
+                return true;
+            }
+            self.path.pop();
+            self.visited[next] = false;
+        }
+        false
+    }
+}
+
+/// Whether there is any Hamiltonian path, by a depth first search that stops
+/// at the first path it finds and abandons partial paths that can no longer
+/// reach every unvisited node.
+pub fn has_hamiltonian_path<G: WeightedGraph>(graph: &G, start: Option<usize>, end: Option<usize>) -> bool {
+    check_ends(graph, start, end);
+    let firsts: Vec<usize> = match start {
+        Some(start) => vec![start],
+        None => (0..graph.nodes()).collect(),
+    };
+
+    firsts.into_iter().filter(|&first| may_end(graph, end, first, 1)).any(|first| {
+        let mut visited = vec![false; graph.nodes()];
+        visited[first] = true;
+        Existence { graph, end, path: vec![first], visited }.search()
+    })
+}
+
+/// Every Hamiltonian path, in lexicographic order. On a symmetric graph each
+/// path is found in both directions unless the ends are fixed.
+pub struct HamiltonianPaths<'a, G: 'a> {
+    graph: &'a G,
+    end: Option<usize>,
+    /// The starts still to be tried, last first.
+    firsts: Vec<usize>,
+    path: Vec<usize>,
+    visited: Vec<bool>,
+    /// For each node on the path, the lowest node it might go to next.
+    next_to_try: Vec<usize>,
+}
+
+impl<'a, G: WeightedGraph> HamiltonianPaths<'a, G> {
+    /// Adds `node` to the path, returning a copy if that completes it.
+    fn push(&mut self, node: usize) -> Option<Vec<usize>> {
+        self.visited[node] = true;
+        self.path.push(node);
+        self.next_to_try.push(0);
+        if self.path.len() == self.graph.nodes() {
+            let complete = self.path.clone();
+            self.pop();
+            return Some(complete);
+        }
+        None
+    }
+
+    fn pop(&mut self) {
+        if let Some(node) = self.path.pop() {
+            self.visited[node] = false;
+            self.next_to_try.pop();
+        }
+    }
+}
+
+impl<'a, G: WeightedGraph> Iterator for HamiltonianPaths<'a, G> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        loop {
+            if self.path.is_empty() {
+                let first = self.firsts.pop()?;
+                if !may_end(self.graph, self.end, first, 1) {
+                    continue;
+                }
+                if let Some(complete) = self.push(first) {
+                    return Some(complete);
+                }
+                continue;
+            }
+
+            let head = self.path[self.path.len() - 1];
+            let length = self.path.len() + 1;
+            let from = self.next_to_try[self.next_to_try.len() - 1];
+            let next = (from..self.graph.nodes()).find(|&node| {
+                !self.visited[node] && self.graph.edge(head, node).is_some() && may_end(self.graph, self.end, node, length)
+            });
+            match next {
+                Some(node) => {
+                    let last = self.next_to_try.len() - 1;
+                    self.next_to_try[last] = node + 1;
+                    if let Some(complete) = self.push(node) {
+                        return Some(complete);
+                    }
+                }
+                None => self.pop(),
+            }
+        }
+    }
+}
+
+/// An iterator over every Hamiltonian path between the given ends.
+pub fn hamiltonian_paths<G: WeightedGraph>(graph: &G, start: Option<usize>, end: Option<usize>) -> HamiltonianPaths<'_, G> {
+    check_ends(graph, start, end);
+    let mut firsts: Vec<usize> = match start {
+        Some(start) => vec![start],
+        None => (0..graph.nodes()).collect(),
+    };
+    firsts.reverse();
+    HamiltonianPaths {
+        graph,
+        end,
+        firsts,
+        path: vec![],
+        visited: vec![false; graph.nodes()],
+        next_to_try: vec![],
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exact::held_karp;
+    use graph::Graph;
+    use testing::{random_graph, Lcg};
+
+    /// 0 - 1 - 2 - 3 in a line, with no way back round.
+    fn line() -> Graph {
+        let mut graph = Graph::new(4, true);
+        graph.set_edge(0, 1, 1);
+        graph.set_edge(1, 2, 2);
+        graph.set_edge(2, 3, 3);
+        graph
+    }
+
+    #[test]
+    fn finds_paths_where_there_is_no_circuit() {
+        let graph = line();
+        assert_eq!(held_karp(&graph), None);
+        assert_eq!(shortest_path(&graph, None, None).map(|(length, _)| length), Some(6));
+        assert_eq!(shortest_path(&graph, Some(3), None), Some((6, vec![3, 2, 1, 0])));
+        assert_eq!(shortest_path(&graph, Some(1), None), None);
+        assert!(has_hamiltonian_path(&graph, None, Some(0)));
+        assert!(!has_hamiltonian_path(&graph, Some(0), Some(2)));
+        assert_eq!(hamiltonian_paths(&graph, None, None).collect::<Vec<_>>(), vec![vec![0, 1, 2, 3], vec![3, 2, 1, 0]]);
+    }
+
+    #[test]
+    fn counts_the_paths_of_a_complete_graph() {
+        let mut graph = Graph::new(5, false);
+        for from in 0..5 {
+            for to in 0..5 {
+                graph.set_edge(from, to, 1);
+            }
+        }
+        assert_eq!(hamiltonian_paths(&graph, None, None).count(), 120);
+        assert_eq!(hamiltonian_paths(&graph, Some(2), None).count(), 24);
+        assert_eq!(hamiltonian_paths(&graph, Some(2), Some(4)).count(), 6);
+        assert_eq!(hamiltonian_paths(&graph, Some(2), Some(2)).count(), 0);
+        assert_eq!(hamiltonian_paths(&Graph::new(1, true), None, None).collect::<Vec<_>>(), vec![vec![0]]);
+    }
+
+    #[test]
+    fn agree_with_enumeration() {
+        let mut rng = Lcg(41);
+        for round in 0..80 {
+            let nodes = 1 + round % 7;
+            let graph = random_graph(&mut rng, nodes, round % 2 == 0, 2 + round as u32 % 3);
+            let start = if round % 3 == 0 { Some(rng.next(nodes as u32) as usize) } else { None };
+            let end = if round % 4 == 0 { Some(rng.next(nodes as u32) as usize) } else { None };
+
+            let paths: Vec<Vec<usize>> = hamiltonian_paths(&graph, start, end).collect();
+            for path in &paths {
+                assert!(graph.path_length(path).is_some(), "{:?} on {}", path, graph);
+                assert!(start.is_none_or(|start| path[0] == start) && end.is_none_or(|end| path[nodes - 1] == end));
+            }
+            let shortest = paths.iter().map(|path| graph.path_length(path).unwrap()).min();
+
+            assert_eq!(has_hamiltonian_path(&graph, start, end), !paths.is_empty(), "{:?} to {:?} on {}", start, end, graph);
+            let found = shortest_path(&graph, start, end);
+            assert_eq!(found.as_ref().map(|&(length, _)| length), shortest, "{:?} to {:?} on {}", start, end, graph);
+            if let Some((length, path)) = found {
+                assert_eq!(graph.path_length(&path), Some(length));
+                assert!(paths.contains(&path));
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Random graphs for tests.
+
+use graph::Graph;
+
+/// A small linear congruential generator, enough to vary test graphs.
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    pub fn next(&mut self, below: u32) -> u32 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % below as u64) as u32
+    }
+}
+
+/// A random graph where about one edge in `missing` is absent.
+pub fn random_graph(rng: &mut Lcg, nodes: usize, symmetric: bool, missing: u32) -> Graph {
+    let mut graph = Graph::new(nodes, symmetric);
+    for from in 0..nodes {
+        for to in 0..nodes {
+            if (!symmetric || from < to) && rng.next(missing) != 0 {
+                graph.set_edge(from, to, 1 + rng.next(50));
+            }
+        }
+    }
+    graph
+}
+
+/// A complete symmetric graph obeying the triangle inequality: one more than
+/// the Manhattan distance between random points.
+pub fn random_metric_graph(rng: &mut Lcg, nodes: usize) -> Graph {
+    let points: Vec<(i64, i64)> = (0..nodes).map(|_| (rng.next(100) as i64, rng.next(100) as i64)).collect();
+    let mut graph = Graph::new(nodes, true);
+    for from in 0..nodes {
+        for to in from + 1..nodes {
+            let distance = (points[from].0 - points[to].0).abs() + (points[from].1 - points[to].1).abs();
+            graph.set_edge(from, to, 1 + distance as u32);
+        }
+    }
+    graph
+}
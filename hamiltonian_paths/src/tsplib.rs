@@ -0,0 +1,308 @@
+//! Reading TSPLIB `.tsp` problems into a `Graph` and writing `.tour` files.
+//! Problems may be `TSP` or `ATSP` with `EXPLICIT` edge weights in any of the
+//! standard matrix layouts, or `TSP` with `EUC_2D` or `GEO` coordinates.
+//!
+//! TSPLIB nodes are numbered from 1 and `Graph` nodes from 0.
+
+use graph::Graph;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+/// A problem read from a `.tsp` file.
+#[derive(Debug, Clone)]
+pub struct Tsp {
+    pub name: String,
+    pub graph: Graph,
+}
+
+/// The lines of the input, numbered from 1 for errors.
+struct Lines<R> {
+    lines: io::Lines<R>,
+    line_number: usize,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn next(&mut self) -> Result<Option<String>, String> {
+        match self.lines.next() {
+            Some(line) => {
+                self.line_number += 1;
+                line.map(Some).map_err(|e| format!("line {}: {}", self.line_number, e))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The next line, failing if the input has ended in the middle of `section`.
+    fn expect(&mut self, section: &str) -> Result<String, String> {
+        self.next()?.ok_or_else(|| format!("line {}: unexpected end of input in {}", self.line_number, section))
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line_number, message)
+    }
+}
+
+/// How the weights in an `EDGE_WEIGHT_SECTION` are laid out: the pairs of
+/// nodes they belong to, in order, including any diagonal entries.
+fn explicit_layout(format: &str, nodes: usize, symmetric: bool) -> Result<Vec<(usize, usize)>, String> {
+    let pairs = |keep: &dyn Fn(usize, usize) -> bool| -> Vec<(usize, usize)> {
+        (0..nodes).flat_map(|row| (0..nodes).map(move |column| (row, column))).filter(|&(row, column)| keep(row, column)).collect()
+    };
+    // on a symmetric matrix a row of the upper triangle is a column of the lower
+    let layout = match format {
+        "FULL_MATRIX" => return Ok(pairs(&|_, _| true)),
+        "UPPER_ROW" | "LOWER_COL" => pairs(&|row, column| row < column),
+        "UPPER_DIAG_ROW" | "LOWER_DIAG_COL" => pairs(&|row, column| row <= column),
+        "LOWER_ROW" | "UPPER_COL" => pairs(&|row, column| row > column),
+        "LOWER_DIAG_ROW" | "UPPER_DIAG_COL" => pairs(&|row, column| row >= column),
+        _ => return Err(format!("unsupported EDGE_WEIGHT_FORMAT {}", format)),
+    };
+    if !symmetric {
+        return Err(format!("EDGE_WEIGHT_FORMAT {} only describes a symmetric TSP", format));
+    }
+    Ok(layout)
+}
+
+fn euclidean(a: (f64, f64), b: (f64, f64)) -> u32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt().round() as u32
+}
+
+/// TSPLIB's degrees.minutes coordinates, in radians. The whole degrees are
+/// truncated, as in the programs that computed the published optima.
+fn geo_radians(coordinate: f64) -> f64 {
+    let degrees = coordinate.trunc();
+    let minutes = coordinate - degrees;
+    // TSPLIB's own rounding of pi, which the published optima depend on
+    #[allow(clippy::approx_constant)]
+    const PI: f64 = 3.141592;
+    PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+/// The distance in kilometres between two (latitude, longitude) points on
+/// TSPLIB's idealised sphere of the Earth.
+fn geographical(a: (f64, f64), b: (f64, f64)) -> u32 {
+    const RADIUS: f64 = 6378.388;
+    let (latitude_a, longitude_a) = (geo_radians(a.0), geo_radians(a.1));
+    let (latitude_b, longitude_b) = (geo_radians(b.0), geo_radians(b.1));
+    let q1 = (longitude_a - longitude_b).cos();
+    let q2 = (latitude_a - latitude_b).cos();
+    let q3 = (latitude_a + latitude_b).cos();
+    let angle = (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).clamp(-1.0, 1.0).acos();
+    (RADIUS * angle + 1.0) as u32
+}
+
+/// Parses a TSPLIB `.tsp` problem.
+pub fn parse<R: BufRead>(reader: R) -> Result<Tsp, String> {
+    let mut lines = Lines { lines: reader.lines(), line_number: 0 };
+    let mut name = String::new();
+    let mut symmetric = None;
+    let mut dimension = None;
+    let mut weight_type = None;
+    let mut weight_format = None;
+    let mut coordinates: Option<Vec<(f64, f64)>> = None;
+    let mut weights: Option<Vec<u32>> = None;
+
+    while let Some(line) = lines.next()? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = match line.find(':') {
+            Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+            None => (line, ""),
+        };
+
+        match key {
+            "EOF" => break,
+            "NAME" => name = value.to_string(),
+            "TYPE" => symmetric = Some(match value {
+                "TSP" => true,
+                "ATSP" => false,
+                _ => return Err(lines.error(&format!("unsupported TYPE {}", value))),
+            }),
+            "DIMENSION" => dimension = Some(value.parse::<usize>().map_err(|_| lines.error(&format!("{} is not a valid DIMENSION", value)))?),
+            "EDGE_WEIGHT_TYPE" => match value {
+                "EXPLICIT" | "EUC_2D" | "GEO" => weight_type = Some(value.to_string()),
+                _ => return Err(lines.error(&format!("unsupported EDGE_WEIGHT_TYPE {}", value))),
+            },
+            "EDGE_WEIGHT_FORMAT" => weight_format = Some(value.to_string()),
+            "COMMENT" | "NODE_COORD_TYPE" | "DISPLAY_DATA_TYPE" => (),
+            "NODE_COORD_SECTION" | "DISPLAY_DATA_SECTION" => {
+                let nodes = dimension.ok_or_else(|| lines.error(&format!("{} before DIMENSION", key)))?;
+                let mut read = vec![None; nodes];
+                for _ in 0..nodes {
+                    let line = lines.expect(key)?;
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    if fields.len() != 3 {
+                        return Err(lines.error("expected a node number and two coordinates"));
+                    }
+                    let node = fields[0].parse::<usize>().ok()
+                        .filter(|&node| node >= 1 && node <= nodes)
+                        .ok_or_else(|| lines.error(&format!("{} is not a node number from 1 to {}", fields[0], nodes)))?;
+                    let coordinate = |field: &str| field.parse::<f64>().map_err(|_| lines.error(&format!("{} is not a valid coordinate", field)));
+                    read[node - 1] = Some((coordinate(fields[1])?, coordinate(fields[2])?));
+                }
+                let read: Option<Vec<(f64, f64)>> = read.into_iter().collect();
+                let read = read.ok_or_else(|| lines.error("a node is listed twice"))?;
+                // display coordinates are only for drawing the problem
+                if key == "NODE_COORD_SECTION" {
+                    coordinates = Some(read);
+                }
+            }
+            "EDGE_WEIGHT_SECTION" => {
+                let nodes = dimension.ok_or_else(|| lines.error("EDGE_WEIGHT_SECTION before DIMENSION"))?;
+                let format = weight_format.as_ref().ok_or_else(|| lines.error("EDGE_WEIGHT_SECTION without an EDGE_WEIGHT_FORMAT"))?;
+                let count = explicit_layout(format, nodes, symmetric.unwrap_or(true)).map_err(|e| lines.error(&e))?.len();
+                let mut read = Vec::with_capacity(count);
+                while read.len() < count {
+                    let line = lines.expect(key)?;
+                    for field in line.split_whitespace() {
+                        // some files write integral weights as floats
+                        let weight = field.parse::<f64>().ok()
+                            .filter(|&weight| weight >= 0.0 && weight <= u32::MAX as f64)
+                            .ok_or_else(|| lines.error(&format!("{} is not a valid edge weight", field)))?;
+                        read.push(weight.round() as u32);
+                    }
+                }
+                if read.len() > count {
+                    return Err(lines.error(&format!("expected {} edge weights, found more", count)));
+                }
+                weights = Some(read);
+            }
+            _ if key.ends_with("_SECTION") => return Err(lines.error(&format!("unsupported section {}", key))),
+            _ => return Err(lines.error(&format!("unknown keyword {}", key))),
+        }
+    }
+
+    let end = |message: &str| format!("line {}: {}", lines.line_number, message);
+    let nodes = dimension.ok_or_else(|| end("no DIMENSION given"))?;
+    let symmetric = symmetric.ok_or_else(|| end("no TYPE given"))?;
+    let weight_type = weight_type.ok_or_else(|| end("no EDGE_WEIGHT_TYPE given"))?;
+    let mut graph = Graph::new(nodes, symmetric);
+
+    if weight_type == "EXPLICIT" {
+        let weights = weights.ok_or_else(|| end("no EDGE_WEIGHT_SECTION given"))?;
+        let layout = explicit_layout(weight_format.as_ref().unwrap(), nodes, symmetric).map_err(|e| end(&e))?;
+        for (&(from, to), &weight) in layout.iter().zip(&weights) {
+            graph.set_edge(from, to, weight);
+        }
+    } else {
+        if !symmetric {
+            return Err(end(&format!("EDGE_WEIGHT_TYPE {} only describes a symmetric TSP", weight_type)));
+        }
+        let coordinates = coordinates.ok_or_else(|| end("no NODE_COORD_SECTION given"))?;
+        let distance = if weight_type == "GEO" { geographical } else { euclidean };
+        for from in 0..nodes {
+            for to in from + 1..nodes {
+                graph.set_edge(from, to, distance(coordinates[from], coordinates[to]));
+            }
+        }
+    }
+
+    Ok(Tsp { name, graph })
+}
+
+/// Reads the TSPLIB problem at `path`, named after the file if it has no
+/// `NAME`; errors name the file and line.
+pub fn read_tsp(path: &Path) -> Result<Tsp, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut tsp = parse(BufReader::new(file)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if tsp.name.is_empty() {
+        tsp.name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    }
+    Ok(tsp)
+}
+
+/// Writes `circuit` as a TSPLIB `.tour` file called `name`, noting its length.
+pub fn write_tour<W: Write>(mut writer: W, name: &str, circuit: &[usize], length: u32) -> io::Result<()> {
+    writeln!(writer, "NAME : {}", name)?;
+    writeln!(writer, "COMMENT : Length {}", length)?;
+    writeln!(writer, "TYPE : TOUR")?;
+    writeln!(writer, "DIMENSION : {}", circuit.len())?;
+    writeln!(writer, "TOUR_SECTION")?;
+    for &node in circuit {
+        writeln!(writer, "{}", node + 1)?;
+    }
+    writeln!(writer, "-1")?;
+    writeln!(writer, "EOF")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exact::held_karp;
+    use graph::WeightedGraph;
+
+    #[test]
+    fn reads_explicit_matrices() {
+        let upper = "NAME: square\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\n\
+                     EDGE_WEIGHT_SECTION\n1 2 1\n1 2\n1\nEOF\n";
+        let lower = "NAME: square\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: LOWER_DIAG_ROW\n\
+                     EDGE_WEIGHT_SECTION\n0 1 0 2 1 0\n1 2 1 0\nEOF\n";
+        for input in [upper, lower] {
+            let tsp = parse(input.as_bytes()).unwrap();
+            assert_eq!(tsp.name, "square");
+            assert!(tsp.graph.is_symmetric());
+            assert_eq!(tsp.graph.circuit_length(&[0, 1, 2, 3]), Some(4));
+            assert_eq!(tsp.graph.edge(2, 0), Some(2));
+        }
+
+        let full = "TYPE: ATSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n\
+                    EDGE_WEIGHT_SECTION\n9999 1 5\n5 9999 1\n1 5 9999\n";
+        let graph = parse(full.as_bytes()).unwrap().graph;
+        assert_eq!(graph.circuit_length(&[0, 1, 2]), Some(3));
+        assert_eq!(graph.circuit_length(&[0, 2, 1]), Some(15));
+    }
+
+    #[test]
+    fn reads_coordinates() {
+        let input = "NAME : box\nTYPE : TSP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\nNODE_COORD_SECTION\n\
+                     1 0 0\n3 3 4\n2 3 0\n4 0 4\nEOF\n";
+        let graph = parse(input.as_bytes()).unwrap().graph;
+        assert_eq!(graph.edge(0, 2), Some(5));
+        assert_eq!(graph.circuit_length(&[0, 1, 2, 3]), Some(14));
+    }
+
+    #[test]
+    fn keeps_zero_distances() {
+        let input = "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 0 0\n3 3 4\nEOF\n";
+        let graph = parse(input.as_bytes()).unwrap().graph;
+        assert_eq!(graph.edge(0, 1), Some(0));
+        assert_eq!(held_karp(&graph).map(|(length, _)| length), Some(10));
+
+        let explicit = "TYPE: ATSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n\
+                        EDGE_WEIGHT_SECTION\n0 0\n3 0\nEOF\n";
+        let graph = parse(explicit.as_bytes()).unwrap().graph;
+        assert_eq!(graph.circuit_length(&[0, 1]), Some(3));
+    }
+
+    #[test]
+    fn solves_burma14_to_its_published_optimum() {
+        let input = "NAME: burma14\nTYPE: TSP\nDIMENSION: 14\nEDGE_WEIGHT_TYPE: GEO\nEDGE_WEIGHT_FORMAT: FUNCTION\n\
+                     DISPLAY_DATA_TYPE: COORD_DISPLAY\nNODE_COORD_SECTION\n\
+                     1 16.47 96.10\n2 16.47 94.44\n3 20.09 92.54\n4 22.39 93.37\n5 25.23 97.24\n6 22.00 96.05\n7 20.47 97.02\n\
+                     8 17.20 96.29\n9 16.30 97.38\n10 14.05 98.12\n11 16.53 97.38\n12 21.52 95.59\n13 19.41 97.13\n14 20.09 94.55\nEOF\n";
+        let tsp = parse(input.as_bytes()).unwrap();
+        assert_eq!(held_karp(&tsp.graph).map(|(length, _)| length), Some(3323));
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        let input = "TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n2 zero 0\n";
+        assert_eq!(parse(input.as_bytes()).unwrap_err(), "line 6: zero is not a valid coordinate");
+        let input = "TYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: ATT\n";
+        assert_eq!(parse(input.as_bytes()).unwrap_err(), "line 3: unsupported EDGE_WEIGHT_TYPE ATT");
+        let input = "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n";
+        assert_eq!(parse(input.as_bytes()).unwrap_err(), "line 6: unexpected end of input in EDGE_WEIGHT_SECTION");
+    }
+
+    #[test]
+    fn writes_tours_numbered_from_one() {
+        let mut written = vec![];
+        write_tour(&mut written, "box.tour", &[0, 2, 1], 12).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(),
+                   "NAME : box.tour\nCOMMENT : Length 12\nTYPE : TOUR\nDIMENSION : 3\nTOUR_SECTION\n1\n3\n2\n-1\nEOF\n");
+    }
+}
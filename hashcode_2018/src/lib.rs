@@ -1,3 +1 @@
 pub mod problem;
-pub mod matrix;
-
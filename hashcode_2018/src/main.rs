@@ -4,32 +4,238 @@ extern crate chan_signal;
 
 mod parser;
 mod problem;
-mod matrix;
 
-use std::fs::File;
-use std::io::{Write,self};
-use problem::ride::Ride;
-use problem::Problem;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use parser::read_input;
-//    let (problem_name,low_expt_max, high_expt_max) = ("b_should_be_easy", 2100, 27000);
-//   let (problem_name, low_expt_max, high_expt_max) = ("d_metropolis", 19000, 900000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("e_high_bonus", 4000, 900000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("c_no_hurry", 5000, 9000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("b_should_be_easy", 2100, 27000);
+use hashcode_2018::problem::scoring::{score, ScoreReport};
+use hashcode_2018::problem::assignment::AssignmentStrategy;
+use hashcode_2018::problem::improve::{anneal, Annealing};
+use hashcode_2018::problem::listen_for_interrupts;
 
+const USAGE : &str = "usage: hashcode_2018 [options] INPUT...
 
+  INPUT                 a problem file, or a pattern like 'problems/*.in'
+                        (wildcards only in the file name); files are solved
+                        in parallel
+  --strategy NAME       how to build the first assignment: greedy, insertion
+                        or dfs (default insertion)
+  --improve-secs N      seconds to spend improving each assignment, 0 to skip
+                        (default 30)
+  --seed N              random seed for the improvement phase (default 0)
+  --out-dir DIR         where to write INPUT's solution, as NAME.out for
+                        NAME.in (default output)
+  --output PATH         write the solution to PATH instead; one INPUT only
+
+Ctrl-C stops every search early and writes the best solutions so far.";
+
+struct Options {
+    inputs : Vec<String>,
+    strategy : AssignmentStrategy,
+    improve : Duration,
+    seed : usize,
+    out_dir : PathBuf,
+    output : Option<PathBuf>
+}
+
+fn value(arg : &str, value : Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} needs a value", arg))
+}
+
+fn number<T : std::str::FromStr>(arg : &str, value : Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+    value.parse().map_err(|_| format!("{} is not a valid value for {}", value, arg))
+}
+
+fn parse_options<I : Iterator<Item=String>>(mut args : I) -> Result<Options, String> {
+    let mut options = Options {
+        inputs : vec![],
+        strategy : AssignmentStrategy::Insertion,
+        improve : Duration::from_secs(30),
+        seed : 0,
+        out_dir : PathBuf::from("output"),
+        output : None
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--strategy" => options.strategy = value(&arg, args.next())?.parse()?,
+            "--improve-secs" => options.improve = Duration::from_secs(number(&arg, args.next())?),
+            "--seed" => options.seed = number(&arg, args.next())?,
+            "--out-dir" => options.out_dir = PathBuf::from(value(&arg, args.next())?),
+            "--output" => options.output = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--help" | "-h" => return Err(String::new()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => options.inputs.push(arg),
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("no input files given".to_string());
+    }
+    Ok(options)
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters
+/// and `?` any single character.
+fn wildcard_match(pattern : &[char], name : &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..name.len() + 1).any(|skip| wildcard_match(&pattern[1..], &name[skip..])),
+        Some(&c) => !name.is_empty() && (c == '?' || c == name[0]) && wildcard_match(&pattern[1..], &name[1..]),
+    }
+}
+
+/// The files `input` names: itself, or if its file name has wildcards, every
+/// matching file in its directory, in order.
+fn expand(input : &str) -> Result<Vec<PathBuf>, String> {
+    let path = Path::new(input);
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    if !file_name.contains(['*', '?']) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let pattern : Vec<char> = file_name.chars().collect();
+    let mut matches = vec![];
+    for entry in fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))? {
+        let entry = entry.map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let name : Vec<char> = entry.file_name().to_string_lossy().chars().collect();
+        if wildcard_match(&pattern, &name) {
+            matches.push(entry.path());
+        }
+    }
+    if matches.is_empty() {
+        return Err(format!("{} matches no files", input));
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Reads, solves and improves one problem, writing the solution to `output`.
+fn solve_file(input : &Path, output : &Path, options : &Options, should_break_early : &Arc<Mutex<bool>>) -> Result<ScoreReport, String> {
+    let problem = read_input(input)?;
+    let mut solution = problem.solve(options.strategy, should_break_early);
+    if options.improve > Duration::from_secs(0) {
+        let annealing = Annealing::new(options.improve, options.seed);
+        let (improved, stats) = anneal(&problem, &solution, &annealing, should_break_early).map_err(|violations| {
+            let violations : Vec<String> = violations.iter().map(|violation| violation.to_string()).collect();
+            format!("{}: can't improve an infeasible solution: {}", input.display(), violations.join("; "))
+        })?;
+        println!("{}: annealing tried {} moves and accepted {}", input.display(), stats.tried, stats.accepted);
+        solution = improved;
+    }
+
+    if let Some(dir) = output.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+    }
+    let mut file = File::create(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    write!(file, "{}", solution).map_err(|e| format!("{}: {}", output.display(), e))?;
+    Ok(score(&problem, &solution))
+}
+
+fn run(options : Options) -> Result<(), String> {
+    let mut inputs = vec![];
+    for input in &options.inputs {
+        inputs.extend(expand(input)?);
+    }
+    if options.output.is_some() && inputs.len() > 1 {
+        return Err("--output can only be used with a single input file".to_string());
+    }
+
+    let jobs : Vec<(PathBuf, PathBuf)> = inputs.into_iter().map(|input| {
+        let output = options.output.clone().unwrap_or_else(|| {
+            let stem = input.file_stem().map(|stem| stem.to_os_string()).unwrap_or_default();
+            options.out_dir.join(stem).with_extension("out")
+        });
+        (input, output)
+    }).collect();
+
+    let options = Arc::new(options);
+    let should_break_early = listen_for_interrupts();
+    let handles : Vec<_> = jobs.iter().cloned().map(|(input, output)| {
+        let options = options.clone();
+        let should_break_early = should_break_early.clone();
+        thread::spawn(move || solve_file(&input, &output, &options, &should_break_early))
+    }).collect();
+
+    let mut total = 0;
+    let mut failed = 0;
+    for ((input, output), handle) in jobs.iter().zip(handles) {
+        let result = handle.join().unwrap_or_else(|_| Err("the solver panicked".to_string()));
+        match result {
+            Ok(report) => {
+                println!("{} -> {}: {}", input.display(), output.display(), report);
+                total += report.score;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("total score {} over {} files", total, jobs.len() - failed);
+    if failed > 0 {
+        return Err(format!("{} of {} files failed", failed, jobs.len()));
+    }
+    Ok(())
+}
 
 fn main() {
-//    let (problem_name,low_expt_max, high_expt_max) = ("b_should_be_easy", 2100, 27000);
-   let (problem_name, low_expt_max, high_expt_max) = ("d_metropolis", 1900, 9000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("e_high_bonus", 4000, 900000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("c_no_hurry", 5000, 9000);
-//    let (problem_name, low_expt_max, high_expt_max) = ("b_should_be_easy", 2100, 27000);
-
-    let problem = read_input(problem_name);
-    println!("problem is {:?}", problem);
-    let solution = problem.solve(low_expt_max, high_expt_max);
-    let mut output = File::create(format!("output/{}.out", problem_name)).expect("Could not open output file");
-    write!(output, "{}", solution);
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern : &str, name : &str) -> bool {
+        wildcard_match(&pattern.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches("*.in", "a_example.in"));
+        assert!(matches("?_*.in", "b_should_be_easy.in"));
+        assert!(!matches("*.in", "a_example.out"));
+        assert!(!matches("?_*", "example"));
+    }
+
+    #[test]
+    fn reads_options() {
+        let args = ["--strategy", "greedy", "--improve-secs", "0", "problems/*.in"];
+        let options = parse_options(args.iter().map(|arg| arg.to_string())).unwrap();
+        assert_eq!(options.strategy, AssignmentStrategy::Greedy);
+        assert_eq!(options.improve, Duration::from_secs(0));
+        assert_eq!(options.inputs, vec!["problems/*.in"]);
 
+        assert!(parse_options(vec!["--strategy".to_string(), "random".to_string()].into_iter()).is_err());
+        assert!(parse_options(Vec::<String>::new().into_iter()).is_err());
+    }
 }
@@ -1,76 +1,90 @@
 use ::std::vec::Vec;
 use ::std::fs::File;
-use ::std::io::{Write, Read, Error, ErrorKind, BufReader, BufRead};
+use ::std::io::{BufReader, BufRead};
+use ::std::path::Path;
+use ::std::str::FromStr;
 
 pub use hashcode_2018::problem::ride::Ride;
 pub use hashcode_2018::problem::Problem;
 
-pub fn read_input(name : &str) -> Problem {
-    let mut input = File::open(format!("problems/{}.in", name)).expect("Could not open input file");
-    let mut reader = BufReader::new(input);
-
-    
-        
-     let rows : i32;
-     let columns : i32;
-     let vehicles : i32;
-     let ride_count : i32;
-     let per_ride_bonus : i32;
-     let total_time : i32;
-
+/// Reads the next whitespace separated field of `line`, named `name` in errors.
+fn field<'a, T : FromStr, I : Iterator<Item=&'a str>>(fields : &mut I, name : &str, line_number : usize) -> Result<T, String> {
+    let value = fields.next().ok_or_else(|| format!("line {}: no {} provided", line_number, name))?;
+    value.parse().map_err(|_| format!("line {}: {} is not a valid {}", line_number, value, name))
+}
 
+/// Reads the next line, failing if the input has ended.
+fn next_line<R : BufRead>(reader : &mut R, line_number : usize) -> Result<String, String> {
+    let mut line = String::new();
+    let read = reader.read_line(&mut line).map_err(|e| format!("line {}: {}", line_number, e))?;
+    if read == 0 {
+        return Err(format!("line {}: unexpected end of input", line_number));
+    }
+    Ok(line)
+}
 
+/// Parses a problem in the contest input format: a header line giving rows,
+/// columns, vehicles, rides, per ride bonus and steps, then one line per ride.
+pub fn parse<R : BufRead>(mut reader : R) -> Result<Problem, String> {
+    let header = next_line(&mut reader, 1)?;
+    let mut fields = header.split_whitespace();
+    let rows : i32 = field(&mut fields, "number of rows", 1)?;
+    let columns : i32 = field(&mut fields, "number of columns", 1)?;
+    let vehicles : i32 = field(&mut fields, "number of vehicles", 1)?;
+    let ride_count : i32 = field(&mut fields, "number of rides", 1)?;
+    let per_ride_bonus : i32 = field(&mut fields, "per ride bonus", 1)?;
+    let total_time : i32 = field(&mut fields, "number of steps", 1)?;
+    if fields.next().is_some() {
+        return Err("line 1: expected 6 numbers".to_string());
+    }
+    if vehicles < 0 || ride_count < 0 {
+        return Err("line 1: the numbers of vehicles and rides can't be negative".to_string());
+    }
 
-     let mut current_line : String = String::new();
-     reader.read_line(&mut current_line);
-     {
-         let i_rows;
-         let i_columns;
-         let i_vehicles;
-         let i_ride_count;
-         let i_per_ride_bonus;
-         let i_total_time;
+    let mut rides = Vec::new();
 
-         let mut iter  = current_line.split(char::is_whitespace);
-         i_rows = iter.next().expect("No rows provided");
-         i_columns = iter.next().expect("No columns provided");
-         i_vehicles = iter.next().expect("No vehicles provided");
-         i_ride_count = iter.next().expect("No number of rides provided");
-         i_per_ride_bonus = iter.next().expect("No per ride bonus provided");
-         i_total_time = iter.next().expect("No overall steps in simulation provided");
+    for i in 0..ride_count {
+        let line_number = i as usize + 2;
+        let line = next_line(&mut reader, line_number)?;
+        let mut fields = line.split_whitespace();
+        let start_x = field(&mut fields, "start row", line_number)?;
+        let start_y = field(&mut fields, "start column", line_number)?;
+        let end_x = field(&mut fields, "finish row", line_number)?;
+        let end_y = field(&mut fields, "finish column", line_number)?;
+        let earliest_start = field(&mut fields, "earliest start", line_number)?;
+        let latest_end = field(&mut fields, "latest finish", line_number)?;
+        if fields.next().is_some() {
+            return Err(format!("line {}: expected 6 numbers", line_number));
+        }
 
-         rows = i_rows.parse().expect("rows was not a number");
-         columns = i_columns.parse().expect("No columns provided");
-         vehicles = i_vehicles.parse().expect("No vehicles provided");
-         ride_count = i_ride_count.parse().expect("No ride_count of rides provided");
-         per_ride_bonus = i_per_ride_bonus.parse().expect("No per_ride_bonus ride bonus provided");
-         total_time = i_total_time.parse().expect("No total_time steps in simulation provided");
-     }
+        rides.push(Ride::new(i, start_x, start_y, end_x, end_y, earliest_start, latest_end));
+    }
 
+    Ok(Problem::new(rows, columns, vehicles, ride_count, per_ride_bonus, total_time, rides))
+}
 
-     let mut rides = Vec::new();
+/// Reads the problem in the file at `path`.
+pub fn read_input(path : &Path) -> Result<Problem, String> {
+    let input = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(BufReader::new(input)).map_err(|e| format!("{}: {}", path.display(), e))
+}
 
-     for i in 0..ride_count {
-        current_line = String::new();
-        reader.read_line(&mut current_line);
-        let mut iter = current_line.split(char::is_whitespace);
-        let start_X = iter.next().expect("No start_X provided");
-        let start_Y = iter.next().expect("No start_Y provided");
-        let end_X = iter.next().expect("No end_X provided");
-        let end_Y = iter.next().expect("No end_Y provided");
-        let earliest_start = iter.next().expect("No earliest_start provided");
-        let latest_end = iter.next().expect("No latest_end provided");
 
-        let start_X : i32 = start_X.parse().expect("No start_X provided");
-        let start_Y : i32 = start_Y.parse().expect("No start_Y provided");
-        let end_X : i32 = end_X.parse().expect("No end_X provided");
-        let end_Y : i32 = end_Y.parse().expect("No end_Y provided");
-        let earliest_start : i32 = earliest_start.parse().expect("No earliest_start provided");
-        let latest_end : i32 = latest_end.parse().expect("No latest_end provided");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let ride = Ride::new(i, start_X, start_Y, end_X, end_Y, earliest_start, latest_end);
-        rides.push(ride); 
-     }
+    #[test]
+    fn parses_the_example() {
+        let problem = parse("3 4 2 3 2 10\n0 0 1 3 2 9\n1 2 1 0 0 9\n2 0 2 2 0 9\n".as_bytes()).unwrap();
+        assert_eq!(format!("{:?}", problem).matches("Ride").count(), 3);
+    }
 
-    Problem::new(rows, columns, vehicles, ride_count, per_ride_bonus, total_time, rides)
+    #[test]
+    fn reports_the_line_of_an_error() {
+        assert_eq!(parse("3 4 2 3 2\n".as_bytes()).unwrap_err(), "line 1: no number of steps provided");
+        assert_eq!(parse("3 4 2 2 2 10\n0 0 1 3 2 9\n1 2 x 0 0 9\n".as_bytes()).unwrap_err(),
+                   "line 3: x is not a valid finish row");
+        assert_eq!(parse("3 4 2 3 2 10\n0 0 1 3 2 9\n".as_bytes()).unwrap_err(), "line 3: unexpected end of input");
+    }
 }
@@ -0,0 +1,197 @@
+use super::Problem;
+use super::ride::Ride;
+use super::scoring::{drive, VehicleState};
+use std::str::FromStr;
+
+/// How the initial routes are built. Every strategy builds at most one route
+/// per vehicle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssignmentStrategy {
+    /// Rides in order of earliest start, each to the vehicle that can reach it soonest.
+    Greedy,
+    /// Rides in order of earliest start, each inserted wherever in any route it
+    /// adds the most points.
+    Insertion,
+    /// The original chain builder: repeated searches of the ride compatibility
+    /// graph, one chain per vehicle.
+    DfsChains
+}
+
+impl FromStr for AssignmentStrategy {
+    type Err = String;
+
+    fn from_str(name : &str) -> Result<Self, String> {
+        match name {
+            "greedy" => Ok(AssignmentStrategy::Greedy),
+            "insertion" => Ok(AssignmentStrategy::Insertion),
+            "dfs" => Ok(AssignmentStrategy::DfsChains),
+            _ => Err(format!("unknown assignment strategy {} (expected greedy, insertion or dfs)", name)),
+        }
+    }
+}
+
+fn by_earliest_start(problem : &Problem) -> Vec<usize> {
+    let mut order : Vec<usize> = (0..problem.rides.len()).collect();
+    order.sort_by_key(|&ride| (problem.rides[ride].earliest_start, problem.rides[ride].latest_end));
+    order
+}
+
+fn to_rides(problem : &Problem, routes : Vec<Vec<usize>>) -> Vec<Vec<Ride>> {
+    routes.into_iter()
+        .map(|route| route.into_iter().map(|ride| problem.rides[ride].clone()).collect())
+        .collect()
+}
+
+/// Gives each ride, in order of earliest start, to the vehicle that can start
+/// it soonest among those that can still finish it in time, preferring the
+/// vehicle that has been idle least on ties.
+pub fn greedy(problem : &Problem) -> Vec<Vec<Ride>> {
+    let vehicles = problem.vehicles.max(0) as usize;
+    let mut states = vec![VehicleState::start(); vehicles];
+    let mut routes = vec![Vec::new(); vehicles];
+
+    for ride in by_earliest_start(problem) {
+        let mut best : Option<((i32, i32), usize, VehicleState)> = None;
+
+        for (vehicle, &state) in states.iter().enumerate() {
+            let (next, outcome) = drive(state, &problem.rides[ride], problem.per_ride_bonus);
+            if outcome.points == 0 || next.time > problem.total_time {
+                continue;
+            }
+            let key = (outcome.start, outcome.start - state.time);
+            if best.is_none_or(|(best_key, _, _)| key < best_key) {
+                best = Some((key, vehicle, next));
+            }
+        }
+
+        if let Some((_, vehicle, next)) = best {
+            states[vehicle] = next;
+            routes[vehicle].push(ride);
+        }
+    }
+
+    to_rides(problem, routes)
+}
+
+/// A route with the state of its vehicle before each ride, and after the
+/// last, and the points each ride earns.
+struct Route {
+    rides : Vec<usize>,
+    states : Vec<VehicleState>,
+    points : Vec<i32>
+}
+
+impl Route {
+    fn new() -> Route {
+        Route { rides : vec![], states : vec![VehicleState::start()], points : vec![] }
+    }
+
+    fn replay(&mut self, problem : &Problem) {
+        self.states.truncate(1);
+        self.points.clear();
+        for &ride in &self.rides {
+            let (next, outcome) = drive(*self.states.last().unwrap(), &problem.rides[ride], problem.per_ride_bonus);
+            self.states.push(next);
+            self.points.push(outcome.points);
+        }
+    }
+
+    /// The change in this route's points from doing `ride` before the ride at
+    /// `position`, or `None` if the vehicle would overrun the simulation, the
+    /// ride would earn nothing or a later ride would become late. Later rides
+    /// are replayed only until the vehicle is back on its old schedule.
+    fn insertion_gain(&self, problem : &Problem, ride : usize, position : usize) -> Option<i64> {
+        let (mut state, outcome) = drive(self.states[position], &problem.rides[ride], problem.per_ride_bonus);
+        if outcome.points == 0 || state.time > problem.total_time {
+            return None;
+        }
+
+        let mut gain = outcome.points as i64;
+        for later in position..self.rides.len() {
+            let (next, outcome) = drive(state, &problem.rides[self.rides[later]], problem.per_ride_bonus);
+            if next.time > problem.total_time || (outcome.points == 0 && self.points[later] > 0) {
+                return None;
+            }
+            gain += outcome.points as i64 - self.points[later] as i64;
+            if next.time == self.states[later + 1].time {
+                break;
+            }
+            state = next;
+        }
+        Some(gain)
+    }
+}
+
+/// Inserts each ride, in order of earliest start, at the position in any
+/// route where it adds the most points, leaving it out if it adds none.
+pub fn insertion(problem : &Problem) -> Vec<Vec<Ride>> {
+    let vehicles = problem.vehicles.max(0) as usize;
+    let mut routes : Vec<Route> = (0..vehicles).map(|_| Route::new()).collect();
+
+    for ride in by_earliest_start(problem) {
+        let mut best : Option<(i64, usize, usize)> = None;
+
+        for (vehicle, route) in routes.iter().enumerate() {
+            for position in 0..route.rides.len() + 1 {
+                if let Some(gain) = route.insertion_gain(problem, ride, position) {
+                    if gain > 0 && best.is_none_or(|(best_gain, _, _)| gain > best_gain) {
+                        best = Some((gain, vehicle, position));
+                    }
+                }
+            }
+        }
+
+        if let Some((_, vehicle, position)) = best {
+            routes[vehicle].rides.insert(position, ride);
+            routes[vehicle].replay(problem);
+        }
+    }
+
+    to_rides(problem, routes.into_iter().map(|route| route.rides).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{example};
+    use super::super::scoring::score;
+    use super::super::solution::Solution;
+
+    #[test]
+    fn builds_one_feasible_route_per_vehicle() {
+        let problem = example();
+        for routes in vec![greedy(&problem), insertion(&problem)] {
+            assert_eq!(routes.len(), 2);
+            let report = score(&problem, &Solution::new(routes));
+            assert!(report.is_feasible(), "{}", report);
+            assert!(report.score > 0);
+        }
+    }
+
+    #[test]
+    fn insertion_finds_the_best_example_solution() {
+        let problem = example();
+        assert_eq!(score(&problem, &Solution::new(insertion(&problem))).score, 10);
+    }
+
+    #[test]
+    fn insertion_never_makes_a_ride_late() {
+        // putting the longer second ride first earns more, but makes the
+        // first ride late
+        let rides = vec![
+            Ride::new(0, 0, 0, 0, 3, 0, 3),
+            Ride::new(1, 0, 0, 5, 0, 0, 5),
+        ];
+        let problem = Problem::new(10, 10, 1, 2, 2, 100, rides);
+        let report = score(&problem, &Solution::new(insertion(&problem)));
+        assert_eq!(report.rides_late, 0);
+        assert_eq!(report.score, 5);
+    }
+
+    #[test]
+    fn parses_strategy_names() {
+        assert_eq!("dfs".parse(), Ok(AssignmentStrategy::DfsChains));
+        assert!("random".parse::<AssignmentStrategy>().is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use super::ride::Ride;
+use std::cmp;
+
+/// The rides whose start falls in one cell of a grid over the city.
+#[derive(Debug)]
+struct Bucket {
+    min_x : i32,
+    max_x : i32,
+    min_y : i32,
+    max_y : i32,
+    /// The latest start of each ride in the bucket and its node, by latest start.
+    rides : Vec<(i32, u32)>
+}
+
+impl Bucket {
+    /// The shortest distance from (x, y) to any ride start in the bucket.
+    fn distance_from(&self, x : i32, y : i32) -> i32 {
+        let dx = cmp::max(0, cmp::max(self.min_x - x, x - self.max_x));
+        let dy = cmp::max(0, cmp::max(self.min_y - y, y - self.max_y));
+        dx + dy
+    }
+}
+
+/// Which rides can follow which. Node 0 is the start of every vehicle at the
+/// origin and node `i + 1` is ride `i`, and there is an edge from `a` to `b`
+/// whenever `Ride::are_connected(a, b)`: finishing `a` as early as possible
+/// still leaves time to drive to `b` and start it.
+///
+/// Only an index of the rides is stored and edges are found when asked for,
+/// since on inputs with wide time windows nearly every ride can follow every
+/// other and the edges alone would take as much memory as a full matrix.
+#[derive(Debug)]
+pub struct CompatibilityGraph<'a> {
+    nodes : &'a [Ride],
+    buckets : Vec<Bucket>
+}
+
+impl<'a> CompatibilityGraph<'a> {
+    /// Indexes `nodes`, where `nodes[0]` stands for the origin, on a city of
+    /// `rows` by `columns`, bucketing rides on their start position and
+    /// sorting each bucket by latest start.
+    pub fn build(nodes : &'a [Ride], rows : i32, columns : i32) -> CompatibilityGraph<'a> {
+        // about 16 rides a bucket
+        let side = cmp::max(1, ((nodes.len() / 16) as f64).sqrt() as i32);
+        let cell = |position : i32, size : i32| {
+            cmp::min(side - 1, cmp::max(0, position as i64 * side as i64 / cmp::max(1, size) as i64) as i32) as usize
+        };
+
+        let mut buckets : Vec<Bucket> = (0..side * side)
+            .map(|_| Bucket { min_x : i32::MAX, max_x : i32::MIN, min_y : i32::MAX, max_y : i32::MIN, rides : vec![] })
+            .collect();
+        for (node, ride) in nodes.iter().enumerate().skip(1) {
+            let bucket = &mut buckets[cell(ride.start_X, rows) * side as usize + cell(ride.start_Y, columns)];
+            bucket.min_x = cmp::min(bucket.min_x, ride.start_X);
+            bucket.max_x = cmp::max(bucket.max_x, ride.start_X);
+            bucket.min_y = cmp::min(bucket.min_y, ride.start_Y);
+            bucket.max_y = cmp::max(bucket.max_y, ride.start_Y);
+            bucket.rides.push((ride.latest_start, node as u32));
+        }
+        buckets.retain(|bucket| !bucket.rides.is_empty());
+        for bucket in buckets.iter_mut() {
+            bucket.rides.sort();
+        }
+
+        CompatibilityGraph { nodes, buckets }
+    }
+
+    /// The nodes that can follow `node`, in no particular order. Whole
+    /// buckets too far away to reach before any of their rides must start
+    /// are skipped, as are the rides in the rest that must start too soon.
+    pub fn neighbours<'b>(&'b self, node : usize) -> impl Iterator<Item = u32> + 'b {
+        let nodes : &'b [Ride] = self.nodes;
+        let from = &nodes[node];
+        self.buckets.iter()
+            .flat_map(move |bucket| {
+                let earliest_arrival = from.earliest_end + bucket.distance_from(from.end_X, from.end_Y);
+                let first = bucket.rides.partition_point(|&(latest_start, _)| latest_start <= earliest_arrival);
+                bucket.rides[first..].iter().map(|&(_, to)| to)
+            })
+            .filter(move |&to| to as usize != node && Ride::are_connected(from, &nodes[to as usize]))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::rand::{Rng, SeedableRng, StdRng};
+
+    fn random_nodes(count : i32, seed : usize) -> Vec<Ride> {
+        let mut rng = StdRng::from_seed(&[seed][..]);
+        let mut nodes = vec![Ride::new(0, 0, 0, 0, 0, -100, 0)];
+        for i in 0..count {
+            let (start_x, start_y) : (i32, i32) = (rng.gen_range(0, 100), rng.gen_range(0, 100));
+            let (end_x, end_y) : (i32, i32) = (rng.gen_range(0, 100), rng.gen_range(0, 100));
+            let earliest_start = rng.gen_range(0, 500);
+            let latest_end = earliest_start + (start_x - end_x).abs() + (start_y - end_y).abs() + rng.gen_range(0, 200);
+            nodes.push(Ride::new(i, start_x, start_y, end_x, end_y, earliest_start, latest_end));
+        }
+        nodes
+    }
+
+    #[test]
+    fn finds_every_connected_pair() {
+        let nodes = random_nodes(300, 3);
+        let graph = CompatibilityGraph::build(&nodes, 100, 100);
+
+        for (a, from) in nodes.iter().enumerate() {
+            let expected : Vec<u32> = (0..nodes.len())
+                .filter(|&b| b != a && Ride::are_connected(from, &nodes[b]))
+                .map(|b| b as u32)
+                .collect();
+            let mut found : Vec<u32> = graph.neighbours(a).collect();
+            found.sort_unstable();
+            assert_eq!(found, expected, "successors of {}", a);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use super::Problem;
+use super::rand::{Rng, SeedableRng, StdRng};
+use super::scoring::{drive, score, VehicleState, Violation};
+use super::solution::Solution;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Settings for the simulated annealing improvement phase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Annealing {
+    pub time_limit : Duration,
+    /// The temperature at the start, cooling linearly to zero at the time
+    /// limit. Defaults to a hundredth of the mean ride length; most moves
+    /// lose a whole ride, so anything hotter just wanders.
+    pub start_temperature : Option<f64>,
+    /// Stop after this many moves as well, cooling over the moves rather
+    /// than the time limit, so that a seeded run is repeatable.
+    pub max_moves : Option<u64>,
+    pub seed : usize
+}
+
+impl Annealing {
+    pub fn new(time_limit : Duration, seed : usize) -> Annealing {
+        Annealing { time_limit, start_temperature : None, max_moves : None, seed }
+    }
+}
+
+/// What an annealing run did, for reporting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnnealingStats {
+    pub tried : u64,
+    pub accepted : u64
+}
+
+/// How many moves are tried between looking at the clock and the interrupt flag.
+const CHECK_EVERY : u64 = 256;
+
+/// The points a vehicle earns driving `route`, or `None` if it can't finish
+/// before the simulation ends.
+fn route_value(problem : &Problem, route : &[usize]) -> Option<i64> {
+    let mut state = VehicleState::start();
+    let mut total = 0;
+    for &ride in route {
+        let (next, outcome) = drive(state, &problem.rides[ride], problem.per_ride_bonus);
+        if next.time > problem.total_time {
+            return None;
+        }
+        total += outcome.points as i64;
+        state = next;
+    }
+    Some(total)
+}
+
+/// Removes the rides in `route` that earn nothing, returning them. Skipping a
+/// ride never makes the vehicle later for the rest, so this never loses points.
+fn strip_late_rides(problem : &Problem, route : &mut Vec<usize>) -> Vec<usize> {
+    let mut state = VehicleState::start();
+    let mut late = vec![];
+    route.retain(|&ride| {
+        let (next, outcome) = drive(state, &problem.rides[ride], problem.per_ride_bonus);
+        if outcome.points == 0 {
+            late.push(ride);
+            false
+        } else {
+            state = next;
+            true
+        }
+    });
+    late
+}
+
+/// Routes for some vehicles to replace their current ones, and possibly an
+/// unassigned ride that the new routes pick up.
+struct Candidate {
+    routes : Vec<(usize, Vec<usize>)>,
+    inserted : Option<usize>
+}
+
+struct Search<'a> {
+    problem : &'a Problem,
+    routes : Vec<Vec<usize>>,
+    values : Vec<i64>,
+    unassigned : Vec<usize>,
+    total : i64
+}
+
+impl<'a> Search<'a> {
+    /// Starts from `solution`, or returns everything wrong with it if the
+    /// judge would reject it.
+    fn new(problem : &'a Problem, solution : &Solution) -> Result<Search<'a>, Vec<Violation>> {
+        let report = score(problem, solution);
+        if !report.is_feasible() {
+            return Err(report.violations);
+        }
+
+        let vehicles = problem.vehicles.max(0) as usize;
+        let mut assigned = vec![false; problem.rides.len()];
+        let mut routes = vec![vec![]; vehicles];
+        for (vehicle, route) in solution.routes().iter().enumerate() {
+            for ride in route {
+                let id = ride.getId() as usize;
+                assigned[id] = true;
+                routes[vehicle].push(id);
+            }
+        }
+
+        let mut unassigned : Vec<usize> = (0..problem.rides.len()).filter(|&ride| !assigned[ride]).collect();
+        for route in routes.iter_mut() {
+            unassigned.extend(strip_late_rides(problem, route));
+        }
+
+        let values : Vec<i64> = routes.iter()
+            .map(|route| route_value(problem, route).expect("dropping rides never makes a feasible route late"))
+            .collect();
+        let total = values.iter().sum();
+        Ok(Search { problem, routes, values, unassigned, total })
+    }
+
+    fn random_route<R : Rng>(&self, rng : &mut R, min_len : usize) -> Option<usize> {
+        if self.routes.is_empty() {
+            return None;
+        }
+        let vehicle = rng.gen_range(0, self.routes.len());
+        if self.routes[vehicle].len() >= min_len { Some(vehicle) } else { None }
+    }
+
+    /// Where `ride` goes in `route` to keep it in order of earliest start;
+    /// random positions almost always make the vehicle late.
+    fn position_for(&self, route : &[usize], ride : usize) -> usize {
+        let earliest_start = self.problem.rides[ride].earliest_start;
+        route.iter().position(|&other| self.problem.rides[other].earliest_start > earliest_start).unwrap_or(route.len())
+    }
+
+    /// Moves a ride to another route, or to a random position in its own.
+    fn relocate<R : Rng>(&self, rng : &mut R) -> Option<Candidate> {
+        let from = self.random_route(rng, 1)?;
+        let to = self.random_route(rng, 0)?;
+        let mut from_route = self.routes[from].clone();
+        let ride = from_route.remove(rng.gen_range(0, from_route.len()));
+
+        if from == to {
+            from_route.insert(rng.gen_range(0, from_route.len() + 1), ride);
+            return Some(Candidate { routes : vec![(from, from_route)], inserted : None });
+        }
+        let mut to_route = self.routes[to].clone();
+        let position = self.position_for(&to_route, ride);
+        to_route.insert(position, ride);
+        Some(Candidate { routes : vec![(from, from_route), (to, to_route)], inserted : None })
+    }
+
+    /// Exchanges two rides, in the same route or different ones.
+    fn swap<R : Rng>(&self, rng : &mut R) -> Option<Candidate> {
+        let (a, b) = (self.random_route(rng, 1)?, self.random_route(rng, 1)?);
+        let (i, j) = (rng.gen_range(0, self.routes[a].len()), rng.gen_range(0, self.routes[b].len()));
+
+        if a == b {
+            if i == j {
+                return None;
+            }
+            let mut route = self.routes[a].clone();
+            route.swap(i, j);
+            return Some(Candidate { routes : vec![(a, route)], inserted : None });
+        }
+        let (mut route_a, mut route_b) = (self.routes[a].clone(), self.routes[b].clone());
+        ::std::mem::swap(&mut route_a[i], &mut route_b[j]);
+        Some(Candidate { routes : vec![(a, route_a), (b, route_b)], inserted : None })
+    }
+
+    /// Reverses a stretch of one route.
+    fn two_opt<R : Rng>(&self, rng : &mut R) -> Option<Candidate> {
+        let vehicle = self.random_route(rng, 2)?;
+        let mut route = self.routes[vehicle].clone();
+        let i = rng.gen_range(0, route.len() - 1);
+        let j = rng.gen_range(i + 1, route.len());
+        route[i..j + 1].reverse();
+        Some(Candidate { routes : vec![(vehicle, route)], inserted : None })
+    }
+
+    /// Gives an unassigned ride to a random route.
+    fn insert_unassigned<R : Rng>(&self, rng : &mut R) -> Option<Candidate> {
+        if self.unassigned.is_empty() {
+            return None;
+        }
+        let index = rng.gen_range(0, self.unassigned.len());
+        let vehicle = self.random_route(rng, 0)?;
+        let mut route = self.routes[vehicle].clone();
+        let position = self.position_for(&route, self.unassigned[index]);
+        route.insert(position, self.unassigned[index]);
+        Some(Candidate { routes : vec![(vehicle, route)], inserted : Some(index) })
+    }
+
+    /// The change in total points from taking `candidate`, and the new
+    /// value of each route it changes, or `None` if it is infeasible.
+    fn evaluate(&self, candidate : &Candidate) -> Option<(i64, Vec<i64>)> {
+        let mut delta = 0;
+        let mut values = Vec::with_capacity(candidate.routes.len());
+        for &(vehicle, ref route) in &candidate.routes {
+            let value = route_value(self.problem, route)?;
+            delta += value - self.values[vehicle];
+            values.push(value);
+        }
+        Some((delta, values))
+    }
+
+    fn apply(&mut self, candidate : Candidate, delta : i64, values : Vec<i64>) {
+        for ((vehicle, route), value) in candidate.routes.into_iter().zip(values) {
+            self.routes[vehicle] = route;
+            self.values[vehicle] = value;
+        }
+        if let Some(index) = candidate.inserted {
+            self.unassigned.swap_remove(index);
+        }
+        self.total += delta;
+    }
+}
+
+/// Improves `solution` by simulated annealing over relocate, swap, 2-opt and
+/// insert-unassigned moves, scoring routes by the contest rules. Stops at the
+/// time limit, after `max_moves` if given, or once `should_break_early` is
+/// set, returning the best
+/// solution seen, which is never worse than `solution`. Fails with the
+/// violations of `solution` if the judge would reject it.
+pub fn anneal(problem : &Problem, solution : &Solution, options : &Annealing, should_break_early : &Arc<Mutex<bool>>) -> Result<(Solution, AnnealingStats), Vec<Violation>> {
+    let mut search = Search::new(problem, solution)?;
+    let mut rng = StdRng::from_seed(&[options.seed][..]);
+
+    let start_temperature = options.start_temperature.unwrap_or_else(|| {
+        let total : i64 = problem.rides.iter().map(|ride| ride.duration as i64).sum();
+        (total as f64 / problem.rides.len().max(1) as f64 / 100.0).max(1.0)
+    });
+
+    let mut best_total = search.total;
+    let mut best_routes = search.routes.clone();
+    let started = Instant::now();
+    let mut temperature = start_temperature;
+    let mut iterations : u64 = 0;
+    let mut accepted : u64 = 0;
+
+    loop {
+        if options.max_moves.is_some_and(|moves| iterations >= moves) {
+            break;
+        }
+        if iterations.is_multiple_of(CHECK_EVERY) {
+            let elapsed = started.elapsed();
+            if elapsed >= options.time_limit || *should_break_early.lock().unwrap() {
+                break;
+            }
+            let progress = match options.max_moves {
+                Some(moves) => iterations as f64 / moves as f64,
+                None => elapsed.as_secs_f64() / options.time_limit.as_secs_f64(),
+            };
+            temperature = start_temperature * (1.0 - progress);
+        }
+        iterations += 1;
+
+        let candidate = match rng.gen_range(0, 4) {
+            0 => search.relocate(&mut rng),
+            1 => search.swap(&mut rng),
+            2 => search.two_opt(&mut rng),
+            _ => search.insert_unassigned(&mut rng),
+        };
+        let (candidate, (delta, values)) = match candidate.and_then(|c| search.evaluate(&c).map(|e| (c, e))) {
+            Some(found) => found,
+            None => continue,
+        };
+
+        if delta >= 0 || rng.gen::<f64>() < (delta as f64 / temperature).exp() {
+            search.apply(candidate, delta, values);
+            accepted += 1;
+            if search.total > best_total {
+                best_total = search.total;
+                best_routes = search.routes.clone();
+            }
+        }
+    }
+
+    for route in best_routes.iter_mut() {
+        strip_late_rides(problem, route);
+    }
+    let best = Solution::new(best_routes.into_iter()
+        .map(|route| route.into_iter().map(|ride| problem.rides[ride].clone()).collect())
+        .collect());
+    Ok((best, AnnealingStats { tried : iterations, accepted }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{example, solution};
+
+    #[test]
+    fn improves_a_poor_solution_to_the_best() {
+        let problem = example();
+        // ride 0 is late after ride 1, and ride 2 is left out
+        let start = solution(&problem, &[&[1, 0]]);
+        let options = Annealing { max_moves : Some(20000), ..Annealing::new(Duration::from_secs(60), 7) };
+
+        let (improved, stats) = anneal(&problem, &start, &options, &Arc::new(Mutex::new(false))).unwrap();
+        assert_eq!(stats.tried, 20000);
+        let report = score(&problem, &improved);
+        assert!(report.is_feasible(), "{}", report);
+        assert_eq!(report.score, 10);
+    }
+
+    #[test]
+    fn stops_when_interrupted_without_losing_points() {
+        let problem = example();
+        let start = solution(&problem, &[&[1, 0], &[2]]);
+        let options = Annealing::new(Duration::from_secs(60), 7);
+
+        let (improved, stats) = anneal(&problem, &start, &options, &Arc::new(Mutex::new(true))).unwrap();
+        assert_eq!(stats.tried, 0);
+        let report = score(&problem, &improved);
+        assert_eq!(report.score, score(&problem, &start).score);
+        // the late ride is dropped rather than driven for nothing
+        assert_eq!(report.rides_late, 0);
+    }
+
+    #[test]
+    fn rejects_infeasible_solutions() {
+        let problem = example();
+        let duplicated = solution(&problem, &[&[0], &[0]]);
+        let options = Annealing::new(Duration::from_millis(10), 7);
+
+        let violations = anneal(&problem, &duplicated, &options, &Arc::new(Mutex::new(false))).err().unwrap();
+        assert_eq!(violations, vec![Violation::DuplicateRide { ride : 0, first_vehicle : 0, vehicle : 1 }]);
+        assert!(anneal(&problem, &solution(&problem, &[&[0], &[1], &[2]]), &options, &Arc::new(Mutex::new(false))).is_err());
+        // ride 2 can't be finished in time after ride 0
+        assert!(anneal(&problem, &solution(&problem, &[&[0, 2]]), &options, &Arc::new(Mutex::new(false))).is_err());
+    }
+}
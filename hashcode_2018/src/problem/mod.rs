@@ -4,10 +4,19 @@ use self::rand::Rng;
 extern crate chan_signal;
 pub mod ride;
 pub mod solution;
+pub mod scoring;
+pub mod assignment;
+pub mod improve;
+pub mod compatibility;
+#[cfg(test)]
+mod testing;
 use self::ride::Ride;
+use self::assignment::AssignmentStrategy;
 use self::solution::Solution;
+use self::compatibility::CompatibilityGraph;
 use std::vec::Vec;
-use super::matrix::Matrix;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use self::chan_signal::Signal;
 use std::sync::{Mutex,Arc};
 use std::thread;
@@ -28,7 +37,7 @@ struct DFSResult {
     path: Vec<i32>
 }
 
-fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &mut Vec<bool>, seen_count : &mut i32, total_time : i32, bonus : i32) -> Option<DFSResult> {
+fn dfs(no_rides : i32, graph : &CompatibilityGraph, rides : &Vec<Ride>, seen : &mut Vec<bool>, seen_count : &mut i32, total_time : i32, bonus : i32) -> Option<DFSResult> {
         let null = Ride::new(0,0,0,0,0,-100,0);
 
         // initialize temporary variables
@@ -44,6 +53,10 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
         cost[0] = Some(0);
         let mut added_nodes = 0 + *seen_count;
         let mut leaves = Vec::new();
+        // reached but unadded nodes by cost, ties to the lowest index; entries
+        // are left behind when a node's cost goes up, so skip any that are stale
+        let mut frontier : BinaryHeap<(i32, Reverse<i32>)> = BinaryHeap::new();
+        frontier.push((0, Reverse(0)));
         // while all nodes haven't been considered (there is early termination built in)
         //while(added_nodes != no_rides) {
         loop {
@@ -51,12 +64,11 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
             let mut max_index = None;
 
             // find the maximum unadded node we can add
-            for i in 0..no_rides {
-                if !added[i as usize] && cost[i as usize].is_some() {
-                    if max.is_none() || max.unwrap() < cost[i as usize] {
-                            max = Some(cost[i as usize].clone()); 
-                            max_index = Some(i);
-                    }
+            while let Some((node_cost, Reverse(i))) = frontier.pop() {
+                if !added[i as usize] && cost[i as usize] == Some(node_cost) {
+                    max = Some(cost[i as usize]);
+                    max_index = Some(i);
+                    break;
                 }
             }
 
@@ -75,7 +87,9 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
             // for each node
             let mut child_found = false;
             let max_index_time = current_time[max_index as usize];
-                for i in 0..no_rides {
+                // only rides that could ever follow this one need checking
+                for i in graph.neighbours(max_index as usize) {
+                    let i = i as i32;
 
                     // if we haven't added it
                     if !added[i as usize] {
@@ -122,6 +136,7 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
                             //if i > 0 && max_index > 0 {
                                 current_time[i as usize] = completed_time;
                             //}
+                            frontier.push((cost[i as usize].unwrap(), Reverse(i)));
                             child_found = true;
                         }
                         
@@ -135,6 +150,7 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
                             }
      
                             parent[i as usize] = Some(max_index);
+                            frontier.push((cost[i as usize].unwrap(), Reverse(i)));
                             child_found = true;
 
                         }
@@ -193,6 +209,37 @@ fn dfs(no_rides : i32, edge_matrix : &Matrix<i32>, rides : &Vec<Ride>, seen : &m
         Some(result)
 }
 
+/// Starts listening for SIGINT and SIGTERM, returning a flag that is set
+/// once either arrives. Long running phases check it between steps so that
+/// the best solution so far can still be written out.
+pub fn listen_for_interrupts() -> Arc<Mutex<bool>> {
+    // shared flag to track user requested early termination
+    let should_break_early = Arc::new(Mutex::new(false));
+    // set up channel to listen for sigint signals from terminal
+    let signal = chan_signal::notify(&[Signal::INT, Signal::TERM, Signal::IO]);
+
+    // create new binding (so that move will move it) for the thread to use to access the
+    // shared variable
+    let thread_should_break_early = should_break_early.clone();
+    // use thread to handle sigint calls
+    thread::spawn(move || {
+        // loop forever
+        loop {
+            // retrieve inputs from signal listener
+            let result = signal.recv();
+            if let Some(signal) = result {
+                println!("Early Termination Requested. Will terminate as soon as the current step ends");
+                // if input recieved, update internal variable
+                let mut data = thread_should_break_early.lock().unwrap();
+                *data = true;
+                break;
+            }
+        }
+    });
+
+    should_break_early
+}
+
 impl Problem {
     pub fn new(
         rows : i32,
@@ -213,65 +260,35 @@ impl Problem {
         }
     }
 
-    pub fn solve(&self, low_expt_max : i32, high_expt_max : i32) -> Solution {
-        let mut assignment : Vec<Vec<Ride>>= Vec::new();
+    /// Builds at most one route per vehicle using `strategy`.
+    pub fn solve(&self, strategy : AssignmentStrategy, should_break_early : &Arc<Mutex<bool>>) -> Solution {
+        let routes = match strategy {
+            AssignmentStrategy::Greedy => assignment::greedy(self),
+            AssignmentStrategy::Insertion => assignment::insertion(self),
+            AssignmentStrategy::DfsChains => self.dfs_chains(should_break_early),
+        };
+        debug_assert!(routes.len() <= self.vehicles as usize);
+        Solution::new(routes)
+    }
 
-        // set  up the weights matrix for later in the problem
-        let mut rng = rand::thread_rng();
-        let mut weights : Matrix<i32> =  Matrix::new((self.no_rides + 1) as usize, (self.no_rides + 1) as usize);
-        for i in 0..self.no_rides {
-            for j in 0..self.no_rides {
-                if i != j {
-                    if Ride::are_connected(&self.rides[i as usize], &self.rides[j as usize]) {
-                        unsafe{
-                            *weights.get_mut_unchecked((i+1) as usize,(j+1) as usize) = Ride::get_weight(&self.rides[i as usize], &self.rides[j as usize], self.per_ride_bonus);
-                        }
-                    }
-                }
-            }
-        }
+    /// Repeatedly takes the most valuable chain of unassigned rides, one per
+    /// vehicle, stopping early on SIGINT.
+    fn dfs_chains(&self, should_break_early : &Arc<Mutex<bool>>) -> Vec<Vec<Ride>> {
+        let mut assignment : Vec<Vec<Ride>>= Vec::new();
 
-        // also add weights from special null node at (0,0) to all connected nodes
+        // index which rides can follow which, with the origin as node 0
         let null = Ride::new(0,0,0,0,0,-100,0);
-        for i in 0..self.no_rides {
-            if Ride::are_connected(&null, &self.rides[i as usize]) {
-                    unsafe{
-                        *weights.get_mut_unchecked(0,(i+1) as usize) = Ride::get_weight(&null, &self.rides[i as usize], self.per_ride_bonus);
-                    }
-            }
-        }
+        let nodes : Vec<Ride> = Some(null).into_iter().chain(self.rides.iter().cloned()).collect();
+        let graph = CompatibilityGraph::build(&nodes, self.rows, self.columns);
 
 
         // Do DFS method to find leaves
         let mut seen = vec![false; ((self.no_rides + 1) as usize)];
         let mut seen_count = 0;
 
-        // shared flag to track user requested early termination
-        let should_break_early = Arc::new(Mutex::new(false));
-        // set up channel to listen for sigint signals from terminal
-        let signal = chan_signal::notify(&[Signal::INT, Signal::TERM, Signal::IO]);
-
-        // create new binding (so that move will move it) for the thread to use to access the
-        // shared variable
-        let thread_should_break_early = should_break_early.clone();
-        // use thread to handle sigint calls
-        thread::spawn(move || {
-            // loop forever
-            loop {
-                // retrieve inputs from signal listener
-                let result = signal.recv();
-                if let Some(signal) = result {
-                    println!("Early Termination Requested. Will terminate as soon as current DFS loop ends");
-                    // if input recieved, update internal variable
-                    let mut data = thread_should_break_early.lock().unwrap();
-                    *data = true;
-                    break;
-                }
-            }
-        });
-
         let mut last_seen_count = 0;
-            for i in 0..self.no_rides {
+            // each chain is one vehicle's route, so stop once every vehicle has one
+            for i in 0..self.vehicles {
                 // at the beginning, check value of shared variable - if set break
                 {
                     let mut data = should_break_early.lock().unwrap();
@@ -282,7 +299,7 @@ impl Problem {
                 
                 println!("Iteration[{}]: allocated {} (+{})", i, seen_count, seen_count-last_seen_count);
                 last_seen_count = seen_count;
-                let path = match dfs((self.no_rides + 1), &weights, &self.rides, &mut seen, &mut seen_count, self.total_time, self.per_ride_bonus) {
+                let path = match dfs((self.no_rides + 1), &graph, &self.rides, &mut seen, &mut seen_count, self.total_time, self.per_ride_bonus) {
                     Some(result) => {
                         result.path.into_iter().map(|x| self.rides[(x - 1) as usize].clone()).collect()
                     }
@@ -296,10 +313,10 @@ impl Problem {
 
             }
 
-        println!("Main Loop Terminated, Writing solution to file");
+        println!("DFS Loop Terminated");
 
 
-        Solution::new(assignment)
+        assignment
     }
 
 }
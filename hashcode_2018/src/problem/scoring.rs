@@ -0,0 +1,223 @@
+use super::Problem;
+use super::ride::Ride;
+use super::solution::Solution;
+use std::cmp;
+use std::fmt::{Display, Formatter, self};
+
+/// Something in a solution the judge would reject.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    TooManyVehicles { used : usize, available : i32 },
+    UnknownRide { vehicle : usize, ride : i32 },
+    DuplicateRide { ride : i32, first_vehicle : usize, vehicle : usize },
+    /// The ride can't be finished before the simulation ends.
+    PastTotalTime { vehicle : usize, ride : i32, finish : i32 }
+}
+
+/// The exact contest score of a solution, with a breakdown and everything
+/// wrong with it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScoreReport {
+    pub score : i64,
+    pub distance_points : i64,
+    pub bonus_points : i64,
+    pub rides_scored : u32,
+    pub rides_on_time : u32,
+    /// Rides finished after their latest finish, which earn nothing.
+    pub rides_late : u32,
+    pub violations : Vec<Violation>
+}
+
+impl ScoreReport {
+    pub fn is_feasible(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Where and when a vehicle is after finishing a ride.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VehicleState {
+    pub x : i32,
+    pub y : i32,
+    pub time : i32
+}
+
+impl VehicleState {
+    /// Every vehicle starts at the origin at step 0.
+    pub fn start() -> VehicleState {
+        VehicleState { x : 0, y : 0, time : 0 }
+    }
+}
+
+/// The outcome of a vehicle driving to and completing a ride.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RideOutcome {
+    pub start : i32,
+    pub finish : i32,
+    /// Distance points plus any bonus, zero if the ride finished late.
+    pub points : i32,
+    pub on_time : bool
+}
+
+/// Drives a vehicle from `state` to `ride`, waiting for its earliest start if
+/// early, and through the ride. A ride earns its distance if it finishes no
+/// later than its latest finish, and the bonus as well if it started exactly
+/// at its earliest start.
+pub fn drive(state : VehicleState, ride : &Ride, bonus : i32) -> (VehicleState, RideOutcome) {
+    let arrival = state.time + (state.x - ride.start_X).abs() + (state.y - ride.start_Y).abs();
+    let start = cmp::max(arrival, ride.earliest_start);
+    let finish = start + ride.duration;
+
+    let in_time = finish <= ride.latest_end;
+    let on_time = in_time && start == ride.earliest_start;
+    let points = if !in_time {
+        0
+    } else if on_time {
+        ride.duration + bonus
+    } else {
+        ride.duration
+    };
+
+    (VehicleState { x : ride.end_X, y : ride.end_Y, time : finish }, RideOutcome { start, finish, points, on_time })
+}
+
+/// The points a vehicle earns driving `rides` in order from the origin,
+/// ignoring feasibility; the building block for comparing route edits.
+pub fn route_score(problem : &Problem, rides : &[Ride]) -> i64 {
+    let mut state = VehicleState::start();
+    let mut total = 0;
+    for ride in rides {
+        let (next, outcome) = drive(state, ride, problem.per_ride_bonus);
+        if next.time > problem.total_time {
+            break;
+        }
+        total += outcome.points as i64;
+        state = next;
+    }
+    total
+}
+
+/// Replays every vehicle's rides against the problem's own ride list, scoring
+/// by the contest rules and recording every violation. Rides that violate
+/// the rules earn nothing.
+pub fn score(problem : &Problem, solution : &Solution) -> ScoreReport {
+    let mut report = ScoreReport::default();
+    let routes = solution.routes();
+    if routes.len() > problem.vehicles as usize {
+        report.violations.push(Violation::TooManyVehicles { used : routes.len(), available : problem.vehicles });
+    }
+
+    let mut assigned_to : Vec<Option<usize>> = vec![None; problem.rides.len()];
+
+    for (vehicle, route) in routes.iter().enumerate() {
+        let mut state = VehicleState::start();
+
+        for ride in route {
+            let id = ride.getId();
+            if id < 0 || id as usize >= problem.rides.len() {
+                report.violations.push(Violation::UnknownRide { vehicle, ride : id });
+                continue;
+            }
+
+            if let Some(first_vehicle) = assigned_to[id as usize] {
+                report.violations.push(Violation::DuplicateRide { ride : id, first_vehicle, vehicle });
+                continue;
+            }
+            assigned_to[id as usize] = Some(vehicle);
+
+            // trust the problem's copy of the ride, not the solution's
+            let (next, outcome) = drive(state, &problem.rides[id as usize], problem.per_ride_bonus);
+            if next.time > problem.total_time {
+                report.violations.push(Violation::PastTotalTime { vehicle, ride : id, finish : next.time });
+                break;
+            }
+            state = next;
+
+            if outcome.finish > problem.rides[id as usize].latest_end {
+                report.rides_late += 1;
+                continue;
+            }
+            report.rides_scored += 1;
+            report.distance_points += problem.rides[id as usize].duration as i64;
+            if outcome.on_time {
+                report.rides_on_time += 1;
+                report.bonus_points += problem.per_ride_bonus as i64;
+            }
+        }
+    }
+
+    report.score = report.distance_points + report.bonus_points;
+    report
+}
+
+impl Display for Violation {
+    fn fmt(&self, f : &mut Formatter) -> fmt::Result {
+        match *self {
+            Violation::TooManyVehicles { used, available } =>
+                write!(f, "{} vehicles used but only {} available", used, available),
+            Violation::UnknownRide { vehicle, ride } =>
+                write!(f, "vehicle {} is given ride {}, which doesn't exist", vehicle, ride),
+            Violation::DuplicateRide { ride, first_vehicle, vehicle } =>
+                write!(f, "ride {} is given to vehicle {} but already belongs to vehicle {}", ride, vehicle, first_vehicle),
+            Violation::PastTotalTime { vehicle, ride, finish } =>
+                write!(f, "vehicle {} would finish ride {} at step {}, after the simulation ends", vehicle, ride, finish),
+        }
+    }
+}
+
+impl Display for ScoreReport {
+    fn fmt(&self, f : &mut Formatter) -> fmt::Result {
+        write!(f, "score {} ({} distance + {} bonus), {} rides scored, {} on time, {} late",
+               self.score, self.distance_points, self.bonus_points, self.rides_scored, self.rides_on_time, self.rides_late)?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testing::{example, solution};
+
+    #[test]
+    fn scores_the_statement_example() {
+        let problem = example();
+        let report = score(&problem, &solution(&problem, &[&[0], &[2, 1]]));
+
+        assert_eq!(report.score, 10);
+        assert_eq!(report.bonus_points, 2);
+        assert_eq!(report.rides_scored, 3);
+        assert!(report.is_feasible());
+        assert_eq!(route_score(&problem, &[problem.rides[2].clone(), problem.rides[1].clone()]), 4);
+    }
+
+    #[test]
+    fn reports_violations() {
+        let problem = example();
+        let report = score(&problem, &solution(&problem, &[&[0], &[2, 0], &[]]));
+
+        assert_eq!(report.violations, vec![
+            Violation::TooManyVehicles { used : 3, available : 2 },
+            Violation::DuplicateRide { ride : 0, first_vehicle : 0, vehicle : 1 },
+        ]);
+        assert_eq!(report.score, 6 + 2);
+    }
+
+    #[test]
+    fn late_rides_earn_nothing_and_overruns_are_rejected() {
+        // after ride 0 the vehicle only reaches ride 2 at step 10, finishing at 12
+        let mut problem = example();
+        problem.total_time = 20;
+        let report = score(&problem, &solution(&problem, &[&[0, 2]]));
+        assert_eq!(report.score, 6);
+        assert_eq!(report.rides_late, 1);
+        assert!(report.is_feasible());
+
+        problem.total_time = 10;
+        let report = score(&problem, &solution(&problem, &[&[0, 2]]));
+        assert_eq!(report.violations, vec![Violation::PastTotalTime { vehicle : 0, ride : 2, finish : 12 }]);
+    }
+}
@@ -13,6 +13,11 @@ impl Solution {
         }
     }
 
+    /// The rides given to each vehicle, in the order it drives them.
+    pub fn routes(&self) -> &Vec<Vec<Ride>> {
+        &self.assignment
+    }
+
 }
 
 impl  Display for Solution {
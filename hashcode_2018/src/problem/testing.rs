@@ -0,0 +1,20 @@
+//! The worked example from the problem statement, for tests.
+
+use super::Problem;
+use super::ride::Ride;
+use super::solution::Solution;
+
+/// Three rides on a 3x4 city with two vehicles, 10 steps and a bonus of 2.
+pub fn example() -> Problem {
+    let rides = vec![
+        Ride::new(0, 0, 0, 1, 3, 2, 9),
+        Ride::new(1, 1, 2, 1, 0, 0, 9),
+        Ride::new(2, 2, 0, 2, 2, 0, 9),
+    ];
+    Problem::new(3, 4, 2, 3, 2, 10, rides)
+}
+
+/// A solution driving `routes`, given as indices into `problem`'s rides.
+pub fn solution(problem : &Problem, routes : &[&[usize]]) -> Solution {
+    Solution::new(routes.iter().map(|route| route.iter().map(|&i| problem.rides[i].clone()).collect()).collect())
+}
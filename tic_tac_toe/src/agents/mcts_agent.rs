@@ -0,0 +1,272 @@
+extern crate rand;
+
+use self::rand::{Rng, SeedableRng, StdRng};
+use std::time::{Duration, Instant};
+use board::{TicTacToeCell, TicTacToeBoard};
+
+/// The exploration constant in the UCT formula.
+const EXPLORATION : f64 = ::std::f64::consts::SQRT_2;
+
+/// How long the agent may think about each move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    /// Run exactly this many playouts per move, so play is reproducible for a given seed.
+    Iterations(u32),
+    /// Run playouts until this much wall-clock time has passed.
+    Time(Duration)
+}
+
+struct Node {
+    /// The cell played to reach this node, `None` at the root.
+    cell : Option<usize>,
+    parent : Option<usize>,
+    children : Vec<usize>,
+    untried : Vec<usize>,
+    /// The piece that moved into this node, whose point of view `wins` is from.
+    mover : TicTacToeCell,
+    /// The result if the game is over here, with `has_anyone_won` semantics.
+    outcome : Option<TicTacToeCell>,
+    visits : u32,
+    wins : f64
+}
+
+/// Monte Carlo tree search with UCT selection and uniformly random playouts.
+///
+/// Unlike `MinimaxAgent` it scales to large boards, trading exactness for
+/// however many playouts the budget allows. A fresh tree is grown for every
+/// move and the most visited reply at the root is played.
+pub struct MctsAgent {
+    player : TicTacToeCell,
+    budget : Budget,
+    rng : StdRng
+}
+
+fn empty_cells(board : &TicTacToeBoard) -> Vec<usize> {
+    board.iter()
+        .enumerate()
+        .filter(|&(_, cell)| *cell == TicTacToeCell::Empty)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// The state of the game after `cell` was just played, with `has_anyone_won`
+/// semantics, only checking lines through `cell`.
+fn outcome_after(board : &TicTacToeBoard, cell : usize, empties : usize) -> Option<TicTacToeCell> {
+    if board.wins_through(cell) {
+        Some(board[cell])
+    } else if empties == 0 {
+        None
+    } else {
+        Some(TicTacToeCell::Empty)
+    }
+}
+
+impl MctsAgent {
+    pub fn new(player : TicTacToeCell, budget : Budget, seed : usize) -> Self {
+        assert!(player != TicTacToeCell::Empty, "Agent can not use an empty space as its piece");
+        if let Budget::Iterations(iterations) = budget {
+            assert!(iterations > 0, "Agent needs at least one iteration per move");
+        }
+
+        MctsAgent {
+            player,
+            budget,
+            rng : StdRng::from_seed(&[seed][..])
+        }
+    }
+
+    fn new_node(&mut self, board : &TicTacToeBoard, cell : Option<usize>, parent : Option<usize>, mover : TicTacToeCell, outcome : Option<TicTacToeCell>) -> Node {
+        let mut untried = if outcome == Some(TicTacToeCell::Empty) { empty_cells(board) } else { vec![] };
+        self.rng.shuffle(&mut untried);
+        Node { cell, parent, children : vec![], untried, mover, outcome, visits : 0, wins : 0.0 }
+    }
+
+    /// The child of `node` with the best upper confidence bound.
+    fn select_child(tree : &[Node], node : usize) -> usize {
+        let log_visits = (tree[node].visits as f64).ln();
+        let uct = |child : usize| {
+            let child = &tree[child];
+            child.wins / child.visits as f64 + EXPLORATION * (log_visits / child.visits as f64).sqrt()
+        };
+
+        *tree[node].children.iter()
+            .max_by(|&&a, &&b| uct(a).partial_cmp(&uct(b)).unwrap())
+            .expect("Sanity failure in agents::mcts_agent::select_child")
+    }
+
+    /// Plays random moves from `board` until the game ends, returning the
+    /// winner or `None` for a draw.
+    fn playout(&mut self, board : &mut TicTacToeBoard, to_move : TicTacToeCell) -> Option<TicTacToeCell> {
+        let mut empties = empty_cells(board);
+        let mut to_move = to_move;
+        self.rng.shuffle(&mut empties);
+
+        while let Some(cell) = empties.pop() {
+            board[cell] = to_move;
+            match outcome_after(board, cell, empties.len()) {
+                Some(TicTacToeCell::Empty) => to_move = to_move.opponent(),
+                outcome => return outcome,
+            }
+        }
+        None
+    }
+
+    /// One round of selection, expansion, simulation and backpropagation.
+    fn iterate(&mut self, tree : &mut Vec<Node>, root_board : &TicTacToeBoard) {
+        let mut board = root_board.clone();
+        let mut node = 0;
+
+        while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+            node = MctsAgent::select_child(tree, node);
+            board[tree[node].cell.unwrap()] = tree[node].mover;
+        }
+
+        if let Some(cell) = tree[node].untried.pop() {
+            let mover = tree[node].mover.opponent();
+            board[cell] = mover;
+            let empties = board.iter().filter(|&&cell| cell == TicTacToeCell::Empty).count();
+            let outcome = outcome_after(&board, cell, empties);
+            let child = self.new_node(&board, Some(cell), Some(node), mover, outcome);
+            tree.push(child);
+            let child = tree.len() - 1;
+            tree[node].children.push(child);
+            node = child;
+        }
+
+        let result = match tree[node].outcome {
+            Some(TicTacToeCell::Empty) => {
+                let to_move = tree[node].mover.opponent();
+                self.playout(&mut board, to_move)
+            },
+            outcome => outcome,
+        };
+
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut tree[index];
+            node.visits += 1;
+            node.wins += match result {
+                None => 0.5,
+                Some(winner) if winner == node.mover => 1.0,
+                Some(_) => 0.0,
+            };
+            current = node.parent;
+        }
+    }
+}
+
+impl super::Agent for MctsAgent {
+    fn get_next_move(&mut self, board: &TicTacToeBoard) -> usize {
+        if board.has_anyone_won() != Some(TicTacToeCell::Empty) {
+            panic!("Get next move called on Agents::MctsAgent after game has been completed");
+        }
+
+        let root = self.new_node(board, None, None, self.player.opponent(), Some(TicTacToeCell::Empty));
+        let mut tree = vec![root];
+
+        match self.budget {
+            Budget::Iterations(iterations) => {
+                for _ in 0..iterations {
+                    self.iterate(&mut tree, board);
+                }
+            },
+            Budget::Time(limit) => {
+                let start = Instant::now();
+                loop {
+                    self.iterate(&mut tree, board);
+                    if start.elapsed() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let best = tree[0].children.iter()
+            .max_by_key(|&&child| tree[child].visits)
+            .expect("Sanity failure in agents::mcts_agent::get_next_move");
+        tree[*best].cell.unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agents::Agent;
+    use agents::optimal_agent::OptimalAgent;
+
+    /// Plays one game to the end, `first` moving first, and returns the result
+    /// with `has_anyone_won` semantics.
+    fn play_game(first : &mut dyn Agent, first_piece : TicTacToeCell, second : &mut dyn Agent, board : TicTacToeBoard) -> Option<TicTacToeCell> {
+        let mut board = board;
+        let mut turn = 0;
+        while board.has_anyone_won() == Some(TicTacToeCell::Empty) {
+            let (cell, piece) = if turn % 2 == 0 {
+                (first.get_next_move(&board), first_piece)
+            } else {
+                (second.get_next_move(&board), first_piece.opponent())
+            };
+            assert_eq!(board[cell], TicTacToeCell::Empty, "Occupied cell {} chosen on {}", cell, board);
+            board[cell] = piece;
+            turn += 1;
+        }
+        board.has_anyone_won()
+    }
+
+    #[test]
+    fn never_loses_to_optimal_agent() {
+        for seed in 0..4 {
+            let mut mcts = MctsAgent::new(TicTacToeCell::X, Budget::Iterations(2000), seed);
+            let mut optimal = OptimalAgent::new(TicTacToeCell::O);
+            assert_ne!(play_game(&mut mcts, TicTacToeCell::X, &mut optimal, TicTacToeBoard::new()), Some(TicTacToeCell::O));
+
+            let mut mcts = MctsAgent::new(TicTacToeCell::O, Budget::Iterations(2000), seed);
+            let mut optimal = OptimalAgent::new(TicTacToeCell::X);
+            assert_ne!(play_game(&mut optimal, TicTacToeCell::X, &mut mcts, TicTacToeBoard::new()), Some(TicTacToeCell::X));
+        }
+    }
+
+    #[test]
+    fn beats_optimal_agent_on_a_larger_board() {
+        let mut mcts = MctsAgent::new(TicTacToeCell::X, Budget::Iterations(1000), 7);
+        let mut optimal = OptimalAgent::new(TicTacToeCell::O);
+        assert_eq!(play_game(&mut mcts, TicTacToeCell::X, &mut optimal, TicTacToeBoard::with_size(5, 5, 4)), Some(TicTacToeCell::X));
+    }
+
+    #[test]
+    fn takes_an_immediate_win_and_blocks_a_loss() {
+        // X X _
+        // O O _
+        // _ _ _
+        let mut board = TicTacToeBoard::new();
+        board[0] = TicTacToeCell::X;
+        board[1] = TicTacToeCell::X;
+        board[3] = TicTacToeCell::O;
+        board[4] = TicTacToeCell::O;
+        assert_eq!(MctsAgent::new(TicTacToeCell::X, Budget::Iterations(500), 1).get_next_move(&board), 2);
+
+        // X X _
+        // O _ _
+        // _ _ _
+        board[4] = TicTacToeCell::Empty;
+        assert_eq!(MctsAgent::new(TicTacToeCell::O, Budget::Iterations(500), 1).get_next_move(&board), 2);
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_move() {
+        let mut board = TicTacToeBoard::with_size(6, 6, 4);
+        board[14] = TicTacToeCell::X;
+        let moves : Vec<usize> = (0..2)
+            .map(|_| MctsAgent::new(TicTacToeCell::O, Budget::Iterations(300), 42).get_next_move(&board))
+            .collect();
+        assert_eq!(moves[0], moves[1]);
+    }
+
+    #[test]
+    fn time_budget_returns_a_legal_move() {
+        let mut board = TicTacToeBoard::with_size(15, 15, 5);
+        board[112] = TicTacToeCell::X;
+        let cell = MctsAgent::new(TicTacToeCell::O, Budget::Time(Duration::from_millis(50)), 3).get_next_move(&board);
+        assert_eq!(board[cell], TicTacToeCell::Empty);
+    }
+}
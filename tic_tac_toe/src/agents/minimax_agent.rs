@@ -0,0 +1,245 @@
+use std::cmp;
+use std::collections::HashMap;
+use board::{TicTacToeCell, TicTacToeBoard};
+
+const INFINITY : i32 = 1000;
+
+/// How a stored score relates to the true value of its position: searches cut
+/// off by alpha-beta pruning only prove a bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    value : i32,
+    bound : Bound
+}
+
+/// Plays perfectly by searching the whole game tree with alpha-beta pruning,
+/// remembering the value of every position it has searched in a
+/// transposition table that persists between moves.
+///
+/// Values are from the point of view of the player to move. A win is worth
+/// one more than the number of empty cells left when it happens, so quicker
+/// wins and slower losses are preferred; a draw is worth 0.
+///
+/// The search is exhaustive, so it is only practical on small boards such as
+/// the classic 3x3.
+pub struct MinimaxAgent {
+    player : TicTacToeCell,
+    table : HashMap<(TicTacToeBoard, TicTacToeCell), Entry>
+}
+
+fn empty_cells(board : &TicTacToeBoard) -> Vec<usize> {
+    board.iter()
+        .enumerate()
+        .filter(|&(_, cell)| *cell == TicTacToeCell::Empty)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+impl MinimaxAgent {
+    pub fn new(player : TicTacToeCell) -> Self {
+        assert!(player != TicTacToeCell::Empty, "Agent can not use an empty space as its piece");
+        MinimaxAgent {
+            player,
+            table : HashMap::new()
+        }
+    }
+
+    /// The value of `board` for `to_move` under perfect play by both sides,
+    /// or a bound on it if that lies outside `alpha..beta`.
+    fn search(&mut self, board : &mut TicTacToeBoard, to_move : TicTacToeCell, alpha : i32, beta : i32) -> i32 {
+        let moves = empty_cells(board);
+        match board.has_anyone_won() {
+            None => return 0,
+            Some(TicTacToeCell::Empty) => (),
+            Some(winner) => {
+                let value = moves.len() as i32 + 1;
+                return if winner == to_move { value } else { -value };
+            }
+        }
+
+        let key = (board.clone(), to_move);
+        let (mut alpha, mut beta) = (alpha, beta);
+        let original_alpha = alpha;
+        if let Some(entry) = self.table.get(&key) {
+            match entry.bound {
+                Bound::Exact => return entry.value,
+                Bound::Lower => alpha = cmp::max(alpha, entry.value),
+                Bound::Upper => beta = cmp::min(beta, entry.value),
+            }
+            if alpha >= beta {
+                return entry.value;
+            }
+        }
+
+        let mut best = -INFINITY;
+        for cell in moves {
+            board[cell] = to_move;
+            let value = -self.search(board, to_move.opponent(), -beta, -alpha);
+            board[cell] = TicTacToeCell::Empty;
+
+            best = cmp::max(best, value);
+            alpha = cmp::max(alpha, value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, Entry { value : best, bound });
+
+        best
+    }
+}
+
+impl super::Agent for MinimaxAgent {
+    fn get_next_move(&mut self, board: &TicTacToeBoard) -> usize {
+        if board.has_anyone_won() != Some(TicTacToeCell::Empty) {
+            panic!("Get next move called on Agents::MinimaxAgent after game has been completed");
+        }
+
+        let mut board = board.clone();
+        let mut best : Option<(i32, usize)> = None;
+        let mut alpha = -INFINITY;
+
+        for cell in empty_cells(&board) {
+            board[cell] = self.player;
+            let value = -self.search(&mut board, self.player.opponent(), -INFINITY, -alpha);
+            board[cell] = TicTacToeCell::Empty;
+
+            if best.is_none_or(|(best_value, _)| value > best_value) {
+                best = Some((value, cell));
+                alpha = value;
+            }
+        }
+
+        best.expect("Sanity failure in agents::minimax_agent::get_next_move").1
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agents::Agent;
+
+    /// Plain minimax with no pruning or memory, to check the agent against.
+    fn reference_value(board : &mut TicTacToeBoard, to_move : TicTacToeCell) -> i32 {
+        let moves = empty_cells(board);
+        match board.has_anyone_won() {
+            None => return 0,
+            Some(TicTacToeCell::Empty) => (),
+            Some(winner) => {
+                let value = moves.len() as i32 + 1;
+                return if winner == to_move { value } else { -value };
+            }
+        }
+
+        moves.into_iter().map(|cell| {
+            board[cell] = to_move;
+            let value = -reference_value(board, to_move.opponent());
+            board[cell] = TicTacToeCell::Empty;
+            value
+        }).max().unwrap()
+    }
+
+    /// Tries every opponent reply at every turn, failing if any game ends in
+    /// a loss for the agent.
+    fn never_loses(agent : &mut MinimaxAgent, board : &mut TicTacToeBoard, to_move : TicTacToeCell) {
+        match board.has_anyone_won() {
+            Some(TicTacToeCell::Empty) => (),
+            Some(winner) => {
+                assert_eq!(winner, agent.player, "Agent lost the game {}", board);
+                return;
+            },
+            None => return,
+        }
+
+        if to_move == agent.player {
+            let cell = agent.get_next_move(board);
+            assert_eq!(board[cell], TicTacToeCell::Empty);
+            board[cell] = to_move;
+            never_loses(agent, board, to_move.opponent());
+            board[cell] = TicTacToeCell::Empty;
+        } else {
+            for cell in empty_cells(board) {
+                board[cell] = to_move;
+                never_loses(agent, board, to_move.opponent());
+                board[cell] = TicTacToeCell::Empty;
+            }
+        }
+    }
+
+    /// Checks the agent's move from every position reachable with `to_move`
+    /// to play keeps the game-theoretic value of the position.
+    fn plays_optimally_everywhere(agent : &mut MinimaxAgent, board : &mut TicTacToeBoard, to_move : TicTacToeCell) {
+        if board.has_anyone_won() != Some(TicTacToeCell::Empty) {
+            return;
+        }
+
+        if to_move == agent.player {
+            let expected = reference_value(&mut board.clone(), to_move);
+            let cell = agent.get_next_move(board);
+            let mut after = board.clone();
+            after[cell] = to_move;
+            assert_eq!(-reference_value(&mut after, to_move.opponent()), expected, "Suboptimal move {} on {}", cell, board);
+        }
+
+        for cell in empty_cells(board) {
+            board[cell] = to_move;
+            plays_optimally_everywhere(agent, board, to_move.opponent());
+            board[cell] = TicTacToeCell::Empty;
+        }
+    }
+
+    #[test]
+    fn never_loses_as_either_player() {
+        for &player in &[TicTacToeCell::X, TicTacToeCell::O] {
+            for &first in &[TicTacToeCell::X, TicTacToeCell::O] {
+                let mut agent = MinimaxAgent::new(player);
+                never_loses(&mut agent, &mut TicTacToeBoard::new(), first);
+            }
+        }
+    }
+
+    #[test]
+    fn moves_optimally_from_every_reachable_position() {
+        for &player in &[TicTacToeCell::X, TicTacToeCell::O] {
+            let mut agent = MinimaxAgent::new(player);
+            plays_optimally_everywhere(&mut agent, &mut TicTacToeBoard::new(), TicTacToeCell::X);
+        }
+    }
+
+    #[test]
+    fn empty_board_is_a_draw() {
+        let mut agent = MinimaxAgent::new(TicTacToeCell::X);
+        assert_eq!(agent.search(&mut TicTacToeBoard::new(), TicTacToeCell::X, -INFINITY, INFINITY), 0);
+    }
+
+    #[test]
+    fn takes_an_immediate_win_over_a_block() {
+        // X X _
+        // O O _
+        // _ _ _
+        let mut board = TicTacToeBoard::new();
+        board[0] = TicTacToeCell::X;
+        board[1] = TicTacToeCell::X;
+        board[3] = TicTacToeCell::O;
+        board[4] = TicTacToeCell::O;
+
+        assert_eq!(MinimaxAgent::new(TicTacToeCell::X).get_next_move(&board), 2);
+        assert_eq!(MinimaxAgent::new(TicTacToeCell::O).get_next_move(&board), 5);
+    }
+}
@@ -1,7 +1,25 @@
 pub mod optimal_agent;
+pub mod minimax_agent;
+pub mod mcts_agent;
 use board::TicTacToeBoard;
 
 
 pub trait Agent {
     fn get_next_move(&mut self, board: &TicTacToeBoard) -> usize; 
+
+    /// The next move, or `None` if the agent gives up instead, as a person
+    /// does by closing the terminal. Agents always move by default.
+    fn try_next_move(&mut self, board: &TicTacToeBoard) -> Option<usize> {
+        Some(self.get_next_move(board))
+    }
+}
+
+impl<T : Agent + ?Sized> Agent for Box<T> {
+    fn get_next_move(&mut self, board: &TicTacToeBoard) -> usize {
+        (**self).get_next_move(board)
+    }
+
+    fn try_next_move(&mut self, board: &TicTacToeBoard) -> Option<usize> {
+        (**self).try_next_move(board)
+    }
 }
@@ -1,6 +1,8 @@
 use std::iter::Iterator;
 use board::{TicTacToeCell, TicTacToeBoard};
 
+// The strategy plays the same whichever piece it has, so only tests read it.
+#[allow(dead_code)]
 pub struct OptimalAgent(TicTacToeCell); 
 
 
@@ -54,6 +56,25 @@ mod Tests {
             );
     }
 
+    #[test]
+    fn plays_legal_moves_on_larger_boards() {
+        use agents::Agent;
+
+        for &(rows, cols, k) in &[(4, 4, 4), (3, 5, 3), (15, 15, 5)] {
+            let mut board = TicTacToeBoard::with_size(rows, cols, k);
+            let mut agents = [OptimalAgent::new(TicTacToeCell::X), OptimalAgent::new(TicTacToeCell::O)];
+            let mut turn = 0;
+
+            while board.has_anyone_won() == Some(TicTacToeCell::Empty) {
+                let cell = agents[turn % 2].get_next_move(&board);
+                assert_eq!(board[cell], TicTacToeCell::Empty, "Occupied cell {} chosen on {}", cell, board);
+                board[cell] = agents[turn % 2].0;
+                turn += 1;
+            }
+            assert!(turn <= rows * cols);
+        }
+    }
+
 
 
 }
@@ -61,16 +82,17 @@ mod Tests {
 fn get_majority<'a, T : Iterator<Item=&'a TicTacToeCell>>(iter: T) -> Option<(TicTacToeCell, usize)> {
     let mut x = 0; 
     let mut o = 0;
+    let mut empty = 0;
 
     for cell in iter {
         match cell {
             TicTacToeCell::X => x += 1,
             TicTacToeCell::O => o += 1,
-            TicTacToeCell::Empty => (),
+            TicTacToeCell::Empty => empty += 1,
         }
     }
 
-    if x + o == 3 {
+    if empty == 0 {
         None
     } else if x > o {
         Some((TicTacToeCell::X, x))
@@ -81,40 +103,27 @@ fn get_majority<'a, T : Iterator<Item=&'a TicTacToeCell>>(iter: T) -> Option<(Ti
     }
 }
 
-fn get_diagonal_majority(slice : &[usize], board: &TicTacToeBoard) -> Option<(TicTacToeCell,u8)> {
-    let mut x = 0; 
-    let mut o = 0;
-
-    for i in slice {
-        match board[*i] {
-            TicTacToeCell::X => x += 1,
-            TicTacToeCell::O => o += 1,
-            TicTacToeCell::Empty => (),
-        }
-    }
-
-    if x + o == 3 {
-        None
-    } else if x > o {
-        Some((TicTacToeCell::X, x))
-    } else if o < x {
-        Some((TicTacToeCell::O, o))
-    } else {
-        None
-    }
+// Only square boards have diagonals running corner to corner.
+fn has_diagonals(board : &TicTacToeBoard) -> bool {
+    board.rows() == board.cols()
 }
 
-fn get_left_diagonal_majority(board : &TicTacToeBoard) -> Option<(TicTacToeCell,u8)> {
-    get_diagonal_majority(&[0,4,8], board)
+fn get_left_diagonal_majority(board : &TicTacToeBoard) -> Option<(TicTacToeCell,usize)> {
+    get_majority(board.diagonal_iter(0, 0))
 }
 
-fn get_right_diagonal_majority(board : &TicTacToeBoard) -> Option<(TicTacToeCell,u8)> {
-    get_diagonal_majority(&[2,4,6], board)
+fn get_right_diagonal_majority(board : &TicTacToeBoard) -> Option<(TicTacToeCell,usize)> {
+    get_majority(board.anti_diagonal_iter(0, board.cols() - 1))
 }
 
 
 
 
+fn centre_of(board : &TicTacToeBoard) -> usize {
+    board.index_of(board.rows() / 2, board.cols() / 2)
+}
+
+
 impl OptimalAgent {
     pub fn new(player : TicTacToeCell) -> Self {
         assert!(player != TicTacToeCell::Empty, "Agent can not use an empty space as its piece");
@@ -131,8 +140,7 @@ impl OptimalAgent {
                 break;
             }
         }
-        println!("Selected ({}, {}) -> {}", row, col, row * 3 + col);
-        return row * 3 +  col;
+        board.index_of(row, col)
     }
 
     fn place_in_col(&self, col: usize, board: &TicTacToeBoard) -> usize {
@@ -143,37 +151,32 @@ impl OptimalAgent {
                 break;
             }
         }
-        return row * 3 +  col;
+        board.index_of(row, col)
     }
 
     fn place_in_diagonal(&self, i: u8, board: &TicTacToeBoard) -> usize {
-        match i {
-            0 => {
-                for i in &[0,4,8]  {
-                    if let TicTacToeCell::Empty = board[*i] {
-                        return *i;
-                    }
-                }
-                panic!("Sanity failure in agents::optimal_agent::place_in_diagonal");
-            }
-            1 => {
-                for i in &[2,4,6]  {
-                    if let TicTacToeCell::Empty = board[*i] {
-                        return *i;
-                    }
-                }
+        let last = board.cols() - 1;
+        let cells = match i {
+            0 => board.diagonal_iter(0, 0),
+            1 => board.anti_diagonal_iter(0, last),
+            _ => panic!("Invalid input to agents::optimal_agent::place_in_diagonal"),
+        };
 
-                panic!("Sanity failure in agents::optimal_agent::place_in_diagonal");
+        for (row, val) in cells.enumerate() {
+            if let TicTacToeCell::Empty = val {
+                let col = if i == 0 { row } else { last - row };
+                return board.index_of(row, col);
             }
-            _ => panic!("Invalid input to agents::optimal_agent::place_in_diagonal"),
         }
+        panic!("Sanity failure in agents::optimal_agent::place_in_diagonal");
     }
 
     fn place_anywhere(&self, board: &TicTacToeBoard) -> usize {
-        if let TicTacToeCell::Empty = board[4] {
-            4
+        let centre = centre_of(board);
+        if let TicTacToeCell::Empty = board[centre] {
+            centre
         } else {
-            for i in 0..9 {
+            for i in 0..board.len() {
                 if let TicTacToeCell::Empty = board[i] {
                     return i;
                 }
@@ -198,11 +201,11 @@ impl super::Agent for OptimalAgent {
         }
 
         // first - always prioritize center.
-        if board[4] == TicTacToeCell::Empty {
-            return 4;
+        let centre = centre_of(board);
+        if board[centre] == TicTacToeCell::Empty {
+            return centre;
         }
 
-        let opponent_piece = if self.0 == TicTacToeCell::X { TicTacToeCell::O } else { TicTacToeCell::X };
 
         // represents     row or col or diag, num, how much
         // for spec - 0 is row, 1 is col, 2 is diag
@@ -210,22 +213,24 @@ impl super::Agent for OptimalAgent {
         let mut max : Option<(i8,usize)> = None;
 
 
-        for i in 0..3 {
-            if let Some((player, count)) = get_majority(board.row_iter(i)) {
-                if count >= 2 {
+        // a line one short of k is about to be won by someone - finish or block it
+        let threatening = board.k() - 1;
+
+        for i in 0..board.rows() {
+            if let Some((_, count)) = get_majority(board.row_iter(i)) {
+                if count >= threatening {
                     return self.place_in_row(i, board);
                 }
 
                 if max.is_none() {
-                    println!("Updating max");
                     max = Some((0, i));
                 }
             }
         }
 
-        for i in 0..3 {
-            if let Some((player, count)) = get_majority(board.col_iter(i)) {
-                if count >= 2 {
+        for i in 0..board.cols() {
+            if let Some((_, count)) = get_majority(board.col_iter(i)) {
+                if count >= threatening {
                     return self.place_in_col(i, board);
                 }
                 if max.is_none() {
@@ -235,8 +240,8 @@ impl super::Agent for OptimalAgent {
             }
         }
 
-        if let Some((player, count)) = get_left_diagonal_majority(board) {
-            if count >= 2 {
+        if let Some((_, count)) = get_left_diagonal_majority(board).filter(|_| has_diagonals(board)) {
+            if count >= threatening {
                 return self.place_in_diagonal(0, board);
             }
             if max.is_none() {
@@ -244,8 +249,8 @@ impl super::Agent for OptimalAgent {
             }
         }
 
-        if let Some((player, count)) = get_right_diagonal_majority(board) {
-            if count >= 2 {
+        if let Some((_, count)) = get_right_diagonal_majority(board).filter(|_| has_diagonals(board)) {
+            if count >= threatening {
                 return self.place_in_diagonal(1, board);
             }
             if max.is_none() {
@@ -255,7 +260,6 @@ impl super::Agent for OptimalAgent {
 
 
 
-        println!("max is {:?}", max);
         if let Some((spec, ind)) = max {
             // first, if any opponent paths are near done, block them
             match spec {
@@ -269,4 +273,3 @@ impl super::Agent for OptimalAgent {
         }
     }
 }
-
@@ -1,160 +1,171 @@
 use std::ops::{Index, IndexMut};
 use std::fmt;
 
-enum TicTacToeIterType {
-    IterRow,
-    IterCol,
-    IterAll
+/// Walks `length` cells of a board starting at `start`, moving `step`
+/// indices each time.
+pub struct TicTacToeIter<'a> {
+    board : &'a TicTacToeBoard,
+    next : usize,
+    step : usize,
+    remaining : usize
 }
 
-pub struct TicTacToeIter<'a>(&'a TicTacToeBoard, TicTacToeIterType, Option<usize>);
-
 
 
 impl<'a> Iterator for TicTacToeIter<'a> {
     type Item = &'a TicTacToeCell;
     fn next(&mut self) -> Option<&'a TicTacToeCell> {
-        match self.1 {
-            TicTacToeIterType::IterRow => {
-                if let Some(index) = self.2 {
-                    if index == 2 || index == 5 || index == 8 {
-                        self.2 = None;
-                    } else {
-                        self.2 = Some(index + 1);
-                    }
-                    Some(&self.0[index])
-                }
-                else {
-                    None
-                }
-            }
-            TicTacToeIterType::IterCol =>  {
-                if let Some(index) = self.2 {
-                    if index == 6 || index == 7 || index == 8 {
-                        self.2 = None;
-                    } else {
-                        self.2 = Some(index + 3);
-                    }
-                    Some(&self.0[index])
-                }
-                else {
-                    None
-                }
-            }
-            TicTacToeIterType::IterAll => {
-                if let Some(index) = self.2 {
-                    if index == 8 {
-                        self.2 = None;
-                    } else {
-                        self.2 = Some(index + 1);
-                    }
-                    Some(&self.0[index])
-                } else {
-                    None
-                }
-            }
+        if self.remaining == 0 {
+            return None;
         }
+
+        let index = self.next;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.next += self.step;
+        }
+        Some(&self.board[index])
     }
 }
 
 
 
-#[derive(Debug,Hash,Copy,Clone,PartialEq)]
+#[derive(Debug,Hash,Copy,Clone,PartialEq,Eq)]
 pub enum TicTacToeCell {
    X,
    O,
    Empty
 }
 
-#[derive(Debug,Hash,Copy,Clone)]
-pub struct TicTacToeBoard {
-    c1 : TicTacToeCell,
-    c2 : TicTacToeCell,
-    c3 : TicTacToeCell,
-
-    c4 : TicTacToeCell,
-    c5 : TicTacToeCell,
-    c6 : TicTacToeCell,
+impl TicTacToeCell {
+    /// The piece of the other player.
+    pub fn opponent(self) -> TicTacToeCell {
+        match self {
+            TicTacToeCell::X => TicTacToeCell::O,
+            TicTacToeCell::O => TicTacToeCell::X,
+            TicTacToeCell::Empty => panic!("An empty space has no opponent"),
+        }
+    }
+}
 
-    c7 : TicTacToeCell,
-    c8 : TicTacToeCell,
-    c9 : TicTacToeCell,
+/// An m×n board on which `k` in a row (horizontally, vertically or
+/// diagonally) wins. Cells are indexed row by row from the top left.
+#[derive(Debug,Hash,Clone,PartialEq,Eq)]
+pub struct TicTacToeBoard {
+    rows : usize,
+    cols : usize,
+    k : usize,
+    cells : Vec<TicTacToeCell>
 }
 
 impl TicTacToeBoard {
+    /// The classic 3x3 board, three in a row to win.
     pub fn new() -> Self {
-        TicTacToeBoard {
-            c1: TicTacToeCell::Empty,
-            c2: TicTacToeCell::Empty,
-            c3: TicTacToeCell::Empty,
+        TicTacToeBoard::with_size(3, 3, 3)
+    }
 
-            c4: TicTacToeCell::Empty,
-            c5: TicTacToeCell::Empty,
-            c6: TicTacToeCell::Empty,
+    /// An empty `rows`×`cols` board, `k` in a row to win, e.g. 15, 15, 5 for Gomoku.
+    pub fn with_size(rows : usize, cols : usize, k : usize) -> Self {
+        assert!(rows > 0 && cols > 0, "Board must have at least one cell");
+        assert!(k > 0 && (k <= rows || k <= cols), "Winning line must fit on the board");
 
-            c7: TicTacToeCell::Empty,
-            c8: TicTacToeCell::Empty,
-            c9: TicTacToeCell::Empty,
+        TicTacToeBoard {
+            rows,
+            cols,
+            k,
+            cells : vec![TicTacToeCell::Empty; rows * cols]
         }
     }
 
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// How many in a row win.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn index_of(&self, row : usize, col : usize) -> usize {
+        assert!(row < self.rows && col < self.cols, "Cell ({}, {}) is off the board", row, col);
+        row * self.cols + col
+    }
+
+    /// Returns the winner, `Some(TicTacToeCell::Empty)` while the game is
+    /// still going, or `None` for a draw.
     pub fn has_anyone_won(&self) -> Option<TicTacToeCell> {
-        // test rows
-        if      self[0] == self[1] &&  self[1] == self[2] && self[0] != TicTacToeCell::Empty {
-            Some(self[0])
-        } 
-        else if self[3] == self[4] &&  self[4] == self[5] && self[3] != TicTacToeCell::Empty  {
-            Some(self[3])
-        } 
-        else if self[6] == self[7] &&  self[7] == self[8] && self[6] != TicTacToeCell::Empty  {
-            Some(self[6])
+        for index in 0..self.cells.len() {
+            if self.cells[index] != TicTacToeCell::Empty && self.wins_through(index) {
+                return Some(self.cells[index]);
+            }
         }
 
-        // test columns
-        else if self[0] == self[3] &&  self[3] == self[6] && self[0] != TicTacToeCell::Empty {
-            Some(self[0])
-        } 
-        else if self[1] == self[4] &&  self[4] == self[7] && self[1] != TicTacToeCell::Empty  {
-            Some(self[1])
+        if self.cells.contains(&TicTacToeCell::Empty) {
+            Some(TicTacToeCell::Empty)
+        } else {
+            None
+        }
+    }
 
-        } 
-        else if self[2] == self[5] &&  self[5] == self[8] && self[2] != TicTacToeCell::Empty  {
-            Some(self[2])
+    /// Whether the piece at `index` is part of `k` in a row. Checking only the
+    /// cell just played is enough to tell whether that move won.
+    pub fn wins_through(&self, index : usize) -> bool {
+        let piece = self.cells[index];
+        if piece == TicTacToeCell::Empty {
+            return false;
         }
 
-        // test diagonals
-
-        else if self[0] == self[4] &&  self[4] == self[8] && self[0] != TicTacToeCell::Empty {
-            Some(self[0])
-        } 
-        else if self[2] == self[4] &&  self[4] == self[6] && self[2] != TicTacToeCell::Empty {
-            Some(self[2])
-        }  else {
-            for cell in self.iter() {
-                if let TicTacToeCell::Empty = cell {
-                    return Some(TicTacToeCell::Empty);
-                }
+        let (row, col) = ((index / self.cols) as isize, (index % self.cols) as isize);
+        let run = |row_step : isize, col_step : isize| {
+            let mut count = 0;
+            let (mut r, mut c) = (row + row_step, col + col_step);
+            while r >= 0 && c >= 0 && r < self.rows as isize && c < self.cols as isize
+                && self.cells[r as usize * self.cols + c as usize] == piece {
+                count += 1;
+                r += row_step;
+                c += col_step;
             }
-            None
-        }
- 
+            count
+        };
 
+        [(0, 1), (1, 0), (1, 1), (1, -1)].iter()
+            .any(|&(row_step, col_step)| 1 + run(row_step, col_step) + run(-row_step, -col_step) >= self.k)
     }
 
-    pub fn row_iter<'a>(&'a self, index : usize) ->  TicTacToeIter<'a> {
+    pub fn row_iter(&self, index : usize) -> TicTacToeIter<'_> {
+        assert!(index < self.rows, "Row iterator must be called on valid row");
+        TicTacToeIter { board : self, next : index * self.cols, step : 1, remaining : self.cols }
+    }
 
-        assert!(index < 3, "Row iterator must be called on valid row");
-        TicTacToeIter(&self, TicTacToeIterType::IterRow, Some(index * 3usize))
+    pub fn col_iter(&self, index : usize) -> TicTacToeIter<'_> {
+        assert!(index < self.cols, "Column iterator must be called on valid column");
+        TicTacToeIter { board : self, next : index, step : self.cols, remaining : self.rows }
     }
 
-    pub fn col_iter<'a>(&'a self, index : usize) ->  TicTacToeIter<'a> {
-        assert!(index < 3, "Row iterator must be called on valid row");
-        TicTacToeIter(&self, TicTacToeIterType::IterCol, Some(index))
+    /// The diagonal running down and to the right from `(row, col)`.
+    pub fn diagonal_iter(&self, row : usize, col : usize) -> TicTacToeIter<'_> {
+        let start = self.index_of(row, col);
+        let length = (self.rows - row).min(self.cols - col);
+        TicTacToeIter { board : self, next : start, step : self.cols + 1, remaining : length }
     }
 
+    /// The diagonal running down and to the left from `(row, col)`.
+    pub fn anti_diagonal_iter(&self, row : usize, col : usize) -> TicTacToeIter<'_> {
+        let start = self.index_of(row, col);
+        let length = (self.rows - row).min(col + 1);
+        TicTacToeIter { board : self, next : start, step : self.cols - 1, remaining : length }
+    }
 
-    pub fn iter<'a>(&'a self) ->  TicTacToeIter<'a> {
-        TicTacToeIter(&self, TicTacToeIterType::IterAll, Some(0))
+    pub fn iter(&self) -> TicTacToeIter<'_> {
+        TicTacToeIter { board : self, next : 0, step : 1, remaining : self.cells.len() }
     }
 
 
@@ -166,20 +177,8 @@ impl Index<usize> for TicTacToeBoard {
     type Output = TicTacToeCell;
 
     fn index(&self, index : usize) -> &Self::Output {
-        assert!(index >= 0 && index < 9);
-        match index {
-            0 => &self.c1,
-            1 => &self.c2,
-            2 => &self.c3,
-            3 => &self.c4,
-            4 => &self.c5,
-            5 => &self.c6,
-            6 => &self.c7,
-            7 => &self.c8,
-            8 => &self.c9,
-            _ => panic!("Cell index out of bounds")
- 
-        }
+        assert!(index < self.cells.len(), "Cell index out of bounds");
+        &self.cells[index]
     }
 
 }
@@ -188,29 +187,17 @@ impl Index<usize> for TicTacToeBoard {
 impl IndexMut<usize> for TicTacToeBoard {
 
     fn index_mut(&mut self, index : usize) -> &mut TicTacToeCell {
-        match index {
-            0 => &mut self.c1,
-            1 => &mut self.c2,
-            2 => &mut self.c3,
-            3 => &mut self.c4,
-            4 => &mut self.c5,
-            5 => &mut self.c6,
-            6 => &mut self.c7,
-            7 => &mut self.c8,
-            8 => &mut self.c9,
-            _ => panic!("Cell index out of bounds")
- 
-        }
- 
+        assert!(index < self.cells.len(), "Cell index out of bounds");
+        &mut self.cells[index]
     }
 }
 
 impl fmt::Display for TicTacToeCell {
      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
            match *self {
-                TicTacToeCell::X =>  write!(f, "{}", "X"),
-                TicTacToeCell::O =>  write!(f, "{}", "O"),
-                TicTacToeCell::Empty =>  write!(f, "{}", "_"),
+                TicTacToeCell::X =>  write!(f, "X"),
+                TicTacToeCell::O =>  write!(f, "O"),
+                TicTacToeCell::Empty =>  write!(f, "_"),
            }
      }
 }
@@ -219,14 +206,14 @@ impl fmt::Display for TicTacToeCell {
 impl fmt::Display for TicTacToeBoard {
 
      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-         write!(f, "[");
-            for i in 0..9 {
-                 write!(f, "{}", self[i]);
-                 if i < 8 {
-                     if i == 2 || i == 5 {
-                         write!(f, "; ");
+         write!(f, "[")?;
+            for i in 0..self.cells.len() {
+                 write!(f, "{}", self[i])?;
+                 if i + 1 < self.cells.len() {
+                     if (i + 1) % self.cols == 0 {
+                         write!(f, "; ")?;
                      } else  {
-                         write!(f, ", ");
+                         write!(f, ", ")?;
                      }
                  }
             }
@@ -236,3 +223,62 @@ impl fmt::Display for TicTacToeBoard {
 }
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(board : &mut TicTacToeBoard, piece : TicTacToeCell, cells : &[(usize, usize)]) {
+        for &(row, col) in cells {
+            let index = board.index_of(row, col);
+            board[index] = piece;
+        }
+    }
+
+    #[test]
+    fn classic_board_is_unchanged() {
+        let mut board = TicTacToeBoard::new();
+        assert_eq!(board.to_string(), "[_, _, _; _, _, _; _, _, _]");
+        assert_eq!(board.has_anyone_won(), Some(TicTacToeCell::Empty));
+
+        play(&mut board, TicTacToeCell::O, &[(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(board.has_anyone_won(), Some(TicTacToeCell::O));
+        assert_eq!(board.anti_diagonal_iter(0, 2).cloned().collect::<Vec<_>>(), vec![TicTacToeCell::O; 3]);
+    }
+
+    #[test]
+    fn iterators_walk_rectangular_boards() {
+        let mut board = TicTacToeBoard::with_size(3, 4, 3);
+        play(&mut board, TicTacToeCell::X, &[(1, 0), (1, 3), (2, 2)]);
+
+        assert_eq!(board.row_iter(1).filter(|&&cell| cell == TicTacToeCell::X).count(), 2);
+        assert_eq!(board.col_iter(3).count(), 3);
+        assert_eq!(board.diagonal_iter(0, 1).count(), 3);
+        assert_eq!(board.diagonal_iter(0, 0).nth(2), Some(&TicTacToeCell::X));
+        assert_eq!(board.anti_diagonal_iter(0, 1).count(), 2);
+        assert_eq!(board.iter().count(), 12);
+    }
+
+    #[test]
+    fn k_in_a_row_wins_anywhere() {
+        let mut board = TicTacToeBoard::with_size(15, 15, 5);
+        play(&mut board, TicTacToeCell::X, &[(10, 14), (11, 13), (12, 12), (13, 11)]);
+        assert_eq!(board.has_anyone_won(), Some(TicTacToeCell::Empty));
+
+        play(&mut board, TicTacToeCell::X, &[(14, 10)]);
+        assert_eq!(board.has_anyone_won(), Some(TicTacToeCell::X));
+        assert!(board.wins_through(board.index_of(12, 12)));
+
+        // four in a row does not win 4x4 with k = 4 if it wraps around the edge
+        let mut board = TicTacToeBoard::with_size(4, 4, 4);
+        play(&mut board, TicTacToeCell::O, &[(0, 2), (0, 3), (1, 0), (1, 1)]);
+        assert_eq!(board.has_anyone_won(), Some(TicTacToeCell::Empty));
+    }
+
+    #[test]
+    fn full_board_without_line_is_a_draw() {
+        let mut board = TicTacToeBoard::new();
+        play(&mut board, TicTacToeCell::X, &[(0, 0), (0, 2), (1, 0), (2, 1)]);
+        play(&mut board, TicTacToeCell::O, &[(0, 1), (1, 1), (1, 2), (2, 0), (2, 2)]);
+        assert_eq!(board.has_anyone_won(), None);
+    }
+}
@@ -1,6 +1,8 @@
 use board::{TicTacToeCell, TicTacToeBoard};
 use agents::Agent;
+use record::Record;
 
+use std::fs;
 use std::io;
 
 pub struct IOEngine<T : Agent> {
@@ -8,6 +10,105 @@ pub struct IOEngine<T : Agent> {
    board: TicTacToeBoard,
    player_type: TicTacToeCell,
    opponent_type: TicTacToeCell,
+   record: Record,
+}
+
+fn print_board(board : &TicTacToeBoard) {
+    for i in 0..board.rows() {
+        print!("    ");
+        for j in 0..board.cols() {
+            print!("{}", board[board.index_of(i, j)]);        
+            if j + 1 != board.cols() {
+                print!(" ");
+            } else {
+                println!();
+            }
+        }
+    }
+}
+
+/// Reads a 1-based coordinate up to `limit` from stdin, asking again until
+/// it gets one, or `None` once stdin is closed.
+fn read_coordinate(name : &str, limit : usize) -> Option<usize> {
+    println!("Which {}[1-{}]:", name, limit);
+    loop {
+        let mut input = String::new();     
+        let read = io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read from stdin");
+        if read == 0 {
+            return None;
+        }
+        let input = input.trim();
+        match input.parse::<usize>() {
+            Ok(i) => {
+                if i > 0 && i <= limit {
+                    return Some(i - 1);
+                } else {
+                    println!("Err: i({}) is not within the required range ([1-{}])", i, limit);
+                }
+            },
+            Err(..) => println!("Err: {} is not recognized as a valid integer.\nPlease try again:", input),
+        }
+    }
+}
+
+/// A person at the terminal, for engines that take an `Agent` on each side.
+/// `try_next_move` gives up if stdin is closed before a move is chosen, and
+/// `get_next_move` panics.
+pub struct HumanPlayer;
+
+impl Agent for HumanPlayer {
+    fn get_next_move(&mut self, board : &TicTacToeBoard) -> usize {
+        self.try_next_move(board).expect("Standard input closed before the player chose a move")
+    }
+
+    fn try_next_move(&mut self, board : &TicTacToeBoard) -> Option<usize> {
+        println!("The board is as follows:");
+        print_board(board);
+        loop {
+            println!("Please select your move.");
+            let row = read_coordinate("row", board.rows())?;
+            let col = read_coordinate("col", board.cols())?;
+
+            let cell = board.index_of(row, col);
+            if board[cell] == TicTacToeCell::Empty {
+                return Some(cell);
+            }
+            println!("That cell[{},{}] is not empty", row + 1, col + 1);
+        }
+    }
+}
+
+/// Steps through a recorded game, showing the board after each move and
+/// waiting for enter before the next. Entering `q` stops early.
+pub fn replay(record : &Record) -> Result<(), String> {
+    let positions = record.positions()?;
+    println!("{} (X) against {} (O), {} moves", record.x_player, record.o_player, record.moves.len());
+    if !record.setup.is_empty() {
+        println!("Starting from:");
+        print_board(&record.starting_position()?);
+    }
+
+    for (number, (&(piece, cell), board)) in record.moves.iter().zip(positions.iter()).enumerate() {
+        println!("Move {}: {} takes cell {}", number + 1, piece, cell);
+        print_board(board);
+
+        if number + 1 < positions.len() {
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).map_err(|e| e.to_string())?;
+            if input.trim() == "q" {
+                return Ok(());
+            }
+        }
+    }
+
+    match record.result {
+        Some(TicTacToeCell::Empty) => println!("The game was not finished"),
+        Some(winner) => println!("{} won", winner),
+        None => println!("It was a draw!"),
+    }
+    Ok(())
 }
 
 
@@ -19,66 +120,58 @@ impl<T : Agent> IOEngine<T> {
             TicTacToeCell::Empty => panic!("Player can not be represented by an empty space"),
         };
 
+        let (x_player, o_player) = if player == TicTacToeCell::X { ("human", "agent") } else { ("agent", "human") };
+        let record = Record::new(&board, x_player, o_player);
 
         IOEngine {
             opponent: agent,
-            board: board,
+            board,
             player_type: player,
-            opponent_type: opponent_type,
+            opponent_type,
+            record,
         }
     }
 
+    /// Picks up a recorded game where it left off, rejecting invalid records.
+    pub fn load(player : TicTacToeCell, agent : T, record : Record) -> Result<Self, String> {
+        let board = record.validate()?;
+        let mut engine = IOEngine::new(player, agent, TicTacToeBoard::with_size(record.rows, record.cols, record.k));
+        engine.board = board;
+        engine.record = record;
+        Ok(engine)
+    }
 
-    pub fn player_turn(&mut self) {
-        loop {
-           println!("Please select your move.\nWhich row[1-3]:"); 
-           let mut row = None;
-
-           while row.is_none() {
-                let mut input = String::new();     
-                io::stdin()
-                    .read_line(&mut input);
-                let input = input.trim();
-                match input.parse::<usize>() {
-                    Ok(i) => {
-                        if i > 0 && i < 4 {
-                            row = Some(i - 1);
-                        } else {
-                            println!("Err: i({}) is not within the required range ([1-3])", i);
-                        }
-                    },
-                    Err(..) => println!("Err: {} is not recognized as a valid integer.\nPlease try again:", input),
-                }
-           }
-           let row = row.unwrap();
-
-           println!("Which col[1-3]:");
-           let mut col  = None;
-        
-           while col.is_none() {
-                let mut input = String::new();     
-                io::stdin()
-                    .read_line(&mut input);
-                let input = input.trim();
-                match input.parse::<usize>() {
-                    Ok(i) => {
-                        if i > 0 && i < 4 {
-                            col = Some(i - 1);
-                        } else {
-                            println!("Err: i({}) is not within the required range ([1-3])", i);
-                        }
-                    },
-                    Err(..) => println!("Err: {} is not recognized as a valid integer.\nPlease try again:", input),
-                }
- 
-           }
-           let col = col.unwrap();
+    /// Names the opponent in the game record.
+    pub fn set_opponent_name(&mut self, name : &str) {
+        match self.opponent_type {
+            TicTacToeCell::X => self.record.x_player = name.to_string(),
+            _ => self.record.o_player = name.to_string(),
+        }
+    }
+
+    pub fn save(&self, path : &str) -> io::Result<()> {
+        fs::write(path, self.record.to_string())
+    }
 
-           if self.board[row * 3 + col] == TicTacToeCell::Empty {
-                self.board[row * 3 + col] = self.player_type; 
-               return;
+
+    /// Returns false if the player left before choosing a move.
+    pub fn player_turn(&mut self) -> bool {
+        loop {
+           println!("Please select your move.");
+           let row = read_coordinate("row", self.board.rows());
+           let col = row.and_then(|_| read_coordinate("col", self.board.cols()));
+           let (row, col) = match (row, col) {
+               (Some(row), Some(col)) => (row, col),
+               _ => return false,
+           };
+
+           let cell = self.board.index_of(row, col);
+           if self.board[cell] == TicTacToeCell::Empty {
+                self.board[cell] = self.player_type; 
+                self.record.push(self.player_type, cell, &self.board);
+               return true;
            } else {
-                println!("That cell[{},{}] is not empty", row,col);
+                println!("That cell[{},{}] is not empty", row + 1, col + 1);
            }
         }
     }
@@ -88,31 +181,31 @@ impl<T : Agent> IOEngine<T> {
         assert!(self.board[pos] == TicTacToeCell::Empty, "Opponent selects non empty cell");
         println!("The opponent selects cell {}", pos);
         self.board[pos] = self.opponent_type;
+        self.record.push(self.opponent_type, pos, &self.board);
     }
 
     pub fn print_state(&self) {
         println!("The board is as follows:");
-        for i in 0..3 {
-            print!("    ");
-            for j in 0..3 {
-                print!("{}", self.board[i * 3 + j]);        
-                if j != 2 {
-                    print!(" ");
-                } else {
-                    print!("\n");
-                }
-            }
-        }
+        print_board(&self.board);
+    }
+
+    /// Continues a loaded game with whoever is due to move.
+    pub fn resume(&mut self) {
+        let opponent_next = self.record.next_to_move() == Some(self.opponent_type);
+        self.execute(opponent_next);
     }
 
     pub fn execute(&mut self, opponent_first : bool) {
-        if opponent_first {
+        if opponent_first && self.board.has_anyone_won() == Some(TicTacToeCell::Empty) {
             self.opponent_turn();
         }
         self.print_state();
         let mut win_status = self.board.has_anyone_won();
         while let Some(TicTacToeCell::Empty) = win_status {
-            self.player_turn();
+            if !self.player_turn() {
+                println!("Game abandoned.");
+                return;
+            }
 
             win_status = self.board.has_anyone_won();
             if let Some(state) = win_status {
@@ -1,4 +1,7 @@
 mod io;
+mod network;
+mod tournament;
 
 pub use engine::io::*;
-
+pub use engine::network::*;
+pub use engine::tournament::*;
@@ -0,0 +1,489 @@
+use board::{TicTacToeCell, TicTacToeBoard};
+use agents::Agent;
+use record::Record;
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// The most rows or columns a joining player accepts, so that a host can't
+/// make it allocate an arbitrarily large board.
+pub const MAX_SIDE : usize = 100;
+
+/// One line of the network protocol.
+///
+/// The host opens with `HELLO <rows> <cols> <k> <your piece> <first piece>`,
+/// after which each side sends `MOVE <cell>` on its turn. Either side may send
+/// `ERROR <reason>` when the other breaks the rules, or `QUIT` to leave; both
+/// end the game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Hello { rows : usize, cols : usize, k : usize, piece : TicTacToeCell, first : TicTacToeCell },
+    Move(usize),
+    Error(String),
+    Quit
+}
+
+fn parse_piece(text : &str) -> Result<TicTacToeCell, String> {
+    match text {
+        "X" => Ok(TicTacToeCell::X),
+        "O" => Ok(TicTacToeCell::O),
+        _ => Err(format!("{} is not a piece", text)),
+    }
+}
+
+fn parse_number(text : &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("{} is not a number", text))
+}
+
+impl Message {
+    pub fn parse(line : &str) -> Result<Message, String> {
+        let mut words = line.split_whitespace();
+        let message = match words.next() {
+            Some("HELLO") => {
+                let fields : Vec<&str> = words.by_ref().take(5).collect();
+                if fields.len() != 5 {
+                    return Err(format!("HELLO needs five fields: {}", line));
+                }
+                Message::Hello {
+                    rows : parse_number(fields[0])?,
+                    cols : parse_number(fields[1])?,
+                    k : parse_number(fields[2])?,
+                    piece : parse_piece(fields[3])?,
+                    first : parse_piece(fields[4])?
+                }
+            },
+            Some("MOVE") => Message::Move(parse_number(words.next().ok_or("MOVE needs a cell")?)?),
+            Some("ERROR") => return Ok(Message::Error(line.trim()["ERROR".len()..].trim().to_string())),
+            Some("QUIT") => Message::Quit,
+            _ => return Err(format!("unknown message {}", line)),
+        };
+
+        match words.next() {
+            Some(extra) => Err(format!("unexpected {} at the end of {}", extra, line)),
+            None => Ok(message),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Message::Hello { rows, cols, k, piece, first } => write!(f, "HELLO {} {} {} {} {}", rows, cols, k, piece, first),
+            Message::Move(cell) => write!(f, "MOVE {}", cell),
+            Message::Error(ref reason) => write!(f, "ERROR {}", reason),
+            Message::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    /// Nothing arrived from the other side within the timeout.
+    Timeout,
+    Disconnected,
+    /// The other side sent something that isn't a valid message at this point.
+    Protocol(String),
+    /// The other side played an occupied or out of range cell.
+    RemoteIllegalMove(String),
+    /// The local agent chose an occupied or out of range cell.
+    LocalIllegalMove(usize),
+    /// The other side reported an error and ended the game.
+    Peer(String),
+    /// The local agent gave up instead of moving.
+    LocalQuit,
+    /// The game would start from a board with pieces on it, which `HELLO`
+    /// can't describe.
+    BoardNotEmpty,
+    PeerQuit
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(error : io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => NetworkError::Timeout,
+            io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof => NetworkError::Disconnected,
+            _ => NetworkError::Io(error),
+        }
+    }
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NetworkError::Io(ref error) => write!(f, "{}", error),
+            NetworkError::Timeout => write!(f, "timed out waiting for the other player"),
+            NetworkError::Disconnected => write!(f, "the other player disconnected"),
+            NetworkError::Protocol(ref reason) => write!(f, "protocol error: {}", reason),
+            NetworkError::RemoteIllegalMove(ref reason) => write!(f, "the other player made an illegal move: {}", reason),
+            NetworkError::LocalIllegalMove(cell) => write!(f, "our player made an illegal move to {}", cell),
+            NetworkError::Peer(ref reason) => write!(f, "the other player reported: {}", reason),
+            NetworkError::BoardNotEmpty => write!(f, "network games start from an empty board"),
+            NetworkError::PeerQuit => write!(f, "the other player quit"),
+            NetworkError::LocalQuit => write!(f, "our player quit"),
+        }
+    }
+}
+
+/// A line-based connection to the other player.
+pub struct Connection {
+    reader : BufReader<TcpStream>,
+    writer : TcpStream
+}
+
+impl Connection {
+    /// Wraps `stream`, failing reads that wait longer than `timeout`.
+    pub fn new(stream : TcpStream, timeout : Option<Duration>) -> Result<Self, NetworkError> {
+        stream.set_read_timeout(timeout)?;
+        stream.set_nodelay(true)?;
+        let writer = stream.try_clone()?;
+        Ok(Connection { reader : BufReader::new(stream), writer })
+    }
+
+    pub fn send(&mut self, message : &Message) -> Result<(), NetworkError> {
+        writeln!(self.writer, "{}", message)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn receive(&mut self) -> Result<Message, NetworkError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NetworkError::Disconnected);
+        }
+        Message::parse(line.trim()).map_err(NetworkError::Protocol)
+    }
+}
+
+/// Plays a local `Agent` against whoever is on the other end of a connection.
+/// Both sides check every move, so a buggy or malicious peer can't corrupt
+/// the game.
+pub struct NetworkEngine<A : Agent> {
+    local : A,
+    local_type : TicTacToeCell,
+    first : TicTacToeCell,
+    board : TicTacToeBoard,
+    connection : Connection,
+    record : Record,
+    verbose : bool
+}
+
+impl<A : Agent> NetworkEngine<A> {
+    /// Waits for a player to connect to `listener` and tells them the board and
+    /// that they play the other piece to `local_type`. `board` must be empty.
+    pub fn host(listener : &TcpListener, local : A, local_type : TicTacToeCell, first : TicTacToeCell, board : TicTacToeBoard, timeout : Option<Duration>) -> Result<Self, NetworkError> {
+        assert!(local_type != TicTacToeCell::Empty && first != TicTacToeCell::Empty, "Players can not be represented by an empty space");
+        if board.iter().any(|&cell| cell != TicTacToeCell::Empty) {
+            return Err(NetworkError::BoardNotEmpty);
+        }
+
+        let (stream, _) = listener.accept()?;
+        let mut connection = Connection::new(stream, timeout)?;
+        connection.send(&Message::Hello { rows : board.rows(), cols : board.cols(), k : board.k(), piece : local_type.opponent(), first })?;
+        Ok(NetworkEngine::start(local, local_type, first, board, connection))
+    }
+
+    /// Connects to a host, building the local agent once the host has said
+    /// which piece it plays.
+    pub fn join<S : ToSocketAddrs, F : FnOnce(TicTacToeCell) -> A>(address : S, make_local : F, timeout : Option<Duration>) -> Result<Self, NetworkError> {
+        let stream = TcpStream::connect(address)?;
+        let mut connection = Connection::new(stream, timeout)?;
+
+        match connection.receive()? {
+            Message::Hello { rows, cols, k, piece, first } => {
+                if rows == 0 || cols == 0 || k == 0 || (k > rows && k > cols) {
+                    let reason = format!("{} in a row on a {}x{} board is not a game", k, rows, cols);
+                    let _ = connection.send(&Message::Error(reason.clone()));
+                    return Err(NetworkError::Protocol(reason));
+                }
+                if rows > MAX_SIDE || cols > MAX_SIDE {
+                    let reason = format!("a {}x{} board is larger than {}x{}", rows, cols, MAX_SIDE, MAX_SIDE);
+                    let _ = connection.send(&Message::Error(reason.clone()));
+                    return Err(NetworkError::Protocol(reason));
+                }
+                let board = TicTacToeBoard::with_size(rows, cols, k);
+                Ok(NetworkEngine::start(make_local(piece), piece, first, board, connection))
+            },
+            Message::Error(reason) => Err(NetworkError::Peer(reason)),
+            Message::Quit => Err(NetworkError::PeerQuit),
+            other => Err(NetworkError::Protocol(format!("expected HELLO but got {}", other))),
+        }
+    }
+
+    fn start(local : A, local_type : TicTacToeCell, first : TicTacToeCell, board : TicTacToeBoard, connection : Connection) -> Self {
+        let (x_player, o_player) = if local_type == TicTacToeCell::X { ("local", "remote") } else { ("remote", "local") };
+        let record = Record::new(&board, x_player, o_player);
+        NetworkEngine { local, local_type, first, board, connection, record, verbose : false }
+    }
+
+    /// Prints each move and the final board as the game goes.
+    pub fn set_verbose(&mut self, verbose : bool) {
+        self.verbose = verbose;
+    }
+
+    pub fn local_type(&self) -> TicTacToeCell {
+        self.local_type
+    }
+
+    pub fn board(&self) -> &TicTacToeBoard {
+        &self.board
+    }
+
+    pub fn record(&self) -> &Record {
+        &self.record
+    }
+
+    fn check_move(&self, cell : usize) -> Result<(), String> {
+        if cell >= self.board.len() {
+            Err(format!("cell {} is off the board", cell))
+        } else if self.board[cell] != TicTacToeCell::Empty {
+            Err(format!("cell {} is already taken", cell))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn apply(&mut self, piece : TicTacToeCell, cell : usize) {
+        self.board[cell] = piece;
+        self.record.push(piece, cell, &self.board);
+        if self.verbose {
+            println!("{} takes cell {}", piece, cell);
+        }
+    }
+
+    /// Plays the game to the end, returning the result with `has_anyone_won`
+    /// semantics.
+    pub fn play(&mut self) -> Result<Option<TicTacToeCell>, NetworkError> {
+        let mut to_move = self.first;
+
+        loop {
+            let status = self.board.has_anyone_won();
+            if status != Some(TicTacToeCell::Empty) {
+                if self.verbose {
+                    println!("{}", self.board);
+                }
+                return Ok(status);
+            }
+
+            if to_move == self.local_type {
+                let cell = match self.local.try_next_move(&self.board) {
+                    Some(cell) => cell,
+                    None => {
+                        let _ = self.connection.send(&Message::Quit);
+                        return Err(NetworkError::LocalQuit);
+                    }
+                };
+                if self.check_move(cell).is_err() {
+                    let _ = self.connection.send(&Message::Quit);
+                    return Err(NetworkError::LocalIllegalMove(cell));
+                }
+                self.connection.send(&Message::Move(cell))?;
+                self.apply(to_move, cell);
+            } else {
+                let cell = match self.connection.receive() {
+                    Ok(Message::Move(cell)) => cell,
+                    Ok(Message::Error(reason)) => return Err(NetworkError::Peer(reason)),
+                    Ok(Message::Quit) => return Err(NetworkError::PeerQuit),
+                    Ok(other) => {
+                        let reason = format!("expected MOVE but got {}", other);
+                        let _ = self.connection.send(&Message::Error(reason.clone()));
+                        return Err(NetworkError::Protocol(reason));
+                    },
+                    Err(error) => {
+                        let _ = self.connection.send(&Message::Error(error.to_string()));
+                        return Err(error);
+                    }
+                };
+
+                if let Err(reason) = self.check_move(cell) {
+                    let _ = self.connection.send(&Message::Error(reason.clone()));
+                    return Err(NetworkError::RemoteIllegalMove(reason));
+                }
+                self.apply(to_move, cell);
+            }
+
+            to_move = to_move.opponent();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agents::minimax_agent::MinimaxAgent;
+    use agents::optimal_agent::OptimalAgent;
+    use std::thread;
+
+    const TIMEOUT : Option<Duration> = Some(Duration::from_secs(5));
+
+    fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    /// Connects a hand-driven client, returning it after reading the greeting.
+    fn raw_client(address : &str) -> (BufReader<TcpStream>, TcpStream) {
+        let stream = TcpStream::connect(address).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut hello = String::new();
+        reader.read_line(&mut hello).unwrap();
+        assert_eq!(hello, "HELLO 3 3 3 O O\n");
+        (reader, stream)
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let messages = vec![
+            Message::Hello { rows : 15, cols : 15, k : 5, piece : TicTacToeCell::O, first : TicTacToeCell::X },
+            Message::Move(112),
+            Message::Error("cell 4 is already taken".to_string()),
+            Message::Quit
+        ];
+        for message in messages {
+            assert_eq!(Message::parse(&message.to_string()), Ok(message));
+        }
+
+        assert!(Message::parse("MOVE").is_err());
+        assert!(Message::parse("MOVE 4 5").is_err());
+        assert!(Message::parse("HELLO 3 3 3 O").is_err());
+        assert!(Message::parse("PASS").is_err());
+    }
+
+    #[test]
+    fn plays_a_full_game_over_localhost() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let mut engine = NetworkEngine::join(address.as_str(), MinimaxAgent::new, TIMEOUT).unwrap();
+            assert_eq!(engine.local_type(), TicTacToeCell::O);
+            let result = engine.play().unwrap();
+            (result, engine.board().clone())
+        });
+
+        let mut host = NetworkEngine::host(&listener, OptimalAgent::new(TicTacToeCell::X), TicTacToeCell::X, TicTacToeCell::X, TicTacToeBoard::new(), TIMEOUT).unwrap();
+        let result = host.play().unwrap();
+        let (client_result, client_board) = client.join().unwrap();
+
+        assert_eq!(result, client_result);
+        assert_eq!(host.board(), &client_board);
+        assert_ne!(result, Some(TicTacToeCell::X));
+        assert_eq!(host.record().validate().unwrap(), client_board);
+    }
+
+    #[test]
+    fn only_hosts_games_from_an_empty_board() {
+        let (listener, _) = listen();
+        let mut board = TicTacToeBoard::new();
+        board[4] = TicTacToeCell::X;
+        let host = NetworkEngine::host(&listener, OptimalAgent::new(TicTacToeCell::X), TicTacToeCell::X, TicTacToeCell::O, board, TIMEOUT);
+        match host.err() {
+            Some(NetworkError::BoardNotEmpty) => (),
+            other => panic!("Expected the board to be refused, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_illegal_and_malformed_moves() {
+        for &(line, expected) in &[("MOVE 9\n", "ERROR cell 9 is off the board\n"), ("JUMP 4\n", "ERROR protocol error: unknown message JUMP 4\n")] {
+            let (listener, address) = listen();
+            let client = thread::spawn(move || {
+                let (mut reader, mut stream) = raw_client(&address);
+                stream.write_all(line.as_bytes()).unwrap();
+                let mut reply = String::new();
+                reader.read_line(&mut reply).unwrap();
+                reply
+            });
+
+            let mut host = NetworkEngine::host(&listener, OptimalAgent::new(TicTacToeCell::X), TicTacToeCell::X, TicTacToeCell::O, TicTacToeBoard::new(), TIMEOUT).unwrap();
+            let error = host.play().unwrap_err();
+            assert_eq!(client.join().unwrap(), expected);
+            match error {
+                NetworkError::RemoteIllegalMove(_) | NetworkError::Protocol(_) => (),
+                other => panic!("Unexpected error {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn refuses_oversized_boards() {
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            writeln!(&stream, "HELLO 1000000 1000000 5 O X").unwrap();
+            let mut reply = String::new();
+            reader.read_line(&mut reply).unwrap();
+            reply
+        });
+
+        let joined = NetworkEngine::join(address.as_str(), OptimalAgent::new, TIMEOUT);
+        assert_eq!(host.join().unwrap(), "ERROR a 1000000x1000000 board is larger than 100x100\n");
+        match joined.err() {
+            Some(NetworkError::Protocol(_)) => (),
+            other => panic!("Expected a protocol error, got {:?}", other),
+        }
+    }
+
+    /// Gives up on its first move, like a person closing the terminal.
+    struct Quitter;
+
+    impl Agent for Quitter {
+        fn get_next_move(&mut self, _board : &TicTacToeBoard) -> usize {
+            panic!("Quitter never moves")
+        }
+
+        fn try_next_move(&mut self, _board : &TicTacToeBoard) -> Option<usize> {
+            None
+        }
+    }
+
+    #[test]
+    fn sends_quit_when_the_local_player_gives_up() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let (mut reader, mut stream) = raw_client(&address);
+            stream.write_all(b"MOVE 4\n").unwrap();
+            let mut reply = String::new();
+            reader.read_line(&mut reply).unwrap();
+            reply
+        });
+
+        let mut host = NetworkEngine::host(&listener, Quitter, TicTacToeCell::X, TicTacToeCell::O, TicTacToeBoard::new(), TIMEOUT).unwrap();
+        match host.play() {
+            Err(NetworkError::LocalQuit) => (),
+            other => panic!("Expected the local player to quit, got {:?}", other),
+        }
+        assert_eq!(client.join().unwrap(), "QUIT\n");
+    }
+
+    #[test]
+    fn reports_disconnects_and_timeouts() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || drop(raw_client(&address)));
+        let mut host = NetworkEngine::host(&listener, OptimalAgent::new(TicTacToeCell::X), TicTacToeCell::X, TicTacToeCell::O, TicTacToeBoard::new(), TIMEOUT).unwrap();
+        client.join().unwrap();
+        match host.play() {
+            Err(NetworkError::Disconnected) => (),
+            other => panic!("Expected a disconnect, got {:?}", other),
+        }
+
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let (mut reader, _stream) = raw_client(&address);
+            let mut reply = String::new();
+            reader.read_line(&mut reply).unwrap();
+            reply
+        });
+        let timeout = Some(Duration::from_millis(100));
+        let mut host = NetworkEngine::host(&listener, OptimalAgent::new(TicTacToeCell::X), TicTacToeCell::X, TicTacToeCell::O, TicTacToeBoard::new(), timeout).unwrap();
+        match host.play() {
+            Err(NetworkError::Timeout) => (),
+            other => panic!("Expected a timeout, got {:?}", other),
+        }
+        assert_eq!(client.join().unwrap(), "ERROR timed out waiting for the other player\n");
+    }
+}
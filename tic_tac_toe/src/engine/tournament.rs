@@ -0,0 +1,228 @@
+use board::{TicTacToeCell, TicTacToeBoard};
+use agents::Agent;
+
+use std::fmt;
+
+/// Which of the two agents in a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    One,
+    Two
+}
+
+impl Side {
+    pub fn other(self) -> Side {
+        match self {
+            Side::One => Side::Two,
+            Side::Two => Side::One,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Side::One => 0,
+            Side::Two => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Win(Side),
+    Draw,
+    /// `side` asked for `cell`, which was occupied or off the board, and lost
+    /// the game.
+    IllegalMove { side : Side, cell : usize }
+}
+
+/// One finished game: who started, every move in order and how it ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub first : Side,
+    pub moves : Vec<(TicTacToeCell, usize)>,
+    pub result : GameResult
+}
+
+/// A side's record over a tournament.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Standing {
+    pub wins : u32,
+    pub draws : u32,
+    pub losses : u32,
+    /// Games lost by asking for an occupied or out of range cell, also counted in `losses`.
+    pub illegal_moves : u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResult {
+    pub games : Vec<GameRecord>,
+    pub standings : [Standing; 2]
+}
+
+impl TournamentResult {
+    pub fn standing(&self, side : Side) -> Standing {
+        self.standings[side.index()]
+    }
+}
+
+/// Plays two agents against each other without any input or output, for
+/// regression testing agents. Each agent keeps the piece it was built with;
+/// the board given is the starting position of every game.
+pub struct TournamentEngine<A : Agent, B : Agent> {
+    one : A,
+    two : B,
+    one_type : TicTacToeCell,
+    board : TicTacToeBoard
+}
+
+impl<A : Agent, B : Agent> TournamentEngine<A, B> {
+    pub fn new(one : A, one_type : TicTacToeCell, two : B, board : TicTacToeBoard) -> Self {
+        assert!(one_type != TicTacToeCell::Empty, "Agent can not be represented by an empty space");
+        TournamentEngine { one, two, one_type, board }
+    }
+
+    fn piece_of(&self, side : Side) -> TicTacToeCell {
+        match side {
+            Side::One => self.one_type,
+            Side::Two => self.one_type.opponent(),
+        }
+    }
+
+    pub fn play_game(&mut self, first : Side) -> GameRecord {
+        let mut board = self.board.clone();
+        let mut moves = vec![];
+        let mut to_move = first;
+
+        let result = loop {
+            match board.has_anyone_won() {
+                None => break GameResult::Draw,
+                Some(TicTacToeCell::Empty) => (),
+                Some(winner) => break GameResult::Win(if winner == self.one_type { Side::One } else { Side::Two }),
+            }
+
+            let cell = match to_move {
+                Side::One => self.one.get_next_move(&board),
+                Side::Two => self.two.get_next_move(&board),
+            };
+            let piece = self.piece_of(to_move);
+            moves.push((piece, cell));
+
+            if cell >= board.len() || board[cell] != TicTacToeCell::Empty {
+                break GameResult::IllegalMove { side : to_move, cell };
+            }
+            board[cell] = piece;
+            to_move = to_move.other();
+        };
+
+        GameRecord { first, moves, result }
+    }
+
+    /// Plays `games` games, side one starting the first and the sides taking
+    /// turns to start after that.
+    pub fn play_tournament(&mut self, games : u32) -> TournamentResult {
+        let mut standings = [Standing::default(); 2];
+        let mut records = Vec::with_capacity(games as usize);
+
+        for game in 0..games {
+            let first = if game % 2 == 0 { Side::One } else { Side::Two };
+            let record = self.play_game(first);
+
+            match record.result {
+                GameResult::Draw => {
+                    standings[0].draws += 1;
+                    standings[1].draws += 1;
+                },
+                GameResult::Win(side) => {
+                    standings[side.index()].wins += 1;
+                    standings[side.other().index()].losses += 1;
+                },
+                GameResult::IllegalMove { side, .. } => {
+                    standings[side.index()].losses += 1;
+                    standings[side.index()].illegal_moves += 1;
+                    standings[side.other().index()].wins += 1;
+                }
+            }
+            records.push(record);
+        }
+
+        TournamentResult { games : records, standings }
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(piece, cell)) in self.moves.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}{}", piece, cell)?;
+        }
+
+        match self.result {
+            GameResult::Win(side) => write!(f, " -> {:?} wins", side),
+            GameResult::Draw => write!(f, " -> draw"),
+            GameResult::IllegalMove { side, cell } => write!(f, " -> {:?} made an illegal move to {}", side, cell),
+        }
+    }
+}
+
+impl fmt::Display for Standing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} won, {} drawn, {} lost ({} illegal moves)", self.wins, self.draws, self.losses, self.illegal_moves)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agents::minimax_agent::MinimaxAgent;
+    use agents::optimal_agent::OptimalAgent;
+
+    /// Always asks for the same cell, occupied or not.
+    struct StubbornAgent(usize);
+
+    impl Agent for StubbornAgent {
+        fn get_next_move(&mut self, _board : &TicTacToeBoard) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn perfect_players_always_draw() {
+        let one = MinimaxAgent::new(TicTacToeCell::X);
+        let two = MinimaxAgent::new(TicTacToeCell::O);
+        let result = TournamentEngine::new(one, TicTacToeCell::X, two, TicTacToeBoard::new()).play_tournament(4);
+
+        assert_eq!(result.standing(Side::One), Standing { wins : 0, draws : 4, losses : 0, illegal_moves : 0 });
+        assert_eq!(result.standing(Side::Two).draws, 4);
+        assert_eq!(result.games.iter().map(|game| game.first).collect::<Vec<_>>(), vec![Side::One, Side::Two, Side::One, Side::Two]);
+        assert!(result.games.iter().all(|game| game.moves.len() == 9));
+    }
+
+    #[test]
+    fn starting_side_moves_first_with_its_own_piece() {
+        let one = OptimalAgent::new(TicTacToeCell::O);
+        let two = MinimaxAgent::new(TicTacToeCell::X);
+        let mut engine = TournamentEngine::new(one, TicTacToeCell::O, two, TicTacToeBoard::new());
+
+        let record = engine.play_game(Side::Two);
+        assert_eq!(record.moves[0].0, TicTacToeCell::X);
+        assert_eq!(record.moves[1].0, TicTacToeCell::O);
+        assert_ne!(record.result, GameResult::Win(Side::One));
+    }
+
+    #[test]
+    fn occupied_and_out_of_range_cells_forfeit_the_game() {
+        let mut engine = TournamentEngine::new(StubbornAgent(4), TicTacToeCell::X, MinimaxAgent::new(TicTacToeCell::O), TicTacToeBoard::new());
+        let result = engine.play_tournament(2);
+
+        assert_eq!(result.standing(Side::One), Standing { wins : 0, draws : 0, losses : 2, illegal_moves : 2 });
+        assert_eq!(result.standing(Side::Two).wins, 2);
+        assert_eq!(result.games[0].result, GameResult::IllegalMove { side : Side::One, cell : 4 });
+        assert_eq!(result.games[0].to_string(), "X4 O0 X4 -> One made an illegal move to 4");
+
+        let mut engine = TournamentEngine::new(StubbornAgent(9), TicTacToeCell::X, OptimalAgent::new(TicTacToeCell::O), TicTacToeBoard::new());
+        assert_eq!(engine.play_game(Side::One).result, GameResult::IllegalMove { side : Side::One, cell : 9 });
+    }
+}
@@ -1,16 +1,264 @@
 mod board;
 mod agents;
 mod engine;
+mod record;
+mod validity;
 
 use board::{TicTacToeBoard, TicTacToeCell};
-use engine::IOEngine;
+use engine::{HumanPlayer, IOEngine, NetworkEngine, NetworkError, TournamentEngine, Side};
+use record::Record;
+use agents::Agent;
 use agents::optimal_agent::OptimalAgent;
+use agents::minimax_agent::MinimaxAgent;
+use agents::mcts_agent::{Budget, MctsAgent};
 
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const USAGE : &str = "usage: tic_tac_toe [human|optimal|minimax|mcts] [options]
+
+  human|optimal|minimax|mcts
+                        the opponent to play against (default minimax on
+                        3x3 boards and mcts on others), or who plays from
+                        here in a network game; minimax only plays 3x3
+  --size ROWSxCOLS      board size (default 3x3), e.g. 15x15 for Gomoku
+  --k K                 how many in a row win (default 3)
+
+headless matches:
+  --versus AGENT        play the opponent against this agent instead of you
+  --games N             games to play, taking turns to start (default 10)
+  --log                 print every game's moves
+
+game records:
+  --save PATH           write the game to PATH when it ends
+  --load PATH           carry on the game recorded in PATH
+  --replay PATH         step through the game recorded in PATH
+
+network play:
+  --host ADDRESS        wait for a player on ADDRESS, e.g. 0.0.0.0:7878, and play X, moving first
+  --join ADDRESS        play the host at ADDRESS
+  --timeout-secs N      give up when the other player is silent this long (default 60)
+
+  --uva                 read UVA 10363 grids from stdin and say whether each is reachable
+
+mcts only:
+  --iterations N        playouts per move
+  --time-ms MS          thinking time per move (default 1000)
+  --seed N              random seed (default from the clock)";
+
+struct Options {
+    opponent : String,
+    rows : usize,
+    cols : usize,
+    k : usize,
+    budget : Budget,
+    seed : Option<usize>,
+    versus : Option<String>,
+    games : u32,
+    log : bool,
+    save : Option<String>,
+    load : Option<String>,
+    replay : Option<String>,
+    uva : bool,
+    host : Option<String>,
+    join : Option<String>,
+    timeout : Duration
+}
+
+fn is_agent(name : &str) -> bool {
+    ["human", "optimal", "minimax", "mcts"].contains(&name)
+}
+
+fn number<T : std::str::FromStr>(arg : &str, value : Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+    value.parse().map_err(|_| format!("{} is not a valid value for {}", value, arg))
+}
+
+fn parse_options<I : Iterator<Item=String>>(mut args : I) -> Result<Options, String> {
+    let classic = TicTacToeBoard::new();
+    let mut opponent = None;
+    let mut options = Options { opponent : String::new(), rows : classic.rows(), cols : classic.cols(), k : classic.k(),
+                                budget : Budget::Time(Duration::from_millis(1000)), seed : None,
+                                versus : None, games : 10, log : false,
+                                save : None, load : None, replay : None, uva : false,
+                                host : None, join : None, timeout : Duration::from_secs(60) };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            name if is_agent(name) => opponent = Some(arg),
+            "--size" => {
+                let value = args.next().ok_or("--size needs a value")?;
+                let mut parts = value.splitn(2, 'x').map(|part| part.parse::<usize>());
+                match (parts.next(), parts.next()) {
+                    (Some(Ok(rows)), Some(Ok(cols))) if rows > 0 && cols > 0 => {
+                        options.rows = rows;
+                        options.cols = cols;
+                    },
+                    _ => return Err(format!("{} is not a board size like 4x4", value)),
+                }
+            },
+            "--k" => options.k = number(&arg, args.next())?,
+            "--iterations" => match number(&arg, args.next())? {
+                0 => return Err("--iterations must be at least 1".to_string()),
+                iterations => options.budget = Budget::Iterations(iterations),
+            },
+            "--time-ms" => options.budget = Budget::Time(Duration::from_millis(number(&arg, args.next())?)),
+            "--seed" => options.seed = Some(number(&arg, args.next())?),
+            "--versus" => match args.next() {
+                Some(ref name) if is_agent(name) => options.versus = Some(name.clone()),
+                Some(name) => return Err(format!("{} is not an agent", name)),
+                None => return Err("--versus needs a value".to_string()),
+            },
+            "--games" => options.games = number(&arg, args.next())?,
+            "--log" => options.log = true,
+            "--save" => options.save = Some(args.next().ok_or("--save needs a value")?),
+            "--load" => options.load = Some(args.next().ok_or("--load needs a value")?),
+            "--replay" => options.replay = Some(args.next().ok_or("--replay needs a value")?),
+            "--uva" => options.uva = true,
+            "--host" => options.host = Some(args.next().ok_or("--host needs a value")?),
+            "--join" => options.join = Some(args.next().ok_or("--join needs a value")?),
+            "--timeout-secs" => options.timeout = Duration::from_secs(number(&arg, args.next())?),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    if options.host.is_some() && options.join.is_some() {
+        return Err("choose one of --host and --join".to_string());
+    }
+    if options.k == 0 || (options.k > options.rows && options.k > options.cols) {
+        return Err(format!("{} in a row doesn't fit on a {}x{} board", options.k, options.rows, options.cols));
+    }
+
+    // minimax searches the whole game tree, which only finishes on the classic board
+    let classic_size = (options.rows, options.cols) == (classic.rows(), classic.cols());
+    options.opponent = opponent.unwrap_or_else(|| if classic_size { "minimax" } else { "mcts" }.to_string());
+    if !classic_size && (options.opponent == "minimax" || options.versus.as_deref() == Some("minimax")) {
+        return Err(format!("minimax can't search a {}x{} board, only 3x3", options.rows, options.cols));
+    }
+    Ok(options)
+}
+
+fn make_agent(name : &str, piece : TicTacToeCell, options : &Options, seed : usize) -> Box<dyn Agent> {
+    match name {
+        "human" => Box::new(HumanPlayer),
+        "optimal" => Box::new(OptimalAgent::new(piece)),
+        "mcts" => Box::new(MctsAgent::new(piece, options.budget, seed)),
+        _ => Box::new(MinimaxAgent::new(piece)),
+    }
+}
+
+fn read_record(path : &str) -> Result<Record, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Record::parse(&text)
+}
+
+fn play_over_network(options : &Options, board : TicTacToeBoard, seed : usize) {
+    let timeout = Some(options.timeout);
+    let engine = match (&options.host, &options.join) {
+        (Some(address), _) => TcpListener::bind(address.as_str()).map_err(NetworkError::from).and_then(|listener| {
+            println!("Waiting for a player on {}", address);
+            let local = make_agent(&options.opponent, TicTacToeCell::X, options, seed);
+            NetworkEngine::host(&listener, local, TicTacToeCell::X, TicTacToeCell::X, board, timeout)
+        }),
+        (_, Some(address)) => NetworkEngine::join(address.as_str(), |piece| make_agent(&options.opponent, piece, options, seed), timeout),
+        _ => unreachable!("only called for network games"),
+    };
+
+    let mut engine = engine.unwrap_or_else(|e| {
+        eprintln!("Couldn't start the game: {}", e);
+        process::exit(1)
+    });
+    println!("Playing {} on a {}x{} board, {} in a row", engine.local_type(), engine.board().rows(), engine.board().cols(), engine.board().k());
+    engine.set_verbose(true);
+
+    let result = engine.play();
+    if let Some(ref path) = options.save {
+        if let Err(e) = fs::write(path, engine.record().to_string()) {
+            eprintln!("Couldn't save {}: {}", path, e);
+        }
+    }
+
+    match result {
+        Ok(Some(winner)) if winner == engine.local_type() => println!("Well done! You won!"),
+        Ok(Some(_)) => println!("Poor performance! Opponent won!"),
+        Ok(None) => println!("It was a draw!"),
+        Err(e) => {
+            eprintln!("Game abandoned: {}", e);
+            process::exit(1);
+        }
+    }
+}
 
 fn main() {
-    let board = TicTacToeBoard::new();
-    let opponent = OptimalAgent::new(TicTacToeCell::X);
-    let mut engine = IOEngine::new(TicTacToeCell::O, opponent, board);
+    let options = parse_options(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        process::exit(2)
+    });
+    if options.uva {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        if let Err(e) = validity::check_uva(stdin.lock(), &mut stdout.lock()) {
+            eprintln!("Couldn't check grids: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(ref path) = options.replay {
+        if let Err(e) = read_record(path).and_then(|record| engine::replay(&record)) {
+            eprintln!("Couldn't replay {}: {}", path, e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let board = TicTacToeBoard::with_size(options.rows, options.cols, options.k);
+    let seed = options.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.subsec_nanos() as usize).unwrap_or(0)
+    });
+
+    if options.host.is_some() || options.join.is_some() {
+        play_over_network(&options, board, seed);
+        return;
+    }
+
+    let opponent = make_agent(&options.opponent, TicTacToeCell::X, &options, seed);
+
+    match options.versus {
+        Some(ref versus) => {
+            let other = make_agent(versus, TicTacToeCell::O, &options, seed.wrapping_add(1));
+            let result = TournamentEngine::new(opponent, TicTacToeCell::X, other, board).play_tournament(options.games);
+
+            if options.log {
+                for (i, game) in result.games.iter().enumerate() {
+                    println!("game {}: {}", i + 1, game);
+                }
+            }
+            println!("{} (X): {}", options.opponent, result.standing(Side::One));
+            println!("{} (O): {}", versus, result.standing(Side::Two));
+        },
+        None => {
+            let mut engine = match options.load {
+                Some(ref path) => {
+                    let engine = read_record(path).and_then(|record| IOEngine::load(TicTacToeCell::O, opponent, record));
+                    engine.unwrap_or_else(|e| {
+                        eprintln!("Couldn't load {}: {}", path, e);
+                        process::exit(1)
+                    })
+                },
+                None => IOEngine::new(TicTacToeCell::O, opponent, board),
+            };
+            engine.set_opponent_name(&options.opponent);
+            engine.resume();
 
-    engine.execute(false);
+            if let Some(ref path) = options.save {
+                if let Err(e) = engine.save(path) {
+                    eprintln!("Couldn't save {}: {}", path, e);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,364 @@
+use board::{TicTacToeCell, TicTacToeBoard};
+
+use std::fmt;
+
+/// A game written down move by move, a minimal PGN for m,n,k games:
+///
+/// ```text
+/// [Size "3x3"]
+/// [K "3"]
+/// [X "minimax"]
+/// [O "human"]
+/// [Result "X"]
+///
+/// 1. Xb2 Oa1 2. Xc3 Oc1 3. Xa3
+/// ```
+///
+/// Cells are a column letter followed by a 1-based row, with `a1` in the top
+/// left, so boards are limited to 26 columns. Either piece may move first.
+/// A `Setup` tag such as `[Setup "Xb2 Oa1"]` lists the pieces already on the
+/// board before the first move, and is left out for an empty board. `Result` is `X`, `O`, `draw` or `*` for an unfinished game; move numbers
+/// are optional when reading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub rows : usize,
+    pub cols : usize,
+    pub k : usize,
+    pub x_player : String,
+    pub o_player : String,
+    /// The pieces on the board before the first move.
+    pub setup : Vec<(TicTacToeCell, usize)>,
+    pub moves : Vec<(TicTacToeCell, usize)>,
+    /// The stated result, with `has_anyone_won` semantics.
+    pub result : Option<TicTacToeCell>
+}
+
+const MAX_COLS : usize = 26;
+
+impl Record {
+    /// A record of a game carrying on from `board`, with no moves yet.
+    pub fn new(board : &TicTacToeBoard, x_player : &str, o_player : &str) -> Self {
+        let setup = board.iter()
+            .enumerate()
+            .filter(|&(_, &piece)| piece != TicTacToeCell::Empty)
+            .map(|(cell, &piece)| (piece, cell))
+            .collect();
+        Record {
+            rows : board.rows(),
+            cols : board.cols(),
+            k : board.k(),
+            x_player : x_player.to_string(),
+            o_player : o_player.to_string(),
+            setup,
+            moves : vec![],
+            result : board.has_anyone_won()
+        }
+    }
+
+    /// Records `piece` playing `cell`, updating the result from `board`, the
+    /// position after the move.
+    pub fn push(&mut self, piece : TicTacToeCell, cell : usize, board : &TicTacToeBoard) {
+        self.moves.push((piece, cell));
+        self.result = board.has_anyone_won();
+    }
+
+    /// The piece due to move next, or `None` before the first move.
+    pub fn next_to_move(&self) -> Option<TicTacToeCell> {
+        self.moves.last().map(|&(piece, _)| match piece {
+            TicTacToeCell::X => TicTacToeCell::O,
+            _ => TicTacToeCell::X,
+        })
+    }
+
+    /// The position before the first move, or an error if the board size or
+    /// the setup is impossible.
+    pub fn starting_position(&self) -> Result<TicTacToeBoard, String> {
+        if self.rows == 0 || self.cols == 0 || self.cols > MAX_COLS {
+            return Err(format!("{}x{} is not a supported board size", self.rows, self.cols));
+        }
+        if self.k == 0 || (self.k > self.rows && self.k > self.cols) {
+            return Err(format!("{} in a row doesn't fit on a {}x{} board", self.k, self.rows, self.cols));
+        }
+
+        let mut board = TicTacToeBoard::with_size(self.rows, self.cols, self.k);
+        for &(piece, cell) in &self.setup {
+            if piece == TicTacToeCell::Empty || cell >= board.len() {
+                return Err(format!("the setup places {} on cell {}", piece, cell));
+            }
+            if board[cell] != TicTacToeCell::Empty {
+                return Err(format!("the setup places two pieces on {}", cell_name(cell, self.cols)));
+            }
+            board[cell] = piece;
+        }
+        Ok(board)
+    }
+
+    /// Replays the moves, returning the position after each one, or an error
+    /// if any move is impossible or the stated result doesn't match the final
+    /// position.
+    pub fn positions(&self) -> Result<Vec<TicTacToeBoard>, String> {
+        let mut board = self.starting_position()?;
+        let mut positions = Vec::with_capacity(self.moves.len());
+        let mut previous : Option<TicTacToeCell> = None;
+
+        for (number, &(piece, cell)) in self.moves.iter().enumerate() {
+            let number = number + 1;
+            if board.has_anyone_won() != Some(TicTacToeCell::Empty) {
+                return Err(format!("move {} is played after the game has ended", number));
+            }
+            if piece == TicTacToeCell::Empty {
+                return Err(format!("move {} has no piece", number));
+            }
+            if previous == Some(piece) {
+                return Err(format!("move {}: {} moves twice in a row", number, piece));
+            }
+            if cell >= board.len() {
+                return Err(format!("move {}: cell {} is off the board", number, cell));
+            }
+            if board[cell] != TicTacToeCell::Empty {
+                return Err(format!("move {}: {} is already taken", number, cell_name(cell, self.cols)));
+            }
+
+            board[cell] = piece;
+            previous = Some(piece);
+            positions.push(board.clone());
+        }
+
+        let actual = board.has_anyone_won();
+        if actual != self.result {
+            return Err(format!("the result is given as {} but the moves end {}", result_name(self.result), result_name(actual)));
+        }
+        Ok(positions)
+    }
+
+    /// The final position, if the record is valid.
+    pub fn validate(&self) -> Result<TicTacToeBoard, String> {
+        let positions = self.positions()?;
+        match positions.last() {
+            Some(board) => Ok(board.clone()),
+            None => self.starting_position(),
+        }
+    }
+
+    /// Reads a record, checking only its syntax; use `validate` to check the
+    /// game itself.
+    pub fn parse(text : &str) -> Result<Record, String> {
+        let mut size = None;
+        let mut k = None;
+        let mut x_player = "?".to_string();
+        let mut o_player = "?".to_string();
+        let mut result = None;
+        let mut setup = None;
+        let mut moves = vec![];
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line_number = line_number + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line).ok_or_else(|| format!("line {}: malformed tag {}", line_number, line))?;
+                match name {
+                    "Size" => size = Some(parse_size(value).ok_or_else(|| format!("line {}: {} is not a size like 3x3", line_number, value))?),
+                    "K" => k = Some(value.parse::<usize>().map_err(|_| format!("line {}: {} is not a valid k", line_number, value))?),
+                    "X" => x_player = value.to_string(),
+                    "O" => o_player = value.to_string(),
+                    "Setup" => setup = Some((line_number, value.to_string())),
+                    "Result" => result = Some(parse_result(value).ok_or_else(|| format!("line {}: {} is not a result", line_number, value))?),
+                    // unknown tags are kept for other tools, as in PGN
+                    _ => (),
+                }
+                continue;
+            }
+
+            let (rows, cols) = size.ok_or_else(|| format!("line {}: moves before the Size tag", line_number))?;
+            for token in line.split_whitespace() {
+                if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                let cell = parse_move(token, rows, cols).ok_or_else(|| format!("line {}: {} is not a move", line_number, token))?;
+                moves.push(cell);
+            }
+        }
+
+        let (rows, cols) = size.ok_or("record has no Size tag")?;
+        let setup = match setup {
+            Some((line_number, value)) => value.split_whitespace()
+                .map(|token| parse_move(token, rows, cols).ok_or_else(|| format!("line {}: {} is not a piece on a cell", line_number, token)))
+                .collect::<Result<Vec<_>, String>>()?,
+            None => vec![],
+        };
+        Ok(Record {
+            rows,
+            cols,
+            k : k.ok_or("record has no K tag")?,
+            x_player,
+            o_player,
+            setup,
+            moves,
+            result : result.ok_or("record has no Result tag")?
+        })
+    }
+}
+
+fn cell_name(cell : usize, cols : usize) -> String {
+    format!("{}{}", (b'a' + (cell % cols) as u8) as char, cell / cols + 1)
+}
+
+fn result_name(result : Option<TicTacToeCell>) -> &'static str {
+    match result {
+        Some(TicTacToeCell::X) => "X",
+        Some(TicTacToeCell::O) => "O",
+        Some(TicTacToeCell::Empty) => "*",
+        None => "draw",
+    }
+}
+
+fn parse_result(value : &str) -> Option<Option<TicTacToeCell>> {
+    match value {
+        "X" => Some(Some(TicTacToeCell::X)),
+        "O" => Some(Some(TicTacToeCell::O)),
+        "*" => Some(Some(TicTacToeCell::Empty)),
+        "draw" => Some(None),
+        _ => None,
+    }
+}
+
+fn parse_tag(line : &str) -> Option<(&str, &str)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let space = inner.find(' ')?;
+    let (name, value) = (&inner[..space], inner[space..].trim());
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some((name, value))
+}
+
+fn parse_size(value : &str) -> Option<(usize, usize)> {
+    let mut parts = value.splitn(2, 'x').map(|part| part.parse::<usize>().ok());
+    match (parts.next()??, parts.next()??) {
+        (rows, cols) if rows > 0 && cols > 0 && cols <= MAX_COLS => Some((rows, cols)),
+        _ => None,
+    }
+}
+
+fn parse_move(token : &str, rows : usize, cols : usize) -> Option<(TicTacToeCell, usize)> {
+    let mut chars = token.chars();
+    let piece = match chars.next()? {
+        'X' => TicTacToeCell::X,
+        'O' => TicTacToeCell::O,
+        _ => return None,
+    };
+    let col = chars.next()?;
+    if !col.is_ascii_lowercase() {
+        return None;
+    }
+    let col = col as usize - 'a' as usize;
+    let row = chars.as_str().parse::<usize>().ok()?;
+
+    if col < cols && row > 0 && row <= rows {
+        Some((piece, (row - 1) * cols + col))
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Size \"{}x{}\"]", self.rows, self.cols)?;
+        writeln!(f, "[K \"{}\"]", self.k)?;
+        if !self.setup.is_empty() {
+            let pieces : Vec<String> = self.setup.iter().map(|&(piece, cell)| format!("{}{}", piece, cell_name(cell, self.cols))).collect();
+            writeln!(f, "[Setup \"{}\"]", pieces.join(" "))?;
+        }
+        writeln!(f, "[X \"{}\"]", self.x_player)?;
+        writeln!(f, "[O \"{}\"]", self.o_player)?;
+        writeln!(f, "[Result \"{}\"]", result_name(self.result))?;
+        writeln!(f)?;
+
+        for (i, pair) in self.moves.chunks(2).enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}.", i + 1)?;
+            for &(piece, cell) in pair {
+                write!(f, " {}{}", piece, cell_name(cell, self.cols))?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME : &str = "[Size \"3x3\"]
+[K \"3\"]
+[X \"minimax\"]
+[O \"human\"]
+[Result \"X\"]
+
+1. Xb2 Oa1 2. Xc3 Oc1 3. Xb1 Oa3 4. Xb3
+";
+
+    #[test]
+    fn round_trips_through_text() {
+        let record = Record::parse(GAME).unwrap();
+        assert_eq!(record.moves[0], (TicTacToeCell::X, 4));
+        assert_eq!(record.moves[3], (TicTacToeCell::O, 2));
+        assert_eq!(record.x_player, "minimax");
+        assert_eq!(record.next_to_move(), Some(TicTacToeCell::O));
+        assert_eq!(record.to_string(), GAME);
+
+        let board = record.validate().unwrap();
+        assert_eq!(board.to_string(), "[O, X, O; _, X, _; O, X, X]");
+    }
+
+    #[test]
+    fn builds_records_as_a_game_is_played() {
+        let mut board = TicTacToeBoard::with_size(15, 15, 5);
+        let mut record = Record::new(&board, "mcts", "human");
+        for &(piece, cell) in &[(TicTacToeCell::O, 112), (TicTacToeCell::X, 224)] {
+            board[cell] = piece;
+            record.push(piece, cell, &board);
+        }
+
+        let text = record.to_string();
+        assert!(text.ends_with("1. Oh8 Xo15\n"));
+        assert_eq!(Record::parse(&text).unwrap().validate().unwrap(), board);
+    }
+
+    #[test]
+    fn carries_on_from_a_starting_position() {
+        let mut board = TicTacToeBoard::new();
+        board[4] = TicTacToeCell::X;
+        board[0] = TicTacToeCell::O;
+        let mut record = Record::new(&board, "human", "agent");
+        assert_eq!(record.validate().unwrap(), board);
+
+        board[8] = TicTacToeCell::X;
+        record.push(TicTacToeCell::X, 8, &board);
+        let text = record.to_string();
+        assert!(text.contains("[Setup \"Oa1 Xb2\"]\n"));
+        assert_eq!(Record::parse(&text).unwrap(), record);
+        assert_eq!(record.validate().unwrap(), board);
+
+        assert!(Record::parse(&text.replace("Oa1 Xb2", "Oa1 Xa1")).unwrap().validate().unwrap_err().contains("two pieces"));
+        assert!(Record::parse(&text.replace("Oa1 Xb2", "Oa1 Xb2 Oc3")).unwrap().validate().unwrap_err().contains("already taken"));
+    }
+
+    #[test]
+    fn rejects_impossible_games() {
+        let replace = |from : &str, to : &str| Record::parse(&GAME.replace(from, to)).unwrap().validate();
+
+        assert!(replace("Oa1", "Ob2").unwrap_err().contains("already taken"));
+        assert!(replace("Oa1", "Xa1").unwrap_err().contains("twice in a row"));
+        assert!(replace("4. Xb3", "4. Xb3 Oc2").unwrap_err().contains("after the game has ended"));
+        assert!(replace("[Result \"X\"]", "[Result \"draw\"]").unwrap_err().contains("result"));
+
+        assert!(Record::parse(&GAME.replace("Xc3", "Xd3")).is_err());
+        assert!(Record::parse(&GAME.replace("[K \"3\"]\n", "")).is_err());
+    }
+}
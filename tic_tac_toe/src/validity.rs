@@ -0,0 +1,163 @@
+use board::{TicTacToeCell, TicTacToeBoard};
+
+use std::io::{BufRead, Write};
+
+/// Why a position could not have come up in a legal game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invalid {
+    /// The piece that moves first must have as many pieces as the other, or one more.
+    MoveCount { first : usize, second : usize },
+    BothWon,
+    /// The winner has the wrong number of pieces to have just moved.
+    WinnerDidNotMoveLast(TicTacToeCell),
+    /// No single move by the winner completes all of their lines, so the game
+    /// would already have been over before it.
+    PlayedOnAfterWin(TicTacToeCell)
+}
+
+fn count(board : &TicTacToeBoard, piece : TicTacToeCell) -> usize {
+    board.iter().filter(|&&cell| cell == piece).count()
+}
+
+fn has_won(board : &TicTacToeBoard, piece : TicTacToeCell) -> bool {
+    (0..board.len()).any(|cell| board[cell] == piece && board.wins_through(cell))
+}
+
+/// Checks `board` could be reached in legal play with `first` moving first
+/// (UVA 10363 is the 3x3 case with X first).
+pub fn check(board : &TicTacToeBoard, first : TicTacToeCell) -> Result<(), Invalid> {
+    let second = first.opponent();
+    let (first_count, second_count) = (count(board, first), count(board, second));
+    if first_count != second_count && first_count != second_count + 1 {
+        return Err(Invalid::MoveCount { first : first_count, second : second_count });
+    }
+
+    let winners : Vec<TicTacToeCell> = [first, second].iter().cloned().filter(|&piece| has_won(board, piece)).collect();
+    let winner = match winners.len() {
+        0 => return Ok(()),
+        1 => winners[0],
+        _ => return Err(Invalid::BothWon),
+    };
+
+    let moved_last = if first_count > second_count { first } else { second };
+    if winner != moved_last {
+        return Err(Invalid::WinnerDidNotMoveLast(winner));
+    }
+
+    let mut before = board.clone();
+    for cell in 0..board.len() {
+        if board[cell] != winner {
+            continue;
+        }
+        before[cell] = TicTacToeCell::Empty;
+        let undone = !has_won(&before, winner);
+        before[cell] = winner;
+        if undone {
+            return Ok(());
+        }
+    }
+    Err(Invalid::PlayedOnAfterWin(winner))
+}
+
+/// Reads a grid of `X`, `O` and `.` rows into a board, `k` in a row to win.
+pub fn parse_grid(lines : &[&str], k : usize) -> Result<TicTacToeBoard, String> {
+    let cols = lines.first().map(|line| line.chars().count()).unwrap_or(0);
+    if cols == 0 || lines.iter().any(|line| line.chars().count() != cols) {
+        return Err(format!("grid rows must all be the same non-zero width: {:?}", lines));
+    }
+    if k == 0 || (k > lines.len() && k > cols) {
+        return Err(format!("{} in a row doesn't fit on a {}x{} grid", k, lines.len(), cols));
+    }
+
+    let mut board = TicTacToeBoard::with_size(lines.len(), cols, k);
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let index = board.index_of(row, col);
+            board[index] = match c {
+                'X' => TicTacToeCell::X,
+                'O' => TicTacToeCell::O,
+                '.' => TicTacToeCell::Empty,
+                _ => return Err(format!("{} is not X, O or . in {}", c, line)),
+            };
+        }
+    }
+    Ok(board)
+}
+
+/// Answers UVA 10363: a count of test cases followed by that many 3x3 grids,
+/// optionally separated by blank lines, printing `yes` or `no` for each.
+pub fn check_uva<R : BufRead, W : Write>(input : R, output : &mut W) -> Result<(), String> {
+    let mut lines = vec![];
+    for line in input.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim().to_string();
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    let cases = lines.first().ok_or("input is empty")?;
+    let cases = cases.parse::<usize>().map_err(|_| format!("{} is not a number of test cases", cases))?;
+    let grids = &lines[1..];
+    if grids.len() != cases * 3 {
+        return Err(format!("expected {} grids of 3 rows but found {} rows", cases, grids.len()));
+    }
+
+    for grid in grids.chunks(3) {
+        let rows : Vec<&str> = grid.iter().map(|row| row.as_str()).collect();
+        let board = parse_grid(&rows, 3)?;
+        if board.cols() != 3 {
+            return Err(format!("grids must be 3x3: {:?}", rows));
+        }
+        let answer = if check(&board, TicTacToeCell::X).is_ok() { "yes" } else { "no" };
+        writeln!(output, "{}", answer).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows : &[&str]) -> TicTacToeBoard {
+        parse_grid(rows, 3).unwrap()
+    }
+
+    #[test]
+    fn accepts_reachable_positions() {
+        assert_eq!(check(&grid(&["...", "...", "..."]), TicTacToeCell::X), Ok(()));
+        assert_eq!(check(&grid(&["X.O", "OO.", "XXX"]), TicTacToeCell::X), Ok(()));
+        // X completes two lines with one move
+        assert_eq!(check(&grid(&["XXX", "OXO", "XOO"]), TicTacToeCell::X), Ok(()));
+        assert_eq!(check(&grid(&["O..", "...", "..."]), TicTacToeCell::O), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unreachable_positions() {
+        assert_eq!(check(&grid(&["OO.", "...", "..."]), TicTacToeCell::X), Err(Invalid::MoveCount { first : 0, second : 2 }));
+        assert_eq!(check(&grid(&["XXX", "OOO", "..."]), TicTacToeCell::X), Err(Invalid::BothWon));
+        assert_eq!(check(&grid(&["O.X", "XX.", "OOO"]), TicTacToeCell::X), Err(Invalid::MoveCount { first : 3, second : 4 }));
+        assert_eq!(check(&grid(&["XXX", "OO.", "O.."]), TicTacToeCell::X), Err(Invalid::WinnerDidNotMoveLast(TicTacToeCell::X)));
+    }
+
+    #[test]
+    fn rejects_play_after_a_win_on_larger_boards() {
+        // two separate lines of four: the first must have ended the game
+        let board = parse_grid(&["XXXX.", ".....", "XXXX.", "OOOOO", "OOO.."], 4).unwrap();
+        assert_eq!(check(&board, TicTacToeCell::X), Err(Invalid::BothWon));
+
+        let board = parse_grid(&["XXXX.", ".....", "XXXX.", "OOO.O", "OOO.."], 4).unwrap();
+        assert_eq!(check(&board, TicTacToeCell::X), Err(Invalid::PlayedOnAfterWin(TicTacToeCell::X)));
+    }
+
+    #[test]
+    fn answers_uva_input() {
+        let input = "3\nX.O\nOO.\nXXX\n\nO.X\nXX.\nOOO\n\nXXX\nOOO\n...\n";
+        let mut output = vec![];
+        check_uva(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "yes\nno\nno\n");
+
+        assert!(check_uva("2\nX..\n...\n...\n".as_bytes(), &mut vec![]).is_err());
+    }
+}